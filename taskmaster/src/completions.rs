@@ -0,0 +1,103 @@
+use clap::Command;
+use clap_complete::{Shell, generate};
+use std::io::{self, Write};
+use tasks::TaskError;
+
+/// the hidden subcommand that prints `<id>\t<description>` per task for the completion scripts
+pub const COMPLETE_IDS_COMMAND: &str = "_complete-ids";
+
+/* names of the first positional argument of subcommands that take a task ID */
+const ID_ARGUMENTS: &[&str] = &["id", "id_arg", "id1"];
+
+/// Prints the completion script for the shell. With dynamic_ids, the task ID argument of
+/// complete, delete, change etc. is completed with the IDs (and descriptions) of the current
+/// task list, which the script gets from `taskmaster _complete-ids`.
+pub fn print_completions(
+    command: &mut Command,
+    shell: Shell,
+    dynamic_ids: bool,
+) -> Result<(), TaskError> {
+    let name = command.get_name().to_string();
+    let mut script = Vec::new();
+    generate(shell, command, &name, &mut script);
+    if dynamic_ids {
+        let commands = id_commands(command);
+        script.extend_from_slice(dynamic_ids_script(shell, &name, &commands)?.as_bytes());
+    }
+    io::stdout().write_all(&script)?;
+    Ok(())
+}
+
+/* the subcommands (with their aliases) whose first positional argument is a task ID */
+fn id_commands(command: &Command) -> Vec<String> {
+    command
+        .get_subcommands()
+        .filter(|sub| {
+            sub.get_positionals()
+                .next()
+                .is_some_and(|arg| ID_ARGUMENTS.contains(&arg.get_id().as_str()))
+        })
+        .flat_map(|sub| {
+            std::iter::once(sub.get_name().to_string())
+                .chain(sub.get_all_aliases().map(str::to_string))
+        })
+        .collect()
+}
+
+/* Added after the generated script: bash and zsh get a function that completes IDs right after
+ * an ID command and leaves everything else to the generated function, fish just gets one more
+ * completion rule */
+fn dynamic_ids_script(shell: Shell, name: &str, commands: &[String]) -> Result<String, TaskError> {
+    let ids = format!("{} {} 2>/dev/null", name, COMPLETE_IDS_COMMAND);
+    let function = format!("_{}", name.replace('-', "__"));
+    let script = match shell {
+        Shell::Bash => format!(
+            r#"
+_{name}_dynamic_ids() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    if [[ ${{COMP_CWORD}} -ge 2 && "${{cur}}" != -* ]]; then
+        case "${{COMP_WORDS[COMP_CWORD-1]}}" in
+            {cases})
+                COMPREPLY=( $(compgen -W "$( {ids} | cut -f1 )" -- "${{cur}}") )
+                return 0
+                ;;
+        esac
+    fi
+    {function} "$@"
+}}
+complete -F _{name}_dynamic_ids -o nosort -o bashdefault -o default {name}
+"#,
+            cases = commands.join("|"),
+        ),
+        Shell::Zsh => format!(
+            r#"
+_{name}_dynamic_ids() {{
+    if [[ ${{words[CURRENT]}} != -* ]]; then
+        case ${{words[CURRENT-1]}} in
+            ({cases})
+                local -a task_ids
+                task_ids=(${{(f)"$( {ids} | tr '\t' ':' )"}})
+                _describe -t tasks 'task' task_ids
+                return
+                ;;
+        esac
+    fi
+    {function} "$@"
+}}
+compdef _{name}_dynamic_ids {name}
+"#,
+            cases = commands.join("|"),
+        ),
+        Shell::Fish => format!(
+            "\ncomplete -c {name} -n \"__fish_seen_subcommand_from {commands}\" -f -a \"({ids})\"\n",
+            commands = commands.join(" "),
+        ),
+        other => {
+            return Err(TaskError::NotSupported(format!(
+                "completing task IDs in {} (only bash, zsh and fish)",
+                other
+            )));
+        }
+    };
+    Ok(script)
+}
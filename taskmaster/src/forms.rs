@@ -0,0 +1,111 @@
+use chrono::NaiveDate;
+use colored::Colorize;
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
+use std::fmt::Display;
+use std::str::FromStr;
+use tasks::{Priority, Task, TaskError, Theme};
+
+/// Asks for the values of a command one field at a time (`add -i`, `complete -i`, ...).
+/// Ctrl-C or Ctrl-D in any field cancels the whole form with TaskError::InputCancelled.
+pub struct FieldPrompt {
+    ed: DefaultEditor,
+}
+
+/// what `add -i` asks for; description is always set, the other fields are optional
+pub struct AddForm {
+    pub description: String,
+    pub priority: Option<Priority>,
+    pub due: Option<NaiveDate>,
+}
+
+impl FieldPrompt {
+    pub fn new() -> Result<Self, TaskError> {
+        Ok(FieldPrompt {
+            ed: DefaultEditor::new()?,
+        })
+    }
+
+    /// one line of input, trimmed; may be empty
+    pub fn ask(&mut self, label: &str) -> Result<String, TaskError> {
+        match self.ed.readline(&format!("{}: ", label.bold())) {
+            Ok(line) => Ok(line.trim().to_string()),
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => {
+                Err(TaskError::InputCancelled)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// asks again until the input isn't empty
+    pub fn ask_required(&mut self, label: &str) -> Result<String, TaskError> {
+        loop {
+            let input = self.ask(label)?;
+            if !input.is_empty() {
+                return Ok(input);
+            }
+            println!("{}", format!("{} needs a value", label).yellow());
+        }
+    }
+
+    /// Parses the input; empty input gives None, invalid input is asked for again
+    pub fn ask_parsed<T>(&mut self, label: &str) -> Result<Option<T>, TaskError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        loop {
+            let input = self.ask(label)?;
+            if input.is_empty() {
+                return Ok(None);
+            }
+            match input.parse() {
+                Ok(value) => return Ok(Some(value)),
+                Err(e) => println!("{}", e.to_string().yellow()),
+            }
+        }
+    }
+
+    /// Lists the tasks and asks for the ID of one of them until a listed ID is entered
+    pub fn ask_task_id(
+        &mut self,
+        label: &str,
+        tasks: &[&Task],
+        theme: &Theme,
+    ) -> Result<usize, TaskError> {
+        if tasks.is_empty() {
+            return Err(TaskError::Empty("Task list".to_string()));
+        }
+        for task in tasks {
+            println!(
+                "{:>4}: {} {}",
+                task.get_id(),
+                task.get_priority(theme),
+                task.get_description()
+            );
+        }
+        loop {
+            match self.ask_parsed::<usize>(label)? {
+                Some(id) if tasks.iter().any(|t| t.get_id() == id) => return Ok(id),
+                Some(id) => println!("{}", format!("#{} is not in the list", id).yellow()),
+                None => {}
+            }
+        }
+    }
+
+    /// The fields of a new task. The description is only asked for if none is given;
+    /// priority and due date can be skipped with Enter.
+    pub fn add_form(&mut self, description: Option<String>) -> Result<AddForm, TaskError> {
+        let description = match description {
+            Some(description) => description,
+            None => self.ask_required("Description")?,
+        };
+        let priority = self.ask_parsed("Priority (low, medium, high) [medium]")?;
+        let due = self.ask_parsed("Due date (YYYY-MM-DD) [none]")?;
+        Ok(AddForm {
+            description,
+            priority,
+            due,
+        })
+    }
+}
@@ -1,22 +1,99 @@
+use crate::task_helper::{COMMANDS, TaskHelper, split_commands};
+use chrono::NaiveDate;
 use colored::Colorize;
+use rustyline::Editor;
 use rustyline::error::ReadlineError;
-use rustyline::DefaultEditor;
+use rustyline::history::{DefaultHistory, History};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
-use tasks::{TaskError, TaskManager};
+use std::process::Command;
+use tasks::{
+    COMMAND_NAMES, GroupBy, Priority, Task, TaskError, TaskFilter, TaskManager, TaskSnapshot,
+    util::{parse_datetime, parse_duration},
+};
+use tempfile::NamedTempFile;
+
+/// editor used if $EDITOR isn't set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// number of entries `history` shows without an argument
+const DEFAULT_HISTORY_ENTRIES: usize = 20;
+
+/// width of the lines around the output of a `!` shell command
+const SHELL_SEPARATOR_LEN: usize = 40;
+
+/// Opens the text in $EDITOR (via a temporary file) and returns the edited text, with lines
+/// joined by spaces. None means cancelled: the editor failed or the text was emptied.
+fn edit_in_editor(text: &str) -> Result<Option<String>, TaskError> {
+    let mut file = NamedTempFile::new()?;
+    writeln!(file, "{}", text)?;
+    file.flush()?;
+
+    /* $EDITOR may contain arguments, e.g. "code --wait" */
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+    let status = Command::new(program)
+        .args(parts)
+        .arg(file.path())
+        .status()?;
+    if !status.success() {
+        eprintln!(
+            "{}",
+            format!("Editor exited with {}, discarding the changes.", status).yellow()
+        );
+        return Ok(None);
+    }
+
+    let edited = fs::read_to_string(file.path())?;
+    let joined = edited
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(if joined.is_empty() {
+        None
+    } else {
+        Some(joined)
+    })
+}
+
+/// how many changes undo and redo remember
+const UNDO_LIMIT: usize = 20;
+
+/// minimum similarity for a task to be suggested when a task couldn't be found
+const SUGGESTION_MIN_SCORE: f32 = 0.2;
+
+/* how deep scripts may run other scripts */
+const SCRIPT_DEPTH_LIMIT: usize = 16;
 
 /* this structure has a lifetime parameter - for the duration of its lifetime, there is a mutable
  * borrow of a reference to a TaskManager */
 pub struct InteractiveMode<'a> {
     manager: &'a mut TaskManager,
-    ed: DefaultEditor,
+    ed: Editor<TaskHelper, DefaultHistory>,
     history_path: Option<PathBuf>,
+    /* command shortcuts: from the config plus the ones defined with 'alias' in this session */
+    aliases: HashMap<String, String>,
+    /* IDs chosen with 'select', used by commands given --selected */
+    selected_ids: HashSet<usize>,
+    /* the task lists before the last changes (newest at the back), for undo */
+    undo_stack: VecDeque<TaskSnapshot>,
+    /* the task lists undo replaced, for redo; emptied by every new change */
+    redo_stack: VecDeque<TaskSnapshot>,
+    /* the scripts that are running right now (outermost first), so none can run itself */
+    open_scripts: Vec<PathBuf>,
 }
 
 impl<'a> InteractiveMode<'a> {
     /// The new method can fail
     pub fn new(manager: &'a mut TaskManager) -> Result<Self, TaskError> {
         // 1. create a new Editor instance
-        let mut rl = DefaultEditor::new()?;
+        let mut rl = Editor::<TaskHelper, DefaultHistory>::new()?;
+        rl.set_helper(Some(TaskHelper::new()));
         // Optional: load history from a file
         let history_path = dirs::home_dir().map(|mut path| {
             path.push(".taskmaster_history");
@@ -24,67 +101,198 @@ impl<'a> InteractiveMode<'a> {
         });
 
         // 2. optionally load history
-        if let Some(path) = &history_path {
-            if rl.load_history(path).is_err() {
-                // ingore if history doesn't exit
-            }
+        if let Some(path) = &history_path
+            && rl.load_history(path).is_err()
+        {
+            // ingore if history doesn't exit
         }
+        let aliases = config_aliases(&manager.get_config().aliases);
         Ok(InteractiveMode {
             manager,
             ed: rl,
             history_path,
+            aliases,
+            selected_ids: HashSet::new(),
+            undo_stack: VecDeque::new(),
+            redo_stack: VecDeque::new(),
+            open_scripts: Vec::new(),
         })
     }
 
     fn print_interactive_help() {
         println!("{}", "\nInteractive Mode Commands:".bold().underline());
-        println!("  {:<25} - {}", "l / list".cyan().bold(), "List all tasks");
         println!(
-            "  {:<25} - {}",
-            "a / add <desc>".cyan().bold(),
-            "Add a new task"
+            "  {:<25} - List all tasks",
+            "l / list [--limit N]".cyan().bold()
+        );
+        println!(
+            "  {:<25} - List page P, N tasks per page",
+            "list --page-size N --page P".cyan().bold()
+        );
+        println!(
+            "  {:<25} - List in sections (priority/status/context)",
+            "list --group-by <G>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Only tasks added since then (also --before, --completed-since/-before)",
+            "list --since <DATE>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - List with the reading time of each task",
+            "list --long".cyan().bold()
+        );
+        println!("  {:<25} - Add a new task", "a / add <desc>".cyan().bold());
+        println!(
+            "  {:<25} - Mark a task as completed",
+            "c / complete <id>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Complete a task and its pending subtasks",
+            "complete <id> --force".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Increase a task's priority",
+            "up / + <id>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Decrease a task's priority",
+            "down / - <id>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Set a task's priority (low/medium/high)",
+            "prio <id> <priority>".cyan().bold()
+        );
+        println!("  {:<25} - Delete a task", "d / delete <id>".cyan().bold());
+        println!(
+            "  {:<25} - Change a task's description",
+            "ch / change <id> <desc>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Edit a description in $EDITOR",
+            "e / edit <id>".cyan().bold()
         );
         println!(
-            "  {:<25} - {}",
-            "c / complete <id>".cyan().bold(),
-            "Mark a task as completed"
+            "  {:<25} - Write a new task in $EDITOR",
+            "e / edit".cyan().bold()
         );
         println!(
-            "  {:<25} - {}",
-            "up / + <id>".cyan().bold(),
-            "Increase a task's priority"
+            "  {:<25} - Replace text in all descriptions",
+            "replace <old> <new>".cyan().bold()
         );
         println!(
-            "  {:<25} - {}",
-            "down / - <id>".cyan().bold(),
-            "Decrease a task's priority"
+            "  {:<25} - Same with a regular expression",
+            "replace --regex <re> <new>".cyan().bold()
         );
         println!(
-            "  {:<25} - {}",
-            "d / delete <id>".cyan().bold(),
-            "Delete a task"
+            "  {:<25} - Exchange the positions of two tasks",
+            "swap <id1> <id2>".cyan().bold()
         );
         println!(
-            "  {:<25} - {}",
-            "ch / change <id> <desc>".cyan().bold(),
-            "Change a task's description"
+            "  {:<25} - Select tasks with ↑/↓ and space",
+            "sel / select".cyan().bold()
         );
         println!(
-            "  {:<25} - {}",
-            "clr / clear".cyan().bold(),
-            "Clear all completed tasks"
+            "  {:<25} - Complete/delete/up/down/prio/pin them",
+            "<command> --selected".cyan().bold()
         );
         println!(
-            "  {:<25} - {}",
-            "h / help / ?".yellow().bold(),
-            "Show this help message"
+            "  {:<25} - Forget the selection",
+            "select --clear".cyan().bold()
         );
         println!(
-            "  {:<25} - {}",
-            "q / quit / x / exit".red().bold(),
-            "Exit interactive mode"
+            "  {:<25} - Find tasks by description",
+            "f / find [--fuzzy] <text>".cyan().bold()
         );
-        println!("");
+        println!("  {:<25} - Show today's tasks", "today".cyan().bold());
+        println!(
+            "  {:<25} - Pick a random pending task",
+            "random [--weighted]".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Show tasks untouched for N days (or e.g. 2w, 3m)",
+            "stale [N]".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Show the last N commands (default 20)",
+            "hist / history [N]".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Forget all commands",
+            "history clear".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Pin a task to the top of the list",
+            "pin <id>".cyan().bold()
+        );
+        println!("  {:<25} - Unpin a task", "unpin <id>".cyan().bold());
+        println!(
+            "  {:<25} - Star a task to keep an eye on it",
+            "* / mark <id>".cyan().bold()
+        );
+        println!("  {:<25} - Remove the star", "unmark <id>".cyan().bold());
+        println!(
+            "  {:<25} - Show all command aliases",
+            "alias [list]".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Define an alias for this session",
+            "alias <name> <command>".cyan().bold()
+        );
+        println!("  {:<25} - Remove an alias", "unalias <name>".cyan().bold());
+        println!(
+            "  {:<25} - Attach a link to a task",
+            "link <id> <url>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Remove a task's link",
+            "unlink <id>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Hide a task until the day (YYYY-MM-DD)",
+            "snooze <id> <date>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Show a snoozed task again",
+            "unsnooze <id>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Open a task's link in the browser",
+            "open <id>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Clear all completed tasks",
+            "clr / clear".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Delete duplicate tasks (--fuzzy [T]: similar ones too)",
+            "dedup [--fuzzy]".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Run the commands in a file",
+            "script <file>".cyan().bold()
+        );
+        println!("  {:<25} - Take back the last change", "undo".cyan().bold());
+        println!(
+            "  {:<25} - Make the last undone change again",
+            "r / redo".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Run several commands one after another",
+            "<cmd>; <cmd>; ...".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Run a shell command, e.g. !git status",
+            "!<shell command>".cyan().bold()
+        );
+        println!(
+            "  {:<25} - Show this help message",
+            "h / help / ?".yellow().bold()
+        );
+        println!(
+            "  {:<25} - Exit interactive mode",
+            "q / quit / x / exit".red().bold()
+        );
+        println!();
     }
 
     pub fn start_interactive_mode(&mut self) -> Result<(), TaskError> {
@@ -106,57 +314,230 @@ impl<'a> InteractiveMode<'a> {
                 }
             };
 
-            if input.is_empty() {
-                continue; // empty input just shows the prompt again
+            /* the whole rest of the line goes to the shell, ';' included */
+            if let Some(shell_command) = input.trim_start().strip_prefix('!') {
+                if let Err(e) = run_shell_command(shell_command) {
+                    eprintln!("{}", e.to_string().red());
+                }
+                continue;
+            }
+            if self.run_commands(&input) {
+                break;
             }
+        }
+        // Optional: save history to a file before exiting
+        if let Some(path) = &self.history_path
+            && let Err(err) = self.ed.save_history(path)
+        {
+            eprintln!("{}", format!("Error saving history: {:?}", err).red());
+        }
+        Ok(())
+    }
 
-            /* split input into commmand and arguments */
-            let parts: Vec<&str> = input.split_whitespace().collect();
-            if parts.is_empty() {
-                continue; // ignore empty input
+    /* runs the commands of a line separated by ';' (see parse_command_line) one after another;
+     * errors are printed and don't stop the following commands. Returns true if one of them
+     * leaves interactive mode, the commands after it aren't run. */
+    fn run_commands(&mut self, input: &str) -> bool {
+        for command in parse_command_line(input) {
+            match self.run_command(&command) {
+                Ok(true) => return true,
+                Ok(false) => {}
+                Err(e) => eprintln!("{}", e.to_string().red()),
             }
+        }
+        false
+    }
+
+    /// Executes a single line of input, exactly like typed at the prompt. Returns true if
+    /// interactive mode should be left. Commands given with all their arguments don't read any
+    /// input, so this can be used without a terminal, e.g. run_command("add test task").
+    pub fn run_command(&mut self, input: &str) -> Result<bool, TaskError> {
+        /* an alias replaces the command word; the rest of the line are arguments */
+        let input = self.resolve_alias(input);
+        match input
+            .split_whitespace()
+            .next()
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("undo") => return self.handle_undo().map(|_| false),
+            Some("redo" | "r") => return self.handle_redo().map(|_| false),
+            /* the commands of a script are recorded one by one */
+            Some("script") => return self.dispatch_command(&input),
+            _ => {}
+        }
+        /* every command that changes the tasks can be undone */
+        let before = self.manager.snapshot();
+        let result = self.dispatch_command(&input);
+        if task_list_changed(before.tasks(), self.manager.snapshot().tasks()) {
+            push_limited(&mut self.undo_stack, before);
+            self.redo_stack.clear();
+        }
+        result
+    }
 
-            let command = parts[0].to_lowercase();
-            let args = &parts[1..];
+    /* runs a command whose alias is already resolved */
+    fn dispatch_command(&mut self, input: &str) -> Result<bool, TaskError> {
+        /* split input into commmand and arguments */
+        let parts: Vec<&str> = input.split_whitespace().collect();
+        if parts.is_empty() {
+            return Ok(false); // ignore empty input
+        }
 
-            let cmd_exec_result = match command.as_str() {
-                "l" | "list" => self.handle_list(),
+        let command = parts[0].to_lowercase();
+        let args = &parts[1..];
+        if args.contains(&"--selected") {
+            let rest: Vec<&str> = args
+                .iter()
+                .copied()
+                .filter(|a| *a != "--selected")
+                .collect();
+            self.run_on_selection(&command, &rest)?;
+            return Ok(false);
+        }
+
+        /* commands with all their arguments are run by the manager; if arguments are missing,
+         * the handlers below ask for them */
+        let direct = if COMMAND_NAMES.contains(&command.as_str()) {
+            match self.manager.apply_command_str(input) {
+                Err(TaskError::Empty(_)) => None,
+                Err(TaskError::HasPendingChildren {
+                    parent_id,
+                    pending_child_ids,
+                }) => Some(self.complete_with_subtasks(parent_id, &pending_child_ids)),
+                result => Some(result.map(|msg| println!("{}", msg.green()))),
+            }
+        } else {
+            None
+        };
+        let result = match direct {
+            Some(result) => result,
+            None => match command.as_str() {
+                "l" | "list" => self.handle_list(args),
                 "a" | "add" => self.handle_add(args),
                 "c" | "complete" => self.handle_complete(args),
                 "+" | "up" => self.handle_prio_change(args, true),
                 "-" | "down" => self.handle_prio_change(args, false),
+                "prio" | "priority" => self.handle_set_priority(args),
                 "d" | "delete" => self.handle_delete(args),
                 "ch" | "change" => self.handle_change(args),
+                "swap" => self.handle_swap(args),
+                "sel" | "select" => self.handle_select(args),
+                "pin" => self.handle_pin(args, true),
+                "unpin" => self.handle_pin(args, false),
+                "*" | "mark" => self.handle_mark(args, true),
+                "unmark" => self.handle_mark(args, false),
+                "alias" => self.handle_alias(args),
+                "unalias" => {
+                    let name = args.first().ok_or(TaskError::Empty("Alias".to_string()))?;
+                    match self.aliases.remove(*name) {
+                        Some(_) => println!("Removed alias '{}'.", name),
+                        None => println!("There is no alias '{}'.", name),
+                    }
+                    Ok(())
+                }
+                "e" | "edit" => self.handle_edit(args),
+                "replace" => self.handle_replace(args),
+                "link" => self.handle_link(args),
+                "unlink" => {
+                    let id = self.get_id_arg(args)?;
+                    let msg = self.manager.unlink_task(id)?;
+                    println!("{}", msg.green());
+                    Ok(())
+                }
+                "snooze" => {
+                    let id = self.get_id_arg(args)?;
+                    let date = args.get(1).ok_or(TaskError::Empty("Date".to_string()))?;
+                    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                        TaskError::ArgumentMismatch(format!(
+                            "'{}' is not a date, use YYYY-MM-DD",
+                            date
+                        ))
+                    })?;
+                    let msg = self.manager.snooze_task(id, date)?;
+                    println!("{}", msg.green());
+                    Ok(())
+                }
+                "unsnooze" => {
+                    let id = self.get_id_arg(args)?;
+                    let msg = self.manager.unsnooze_task(id)?;
+                    println!("{}", msg.green());
+                    Ok(())
+                }
+                "open" => {
+                    let id = self.get_id_arg(args)?;
+                    crate::open_url(self.manager.task_url(id)?)
+                }
                 "clr" | "clear" => self.handle_clear(),
+                "dedup" => {
+                    let fuzzy = match flag_value(args, "--fuzzy") {
+                        Some(threshold) => Some(threshold.parse::<f32>().map_err(|_| {
+                            TaskError::ArgumentMismatch(format!(
+                                "'{}' is not a similarity threshold.",
+                                threshold
+                            ))
+                        })?),
+                        None if args.contains(&"--fuzzy") => Some(crate::DEFAULT_FUZZY_THRESHOLD),
+                        None => None,
+                    };
+                    crate::remove_duplicates(self.manager, crate::dedup_strategy(fuzzy)?);
+                    Ok(())
+                }
+                "script" => self.handle_script(args),
+                "f" | "find" => self.handle_find(args),
+                "today" => {
+                    crate::print_today(self.manager);
+                    Ok(())
+                }
+                "random" => {
+                    let priority = flag_value(args, "--priority")
+                        .map(str::parse::<Priority>)
+                        .transpose()?;
+                    let weighted = args.contains(&"--weighted");
+                    crate::print_random_task(self.manager, priority, weighted);
+                    Ok(())
+                }
+                "hist" | "history" => self.handle_history(args),
+                "stale" => {
+                    let threshold = match args.first() {
+                        Some(age) => parse_duration(age)?,
+                        None => crate::days_duration(crate::DEFAULT_STALE_DAYS),
+                    };
+                    crate::print_stale(self.manager, threshold);
+                    Ok(())
+                }
                 "h" | "help" | "?" => {
                     Self::print_interactive_help();
                     Ok(())
                 }
-                "q" | "quit" | "x" | "exit" => break,
+                "q" | "quit" | "x" | "exit" => return Ok(true),
                 _ => {
+                    // unknown commands don't stop the loop
                     eprintln!("unknown command: '{}'. Type 'h' for help.", command);
-                    Ok(()) // unknown commands don't stop the loop
+                    Ok(())
                 }
-            };
-            if let Err(e) = cmd_exec_result {
-                eprintln!("{}", e.to_string().red());
-            }
-        }
-        // Optional: save history to a file before exiting
-        if let Some(path) = &self.history_path {
-            if let Err(err) = self.ed.save_history(path) {
-                eprintln!("{}", format!("Error saving history: {:?}", err).red());
-            }
+            },
+        };
+        /* the task the user was looking for couldn't be found - maybe the arguments describe it */
+        if let Err(TaskError::TaskNotFound(_) | TaskError::ArgumentMismatch(_)) = &result
+            && !args.is_empty()
+        {
+            self.suggest_tasks(&args.join(" "));
         }
-        Ok(())
+        result?;
+        Ok(false)
     }
 
     /// Returns the input from the user and True if there was a valid input; error message and False
     /// otherwise
     fn read_input(&mut self, prompt: &str) -> Result<String, TaskError> {
+        self.refresh_helper();
         match self.ed.readline(prompt) {
             Ok(line) => {
-                self.ed.add_history_entry(line.as_str())?;
+                /* looking at the history shouldn't fill it up */
+                if !matches!(line.split_whitespace().next(), Some("hist" | "history")) {
+                    self.ed.add_history_entry(line.as_str())?;
+                }
                 Ok(line.trim().to_string())
             }
             Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => {
@@ -173,6 +554,7 @@ impl<'a> InteractiveMode<'a> {
         prompt: &str,
         initial_text: &str,
     ) -> Result<String, TaskError> {
+        self.refresh_helper();
         match self.ed.readline_with_initial(prompt, (initial_text, "")) {
             Ok(line) => {
                 self.ed.add_history_entry(line.as_str())?;
@@ -185,8 +567,61 @@ impl<'a> InteractiveMode<'a> {
         }
     }
 
-    fn handle_list(&mut self) -> Result<(), TaskError> {
-        self.manager.list_tasks();
+    /// hands the current tasks to the editor helper, so completion knows all IDs
+    fn refresh_helper(&mut self) {
+        if let Some(helper) = self.ed.helper_mut() {
+            helper.update_tasks(self.manager.iter());
+            helper.update_aliases(self.aliases.keys());
+        }
+    }
+
+    fn handle_list(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        let limit = match flag_value(args, "--limit") {
+            Some(n) => n.parse::<usize>().map_err(|_| {
+                TaskError::ArgumentMismatch(format!("'{}' is not a valid limit.", n))
+            })?,
+            None => 0,
+        };
+        let group_by = match flag_value(args, "--group-by") {
+            Some(by) => by.parse::<GroupBy>()?,
+            None => self
+                .manager
+                .get_config()
+                .default_group_by
+                .unwrap_or_default(),
+        };
+        let page_size = match flag_value(args, "--page-size") {
+            Some(n) => n.parse::<usize>().map_err(|_| {
+                TaskError::ArgumentMismatch(format!("'{}' is not a valid page size.", n))
+            })?,
+            None => 0,
+        };
+        let page = match flag_value(args, "--page") {
+            Some(n) => n.parse::<usize>().map_err(|_| {
+                TaskError::ArgumentMismatch(format!("'{}' is not a valid page number.", n))
+            })?,
+            None => 1,
+        };
+        let date = |flag: &str| flag_value(args, flag).map(parse_datetime).transpose();
+        let filter = TaskFilter {
+            created_since: date("--since")?,
+            created_before: date("--before")?,
+            completed_since: date("--completed-since")?,
+            completed_before: date("--completed-before")?,
+            include_snoozed: args.contains(&"--show-snoozed"),
+            starred: args.contains(&"--starred"),
+            ..TaskFilter::default()
+        };
+        self.manager.set_long_list(args.contains(&"--long"));
+        let list = if page_size > 0 {
+            self.manager
+                .format_task_list_page(&filter, group_by, page, page_size)
+        } else {
+            self.manager
+                .format_task_list_grouped(&filter, limit, group_by)
+        };
+        self.manager.set_long_list(false);
+        print!("{}", list);
         Ok(())
     }
 
@@ -207,11 +642,77 @@ impl<'a> InteractiveMode<'a> {
         if desc.is_empty() {
             return Err(TaskError::Empty("Description".to_string()));
         }
-        let index = self.manager.add_task(desc);
+        let index = self.manager.add_task(desc)?;
         println!("{}", format!("Added task with ID {}.", index).green());
         Ok(())
     }
 
+    /* edit <id>: change the description in $EDITOR; edit without ID: add a task written there */
+    fn handle_edit(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        if args.is_empty() {
+            let Some(desc) = edit_in_editor("")? else {
+                println!("{}", "Cancelled, no task added.".yellow());
+                return Ok(());
+            };
+            let index = self.manager.add_task(desc)?;
+            println!("{}", format!("Added task with ID {}.", index).green());
+            return Ok(());
+        }
+        let id = self.get_id_arg(args)?;
+        let old_desc = self
+            .manager
+            .at(id)
+            .ok_or(TaskError::TaskNotFound(id))?
+            .get_description()
+            .to_string();
+        match edit_in_editor(&old_desc)? {
+            Some(new_desc) if new_desc != old_desc => {
+                self.manager.change_description(id, new_desc.as_str())?;
+                println!(
+                    "Updated task description from '{}' to '{}'.",
+                    old_desc.yellow(),
+                    new_desc.green()
+                );
+            }
+            Some(_) => println!("Description unchanged."),
+            None => println!("{}", "Cancelled, description unchanged.".yellow()),
+        }
+        Ok(())
+    }
+
+    /* replace [--regex] <pattern> <replacement>: shows all changes and asks before applying */
+    fn handle_replace(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        let regex = args.first() == Some(&"--regex");
+        let args = if regex { &args[1..] } else { args };
+        let Some((pattern, replacement)) = args.split_first() else {
+            return Err(TaskError::Empty("Pattern".to_string()));
+        };
+        let replacement = replacement.join(" ");
+        let changes = self
+            .manager
+            .description_replacements(pattern, &replacement, regex)?;
+        if changes.is_empty() {
+            println!("No descriptions match '{}'.", pattern);
+            return Ok(());
+        }
+        for (id, old_desc, new_desc) in &changes {
+            println!("#{}: '{}' → '{}'", id, old_desc.yellow(), new_desc.green());
+        }
+        let answer = self.read_input(&format!("Replace in {} tasks? [y/N] ", changes.len()))?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            println!("Nothing replaced.");
+            return Ok(());
+        }
+        let changed = self
+            .manager
+            .replace_in_descriptions(pattern, &replacement, regex)?;
+        println!(
+            "{}",
+            format!("Changed {} descriptions.", changed.len()).green()
+        );
+        Ok(())
+    }
+
     fn handle_complete(&mut self, args: &[&str]) -> Result<(), TaskError> {
         /* if args has the wrong length, or isn't a number, we'll get a subprompt from the user */
         let istr: String;
@@ -228,18 +729,44 @@ impl<'a> InteractiveMode<'a> {
         match istr.parse::<usize>() {
             Ok(id) => match self.manager.complete_task(id) {
                 Ok(msg) => println!("{}", msg.green()),
+                Err(TaskError::HasPendingChildren {
+                    parent_id,
+                    pending_child_ids,
+                }) => return self.complete_with_subtasks(parent_id, &pending_child_ids),
                 Err(_) => return Err(TaskError::TaskNotFound(id)),
             },
             Err(_) => {
                 return Err(TaskError::ArgumentMismatch(format!(
                     "wrong argument: '{}' is not a valid task ID.",
                     istr
-                )))
+                )));
             }
         }
         Ok(())
     }
 
+    /* asks before completing a task together with its pending subtasks */
+    fn complete_with_subtasks(&mut self, id: usize, pending: &[usize]) -> Result<(), TaskError> {
+        let noun = if pending.len() == 1 {
+            "subtask"
+        } else {
+            "subtasks"
+        };
+        let answer = self.read_input(&format!(
+            "Task #{} has {} pending {}. Complete anyway? [y/N] ",
+            id,
+            pending.len(),
+            noun
+        ))?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            println!("Nothing completed.");
+            return Ok(());
+        }
+        let msg = self.manager.complete_task_with_subtasks(id)?;
+        println!("{}", msg.green());
+        Ok(())
+    }
+
     fn handle_prio_change(&mut self, args: &[&str], prioritize: bool) -> Result<(), TaskError> {
         /* if args has the wrong length, or isn't a number, we'll get a subprompt from the user */
         let istr: String;
@@ -262,7 +789,7 @@ impl<'a> InteractiveMode<'a> {
                 return Err(TaskError::ArgumentMismatch(format!(
                     "wrong argument: '{}' is not a valid task ID.",
                     istr
-                )))
+                )));
             }
         }
         Ok(())
@@ -290,7 +817,7 @@ impl<'a> InteractiveMode<'a> {
                 return Err(TaskError::ArgumentMismatch(format!(
                     "wrong argument: '{}' is not a valid task ID.",
                     istr
-                )))
+                )));
             }
         }
         Ok(())
@@ -299,7 +826,7 @@ impl<'a> InteractiveMode<'a> {
     fn handle_change(&mut self, args: &[&str]) -> Result<(), TaskError> {
         /* part 1: check for index */
         let istr: String;
-        if args.len() < 1 {
+        if args.is_empty() {
             match self.read_input(&format!("{}> ", "ID".cyan())) {
                 Ok(s) => istr = s,
                 Err(TaskError::InputCancelled) => return Err(TaskError::InputCancelled),
@@ -323,10 +850,7 @@ impl<'a> InteractiveMode<'a> {
         /* part 3: check for new description */
         let new_desc: String;
         if args.len() < 2 {
-            match self.read_input_initial(
-                &format!("{}> ", "Description".cyan()),
-                &format!("{}", &old_desc),
-            ) {
+            match self.read_input_initial(&format!("{}> ", "Description".cyan()), &old_desc) {
                 Ok(s) => new_desc = s,
                 Err(TaskError::InputCancelled) => return Err(TaskError::InputCancelled),
                 Err(e) => return Err(e),
@@ -338,16 +862,346 @@ impl<'a> InteractiveMode<'a> {
         if new_desc.is_empty() {
             return Err(TaskError::Empty("Description".to_string()));
         }
-        let task_to_update = self.manager.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
-        task_to_update.set_description(new_desc);
+        self.manager.change_description(id, new_desc.as_str())?;
         println!(
             "Updated task description from '{}' to '{}'.",
             old_desc.yellow(),
-            task_to_update.get_description().green()
+            new_desc.green()
         );
         Ok(())
     }
 
+    /// Returns the task ID given as the first argument, or asks the user for one if there is none
+    fn get_id_arg(&mut self, args: &[&str]) -> Result<usize, TaskError> {
+        let istr = match args.first() {
+            Some(s) => s.to_string(),
+            None => self.read_input(&format!("{}> ", "ID".cyan()))?,
+        };
+        istr.parse::<usize>().map_err(|_| {
+            TaskError::ArgumentMismatch(format!(
+                "wrong argument: '{}' is not a valid task ID.",
+                istr
+            ))
+        })
+    }
+
+    /// prints up to three tasks whose description is similar to the query
+    fn suggest_tasks(&self, query: &str) {
+        let suggestions: Vec<String> = self
+            .manager
+            .fuzzy_find(query)
+            .into_iter()
+            .filter(|(_, score)| *score >= SUGGESTION_MIN_SCORE)
+            .take(3)
+            .map(|(task, _)| format!("#{} '{}'", task.get_id(), task.get_description()))
+            .collect();
+        if !suggestions.is_empty() {
+            println!("Did you mean: {}?", suggestions.join(", ").yellow());
+        }
+    }
+
+    fn handle_find(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        let fuzzy = args.first() == Some(&"--fuzzy");
+        let query = if fuzzy { &args[1..] } else { args }.join(" ");
+        if query.is_empty() {
+            return Err(TaskError::Empty("Search text".to_string()));
+        }
+        let found: Vec<&Task> = if fuzzy {
+            self.manager
+                .fuzzy_find(&query)
+                .into_iter()
+                .map(|(task, _)| task)
+                .collect()
+        } else {
+            self.manager.find_by_description(&query)
+        };
+        if found.is_empty() {
+            println!("No tasks match '{}'", query);
+        } else {
+            self.manager.print_tasks(&found);
+        }
+        Ok(())
+    }
+
+    fn handle_set_priority(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        let id = self.get_id_arg(args)?;
+        let name = match args.get(1) {
+            Some(name) => name.to_string(),
+            None => self.read_input(&format!("{}> ", "Priority".cyan()))?,
+        };
+        let msg = self.manager.set_priority_by_name(id, &name)?;
+        println!("{}", msg.green());
+        Ok(())
+    }
+
+    fn handle_swap(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        let id1 = self.get_id_arg(args)?;
+        let id2 = self.get_id_arg(args.get(1..).unwrap_or_default())?;
+        let msg = self.manager.swap_positions(id1, id2)?;
+        println!("{}", msg.green());
+        Ok(())
+    }
+
+    /* undo: puts back the task list from before the last change */
+    fn handle_undo(&mut self) -> Result<(), TaskError> {
+        let Some(snapshot) = self.undo_stack.pop_back() else {
+            println!("{}", "Nothing to undo.".yellow());
+            return Ok(());
+        };
+        let current = self.manager.snapshot();
+        let summary = change_summary(current.tasks(), snapshot.tasks());
+        self.manager.restore(snapshot);
+        push_limited(&mut self.redo_stack, current);
+        println!("{} {}", "Undid:".green(), summary);
+        Ok(())
+    }
+
+    /* redo / r: makes the last undone change again */
+    fn handle_redo(&mut self) -> Result<(), TaskError> {
+        let Some(snapshot) = self.redo_stack.pop_back() else {
+            println!("{}", "Nothing to redo.".yellow());
+            return Ok(());
+        };
+        let current = self.manager.snapshot();
+        let summary = change_summary(current.tasks(), snapshot.tasks());
+        self.manager.restore(snapshot);
+        push_limited(&mut self.undo_stack, current);
+        println!("{} {}", "Redid:".green(), summary);
+        Ok(())
+    }
+
+    /* returns the input with an alias in the first word replaced by its expansion; the
+     * expansion isn't looked up again, so aliases can't refer to other aliases (or loop) */
+    fn resolve_alias(&self, input: &str) -> String {
+        let trimmed = input.trim_start();
+        let (command, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        match self.aliases.get(&command.to_lowercase()) {
+            Some(expansion) => format!("{} {}", expansion, rest),
+            None => input.to_string(),
+        }
+    }
+
+    /* alias / alias list: show all, alias <name> <expansion> or alias <name>=<expansion>: define */
+    fn handle_alias(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        if args.is_empty() || args == ["list"] {
+            if self.aliases.is_empty() {
+                println!("No aliases defined.");
+            }
+            let mut names: Vec<&String> = self.aliases.keys().collect();
+            names.sort();
+            for name in names {
+                println!("  {:<10} = {}", name.cyan().bold(), self.aliases[name]);
+            }
+            return Ok(());
+        }
+        let definition = args.join(" ");
+        let (name, expansion) = match definition.split_once('=') {
+            Some((name, expansion)) => (name.trim().to_string(), expansion.trim().to_string()),
+            None => (args[0].to_string(), args[1..].join(" ")),
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "'{}' is not a valid alias name",
+                name
+            )));
+        }
+        if expansion.is_empty() {
+            return Err(TaskError::Empty("Alias expansion".to_string()));
+        }
+        let name = name.to_lowercase();
+        if COMMANDS.contains(&name.as_str()) {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "'{}' is a built-in command and can't be redefined",
+                name
+            )));
+        }
+        warn_unknown_alias_target(&name, &expansion);
+        self.aliases.insert(name.clone(), expansion.clone());
+        println!("Defined alias {} = {}", name.cyan().bold(), expansion);
+        self.refresh_helper();
+        Ok(())
+    }
+
+    fn handle_link(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        let id = self.get_id_arg(args)?;
+        let url = match args.get(1) {
+            Some(url) => url.to_string(),
+            None => self.read_input(&format!("{}> ", "URL".cyan()))?,
+        };
+        let msg = self.manager.link_task(id, url.trim())?;
+        println!("{}", msg.green());
+        Ok(())
+    }
+
+    fn handle_pin(&mut self, args: &[&str], pin: bool) -> Result<(), TaskError> {
+        let id = self.get_id_arg(args)?;
+        let msg = if pin {
+            self.manager.pin_task(id)?
+        } else {
+            self.manager.unpin_task(id)?
+        };
+        println!("{}", msg.green());
+        Ok(())
+    }
+
+    fn handle_mark(&mut self, args: &[&str], star: bool) -> Result<(), TaskError> {
+        let id = self.get_id_arg(args)?;
+        let msg = if star {
+            self.manager.star_task(id)?
+        } else {
+            self.manager.unstar_task(id)?
+        };
+        println!("{}", msg.green());
+        Ok(())
+    }
+
+    /// Runs every line of the given file as if it was typed in. Empty lines and lines starting
+    /// with '#' are skipped; errors are printed, but don't stop the script. A quit command ends
+    /// the script, not interactive mode.
+    fn handle_script(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        let path = if args.is_empty() {
+            self.read_input(&format!("{}> ", "File".cyan()))?
+        } else {
+            args.join(" ")
+        };
+        if path.is_empty() {
+            return Err(TaskError::Empty("File".to_string()));
+        }
+        let contents = fs::read_to_string(&path)?;
+        /* a script that runs itself (directly or through others) would never end */
+        let canonical = fs::canonicalize(&path)?;
+        if self.open_scripts.contains(&canonical) {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "script '{}' is already running, scripts can't run themselves",
+                path
+            )));
+        }
+        if self.open_scripts.len() >= SCRIPT_DEPTH_LIMIT {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "scripts can run other scripts only {} levels deep",
+                SCRIPT_DEPTH_LIMIT
+            )));
+        }
+        self.open_scripts.push(canonical);
+        for (line_no, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            println!("{} {}", format!("{}:{}»", path, line_no + 1).dimmed(), line);
+            if self.run_commands(line) {
+                break;
+            }
+        }
+        self.open_scripts.pop();
+        Ok(())
+    }
+
+    /* select: choose tasks with the arrow keys; select --clear: forget the selection */
+    fn handle_select(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        if args.contains(&"--clear") {
+            self.selected_ids.clear();
+            println!("Selection cleared.");
+            return Ok(());
+        }
+        let tasks: Vec<&Task> = self.manager.iter().collect();
+        if tasks.is_empty() {
+            println!("{}", "No tasks to select.".yellow());
+            return Ok(());
+        }
+        let theme = &self.manager.get_config().theme;
+        match crate::select::select_tasks(&tasks, &self.selected_ids, theme)? {
+            Some(selected) => {
+                self.selected_ids = selected;
+                println!(
+                    "{} tasks selected, use e.g. 'complete --selected'.",
+                    self.selected_ids.len()
+                );
+            }
+            None => println!("Selection unchanged."),
+        }
+        Ok(())
+    }
+
+    /* runs a command for every selected task; a failure for one task doesn't stop the others */
+    fn run_on_selection(&mut self, command: &str, args: &[&str]) -> Result<(), TaskError> {
+        if self.selected_ids.is_empty() {
+            return Err(TaskError::ArgumentMismatch(
+                "no tasks selected, use 'select' first".to_string(),
+            ));
+        }
+        let priority = match command {
+            "prio" | "priority" => {
+                let name = args
+                    .first()
+                    .ok_or(TaskError::Empty("Priority".to_string()))?;
+                Some(name.parse::<Priority>()?)
+            }
+            _ => None,
+        };
+        /* in list order, so the output reads like the list */
+        let ids: Vec<usize> = self
+            .manager
+            .tasks_matching(|t| self.selected_ids.contains(&t.get_id()))
+            .map(Task::get_id)
+            .collect();
+        for id in ids {
+            let result = match (command, priority) {
+                ("c" | "complete", _) => self.manager.complete_task(id),
+                ("d" | "delete", _) => self.manager.delete_task(id),
+                ("+" | "up", _) => self.manager.change_priority(id, true),
+                ("-" | "down", _) => self.manager.change_priority(id, false),
+                (_, Some(priority)) => self.manager.set_priority(id, priority),
+                ("pin", _) => self.manager.pin_task(id),
+                ("unpin", _) => self.manager.unpin_task(id),
+                _ => {
+                    return Err(TaskError::ArgumentMismatch(format!(
+                        "'{}' doesn't work with --selected; use complete, delete, up, down, prio, pin or unpin",
+                        command
+                    )));
+                }
+            };
+            match result {
+                Ok(msg) => println!("{}", msg.green()),
+                Err(e) => eprintln!("{}", e.to_string().red()),
+            }
+        }
+        /* deleted tasks can't stay selected */
+        let manager = &self.manager;
+        self.selected_ids.retain(|id| manager.at(*id).is_some());
+        Ok(())
+    }
+
+    /* history [N]: the last N entries (default 20) numbered like bash's history - that's the
+     * history file plus this session's commands, which are only written to the file on exit.
+     * history clear: empties the history and the file */
+    fn handle_history(&mut self, args: &[&str]) -> Result<(), TaskError> {
+        if args.first() == Some(&"clear") {
+            self.ed.clear_history()?;
+            if let Some(path) = &self.history_path
+                && path.exists()
+            {
+                self.ed.save_history(path)?;
+            }
+            println!("History cleared.");
+            return Ok(());
+        }
+        let count = match args.first() {
+            Some(n) => n.parse::<usize>().map_err(|_| {
+                TaskError::ArgumentMismatch(format!("'{}' is not a number of entries.", n))
+            })?,
+            None => DEFAULT_HISTORY_ENTRIES,
+        };
+        let history = self.ed.history();
+        let skip = history.len().saturating_sub(count);
+        for (number, entry) in history.iter().enumerate().skip(skip) {
+            println!("{} {}", format!("{:>5}", number + 1).dimmed(), entry);
+        }
+        Ok(())
+    }
+
     fn handle_clear(&mut self) -> Result<(), TaskError> {
         let cleared_count = self.manager.clear_completed_tasks();
         println!(
@@ -357,3 +1211,309 @@ impl<'a> InteractiveMode<'a> {
         Ok(())
     }
 }
+
+/* the aliases of the configuration with lowercase names, since commands are matched ignoring
+ * case. Aliases named like a built-in command are left out with a warning, they would hide it */
+fn config_aliases(aliases: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut names: Vec<&String> = aliases.keys().collect();
+    names.sort();
+    let mut lowercase = HashMap::new();
+    for name in names {
+        let expansion = &aliases[name];
+        let key = name.to_lowercase();
+        if COMMANDS.contains(&key.as_str()) {
+            eprintln!(
+                "{}",
+                format!(
+                    "Warning: alias '{}' is a built-in command and is ignored",
+                    name
+                )
+                .yellow()
+            );
+            continue;
+        }
+        warn_unknown_alias_target(name, expansion);
+        lowercase.insert(key, expansion.clone());
+    }
+    lowercase
+}
+
+/* an alias still gets defined if its expansion doesn't start with a command (it may be a typo
+ * or a command of a newer version), but using it will fail, so say so right away */
+fn warn_unknown_alias_target(name: &str, expansion: &str) {
+    let command = expansion
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if !COMMANDS.contains(&command.as_str()) {
+        eprintln!(
+            "{}",
+            format!(
+                "Warning: alias '{}' points to unknown command '{}'",
+                name, command
+            )
+            .yellow()
+        );
+    }
+}
+
+/* runs the command with `sh -c` on the terminal of interactive mode, between two separator
+ * lines, and waits for it */
+fn run_shell_command(command: &str) -> Result<(), TaskError> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Err(TaskError::Empty("Shell command".to_string()));
+    }
+    let separator = "─".repeat(SHELL_SEPARATOR_LEN);
+    println!("{}", separator.dimmed());
+    let status = Command::new("sh").arg("-c").arg(command).status();
+    println!("{}", separator.dimmed());
+    let status = status?;
+    match status.code() {
+        Some(0) => {}
+        Some(code) => println!("{}", format!("Command exited with code {}", code).yellow()),
+        None => println!("{}", format!("Command ended: {}", status).yellow()),
+    }
+    Ok(())
+}
+
+/* adds a snapshot to undo or redo, forgetting the oldest beyond UNDO_LIMIT */
+fn push_limited(stack: &mut VecDeque<TaskSnapshot>, snapshot: TaskSnapshot) {
+    stack.push_back(snapshot);
+    if stack.len() > UNDO_LIMIT {
+        stack.pop_front();
+    }
+}
+
+/* true if tasks were added, removed or changed, or the order of the list changed */
+fn task_list_changed(before: &[Task], after: &[Task]) -> bool {
+    !TaskManager::diff(before, after).is_empty()
+        || !before
+            .iter()
+            .map(Task::get_id)
+            .eq(after.iter().map(Task::get_id))
+}
+
+/* what going from one task list to the other changes, in one line, e.g. "+ #4 [Medium] Buy milk" */
+fn change_summary(from: &[Task], to: &[Task]) -> String {
+    let diff = TaskManager::diff(from, to);
+    if diff.is_empty() {
+        return "order of the tasks".to_string();
+    }
+    diff.to_string().lines().collect::<Vec<_>>().join(", ")
+}
+
+/// Splits a line at the ';' that aren't inside single or double quotes, e.g.
+/// `add foo; add "a;b"` gives `add foo` and `add "a;b"`. The commands are trimmed and empty ones
+/// left out; quotes stay in the text, as they do in single commands (see split_commands).
+fn parse_command_line(input: &str) -> Vec<String> {
+    split_commands(input)
+        .into_iter()
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+/// Returns the value of a flag given as '--flag value' or '--flag=value', if it is present
+fn flag_value<'s>(args: &[&'s str], flag: &str) -> Option<&'s str> {
+    for (i, arg) in args.iter().enumerate() {
+        if *arg == flag {
+            return args.get(i + 1).copied();
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|v| v.strip_prefix('=')) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* a manager that never touches the disk */
+    fn test_manager() -> TaskManager {
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.set_no_save(true);
+        manager
+    }
+
+    #[test]
+    fn script_that_runs_itself_stops() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.tm");
+        let b = dir.path().join("b.tm");
+        fs::write(&a, format!("add from a\nscript {}\n", b.display())).unwrap();
+        fs::write(&b, format!("add from b\nscript {}\n", a.display())).unwrap();
+        let mut manager = test_manager();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        mode.run_command(&format!("script {}", a.display()))
+            .unwrap();
+        assert!(mode.open_scripts.is_empty());
+        drop(mode);
+        assert_eq!(manager.iter().count(), 2);
+    }
+
+    #[test]
+    fn config_aliases_ignore_case_and_cant_hide_commands() {
+        let mut manager = test_manager();
+        let mut config = manager.get_config().clone();
+        config.aliases = HashMap::from([
+            ("New".to_string(), "add".to_string()),
+            ("list".to_string(), "clear".to_string()),
+        ]);
+        manager.set_config(config);
+        manager.add_task("Write the report").unwrap();
+        manager.complete_task(1).unwrap();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert_eq!(mode.aliases.len(), 1);
+        mode.run_command("NEW Call Bob").unwrap();
+        /* "list" still lists instead of clearing the completed task */
+        mode.run_command("list").unwrap();
+        assert!(mode.run_command("alias c delete").is_err());
+        drop(mode);
+        assert_eq!(manager.iter().count(), 2);
+    }
+
+    #[test]
+    fn aliases_expand_once_with_their_arguments() {
+        let mut manager = test_manager();
+        let mut config = manager.get_config().clone();
+        config.aliases = HashMap::from([
+            ("hi".to_string(), "list --priority=high".to_string()),
+            ("todo".to_string(), "add TODO:".to_string()),
+            ("loop".to_string(), "todo again".to_string()),
+        ]);
+        manager.set_config(config);
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert_eq!(mode.resolve_alias("hi"), "list --priority=high ");
+        assert_eq!(mode.resolve_alias("TODO call Bob"), "add TODO: call Bob");
+        /* only the first word is expanded, and only once */
+        assert_eq!(mode.resolve_alias("loop"), "todo again ");
+        assert_eq!(mode.resolve_alias("list hi"), "list hi");
+        mode.run_command("todo call Bob").unwrap();
+        mode.run_command("hi").unwrap();
+        drop(mode);
+        assert_eq!(descriptions(&manager), ["TODO: call Bob"]);
+    }
+
+    #[test]
+    fn runtime_aliases_last_for_the_session() {
+        let mut manager = test_manager();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        mode.run_command("alias n=add").unwrap();
+        mode.run_command("alias Done complete").unwrap();
+        assert!(mode.run_command("alias x").is_err());
+        assert!(mode.run_command("alias =add").is_err());
+        mode.run_command("n Buy milk").unwrap();
+        mode.run_command("done 1").unwrap();
+        drop(mode);
+        assert!(manager.get_config().aliases.is_empty());
+        assert!(manager.iter().all(|t| t.get_completed()));
+
+        let mode = InteractiveMode::new(&mut manager).unwrap();
+        assert!(mode.aliases.is_empty());
+    }
+
+    /* the descriptions of all tasks in list order */
+    fn descriptions(manager: &TaskManager) -> Vec<String> {
+        manager
+            .iter()
+            .map(|t| t.get_description().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn add_creates_a_task() {
+        let mut manager = test_manager();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert!(!mode.run_command("add Buy milk").unwrap());
+        assert!(!mode.run_command("a Call Bob").unwrap());
+        drop(mode);
+        assert_eq!(descriptions(&manager), ["Buy milk", "Call Bob"]);
+    }
+
+    #[test]
+    fn delete_removes_the_task() {
+        let mut manager = test_manager();
+        manager.add_task("Buy milk").unwrap();
+        manager.add_task("Call Bob").unwrap();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert!(!mode.run_command("delete 1").unwrap());
+        assert!(mode.run_command("delete 42").is_err());
+        drop(mode);
+        assert_eq!(descriptions(&manager), ["Call Bob"]);
+    }
+
+    #[test]
+    fn complete_marks_the_task() {
+        let mut manager = test_manager();
+        manager.add_task("Buy milk").unwrap();
+        manager.add_task("Call Bob").unwrap();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert!(!mode.run_command("complete 2").unwrap());
+        drop(mode);
+        let completed: Vec<bool> = manager.iter().map(|t| t.get_completed()).collect();
+        assert_eq!(completed, [false, true]);
+    }
+
+    #[test]
+    fn complete_force_completes_the_subtasks() {
+        let mut manager = test_manager();
+        manager.add_task("Plan the party").unwrap();
+        manager.add_task("Book the venue").unwrap();
+        manager.set_parent(2, Some(1)).unwrap();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert!(!mode.run_command("complete 1 --force").unwrap());
+        drop(mode);
+        assert!(manager.iter().all(|t| t.get_completed()));
+    }
+
+    #[test]
+    fn change_replaces_the_description() {
+        let mut manager = test_manager();
+        manager.add_task("Buy milk").unwrap();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert!(!mode.run_command("change 1 Buy oat milk").unwrap());
+        drop(mode);
+        assert_eq!(descriptions(&manager), ["Buy oat milk"]);
+    }
+
+    #[test]
+    fn command_line_splits_at_unquoted_semicolons() {
+        assert_eq!(
+            parse_command_line("add foo; add bar ;list"),
+            ["add foo", "add bar", "list"]
+        );
+        assert_eq!(
+            parse_command_line(r#"add "a;b"; add 'c;d' "#),
+            [r#"add "a;b""#, "add 'c;d'"]
+        );
+        assert_eq!(
+            parse_command_line(r#"add "it's; fine""#),
+            [r#"add "it's; fine""#]
+        );
+        assert_eq!(parse_command_line("add 'open; quote"), ["add 'open; quote"]);
+        assert!(parse_command_line(" ; ;; ").is_empty());
+    }
+
+    #[test]
+    fn failing_commands_dont_stop_the_line() {
+        let mut manager = test_manager();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert!(!mode.run_commands("add Buy milk; delete 42; add Call Bob"));
+        assert!(mode.run_commands("add Pay rent; quit; add never"));
+        drop(mode);
+        assert_eq!(descriptions(&manager), ["Buy milk", "Call Bob", "Pay rent"]);
+    }
+
+    #[test]
+    fn help_and_quit() {
+        let mut manager = test_manager();
+        let mut mode = InteractiveMode::new(&mut manager).unwrap();
+        assert!(!mode.run_command("help").unwrap());
+        assert!(!mode.run_command("").unwrap());
+        assert!(mode.run_command("quit").unwrap());
+    }
+}
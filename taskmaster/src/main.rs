@@ -1,10 +1,38 @@
-use tasks::{TaskError, TaskManager};
+use tasks::{
+    AuditEntry, Config, CsvSerializer, DeduplicateStrategy, GroupBy, HealthIssue, JsonSerializer,
+    LintWarning, Priority, Serializer, SortKey, StorageFormat, TagStats, Task, TaskAuditLog,
+    TaskError, TaskFilter, TaskLinter, TaskManager, TaskStatus, TaskTemplate, Theme,
+    format_minutes,
+    interop::github::{GithubIssue, import_issues},
+    interop::ical::export_ical_with_uid_base,
+    interop::orgmode::{import_orgmode, to_orgmode},
+    parse_estimate, sanitize_description, unknown_placeholders,
+    util::{check_url, parse_datetime, parse_duration, whole_days},
+};
+mod completions;
+mod forms;
 mod interactive;
+mod select;
+mod task_helper;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+mod workspace;
+use crate::forms::{AddForm, FieldPrompt};
 use crate::interactive::InteractiveMode;
 
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use colored::Colorize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, Write};
 use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
 #[derive(Parser)]
 #[command(
@@ -15,38 +43,194 @@ use std::path::PathBuf;
     after_help = "For more detailed help on a specific command, use:\n  taskmaster <COMMAND> --help \n  taskmaster help <COMMAND"
 )]
 struct Cli {
+    /// Record all changes in the audit log (~/.taskmaster_audit.log)
+    #[arg(long, global = true)]
+    audit: bool,
+    /// Only show what would change, without changing or writing anything
+    #[arg(long, short = 'n', global = true)]
+    dry_run: bool,
+    /// Check the task file for problems before running the command, and log every change,
+    /// load and save to stderr
+    #[arg(long, short, global = true)]
+    verbose: bool,
+    /// Work on this workspace instead of the active one
+    #[arg(long, global = true, value_name = "NAME")]
+    workspace: Option<String>,
     #[command(subcommand)]
     command: Commands,
 }
 
+/// How much run_app tells about what it does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verbosity {
+    Normal,
+    /// health check first, debug lines for changes, loading and saving
+    Verbose,
+}
+
+impl Verbosity {
+    fn from_flag(verbose: bool) -> Self {
+        if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Add a new task
     #[command(visible_alias = "a")]
     Add {
         /// The description of the task to be added
-        #[arg(required = true)]
+        #[arg(required_unless_present = "interactive")]
         description: Vec<String>,
+        /// Ask for the description (if not given), priority and due date
+        #[arg(long, short)]
+        interactive: bool,
+        /// Add the task as a subtask of the task with this ID
+        #[arg(long, value_name = "ID")]
+        parent: Option<usize>,
     }, // Vec<String> to capture multiple words
     /// change description of Task
     #[command(visible_alias = "ch")]
     Change {
         /// The ID of the task you want to change
-        #[arg(required = true)]
-        id: usize,
+        #[arg(value_name = "ID", required_unless_present_any = ["interactive", "desc"])]
+        id_arg: Option<String>,
+        /* id_arg as a number; with --desc, id_arg is the first word of the new description
+         * instead, so both are sorted out by resolve_desc before the command runs */
+        #[arg(skip)]
+        id: Option<usize>,
+        /// Choose the task by (the start of) its description instead of its ID
+        #[arg(long, value_name = "TEXT")]
+        desc: Option<String>,
         /// The new description for the task
-        #[arg(required = true)]
+        #[arg(required_unless_present_any = ["interactive", "desc"])]
         description: Vec<String>,
+        /// Ask for the ID and the new description if they aren't given
+        #[arg(long, short)]
+        interactive: bool,
+    },
+    /// Clean up descriptions: trim them, collapse whitespace, remove control characters
+    Sanitize {
+        /// The ID of the task to clean up
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        id: Option<usize>,
+        /// Clean up the descriptions of all tasks
+        #[arg(long)]
+        all: bool,
     },
     /// List all tasks
     #[command(visible_alias = "l")]
-    List,
+    List {
+        /// Also load and show the tasks of this file (can be given multiple times)
+        #[arg(long, value_name = "FILE")]
+        also: Vec<PathBuf>,
+        /// Show only the first N tasks (0 shows all)
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        limit: usize,
+        /// Show only tasks assigned to this person
+        #[arg(long, value_name = "USERNAME", conflicts_with = "mine")]
+        assignee: Option<String>,
+        /// Show only tasks assigned to you ($USER)
+        #[arg(long)]
+        mine: bool,
+        /// Show only tasks with a link
+        #[arg(long)]
+        has_link: bool,
+        /// Show only tasks whose description has at least N words
+        #[arg(long, value_name = "N")]
+        min_words: Option<usize>,
+        /// Show only tasks whose description has at most N words, e.g. to find vague ones
+        #[arg(long, value_name = "N")]
+        max_words: Option<usize>,
+        /// Show only tasks added since this date, e.g. 2024-11-01, 7d or 1w (ago)
+        #[arg(long, value_name = "DATE", value_parser = parse_datetime)]
+        since: Option<DateTime<Utc>>,
+        /// Show only tasks added before this date, e.g. 2024-11-01, 7d or 1w (ago)
+        #[arg(long, value_name = "DATE", value_parser = parse_datetime)]
+        before: Option<DateTime<Utc>>,
+        /// Show only tasks completed since this date, e.g. 2024-11-01, 7d or 1w (ago)
+        #[arg(long, value_name = "DATE", value_parser = parse_datetime)]
+        completed_since: Option<DateTime<Utc>>,
+        /// Show only tasks completed before this date, e.g. 2024-11-01, 7d or 1w (ago)
+        #[arg(long, value_name = "DATE", value_parser = parse_datetime)]
+        completed_before: Option<DateTime<Utc>>,
+        /// Divide the list into sections: priority, status, context or none
+        #[arg(long, value_name = "GROUPING")]
+        group_by: Option<GroupBy>,
+        /// Sort the list: urgency, priority, due or id; several orders separated by commas
+        /// (only for this listing)
+        #[arg(long, value_name = "ORDER", value_delimiter = ',')]
+        sort_by: Vec<SortKey>,
+        /// Show the list in pages of N tasks (0 shows all)
+        #[arg(long, value_name = "N", default_value_t = 0, conflicts_with = "limit")]
+        page_size: usize,
+        /// The page to show with --page-size (1-based)
+        #[arg(long, value_name = "P", default_value_t = 1, requires = "page_size")]
+        page: usize,
+        /// Print one line per task from this template, e.g. "{id}\t{description}".
+        /// Placeholders: {id} {description} {priority} {status} {due} {assignee} {url}
+        /// {estimate} {updated} {created} {tags}
+        #[arg(long, value_name = "TEMPLATE", conflicts_with_all = ["group_by", "page_size"])]
+        format: Option<String>,
+        /// Also show how long each task takes to read (description and notes)
+        #[arg(long, conflicts_with = "format")]
+        long: bool,
+        /// Also show snoozed tasks
+        #[arg(long)]
+        show_snoozed: bool,
+        /// Show only starred tasks
+        #[arg(long)]
+        starred: bool,
+        /// Show only tasks with this priority (low, medium or high; or l, m, h)
+        #[arg(long)]
+        priority: Option<Priority>,
+    },
+    /// Find tasks by their description
+    #[command(visible_alias = "f")]
+    Find {
+        /// Use similarity matching instead of a case-insensitive substring search
+        #[arg(long)]
+        fuzzy: bool,
+        /// The text to search for
+        #[arg(required = true)]
+        query: Vec<String>,
+    },
+    /// Show the tasks to work on today
+    Today,
+    /// Pick a random pending task to work on
+    Random {
+        /// Pick only from the tasks with this priority (low, medium or high; or l, m, h)
+        #[arg(long)]
+        priority: Option<Priority>,
+        /// Prefer tasks with higher priority (see priority_weights in the configuration)
+        #[arg(long)]
+        weighted: bool,
+    },
+    /// List pending tasks that weren't changed for a long time
+    Stale {
+        /// Tasks not changed for longer than this, e.g. 90 (days), 2w or 3m [default: 30 days]
+        #[arg(long, visible_alias = "days", value_name = "AGE", value_parser = parse_duration)]
+        older_than: Option<Duration>,
+    },
     /// Mark a task as completed
     #[command(visible_alias = "c")]
     Complete {
         /// The ID of the task to mark as complete
-        #[arg(required = true)]
-        id: usize,
+        #[arg(required_unless_present_any = ["interactive", "desc"], conflicts_with = "desc")]
+        id: Option<usize>,
+        /// Choose the task by (the start of) its description instead of its ID
+        #[arg(long, value_name = "TEXT")]
+        desc: Option<String>,
+        /// Show the pending tasks and ask for the ID if it isn't given
+        #[arg(long, short)]
+        interactive: bool,
+        /// Also complete the pending subtasks without asking
+        #[arg(long)]
+        force: bool,
     },
     /// Ranks up the task's priority
     Up {
@@ -58,21 +242,559 @@ enum Commands {
         /// The ID of the task who's priority should be downranked
         id: usize,
     },
-    /// Delete a task
+    /// Focus a task: marks it as the one you're working on right now
+    Focus {
+        /// The ID of the task to focus
+        #[arg(required = true)]
+        id: usize,
+    },
+    /// Remove the focus from the currently focused task
+    Unfocus,
+    /// Pin a task, so it is always listed first
+    Pin {
+        /// The ID of the task to pin
+        #[arg(required = true)]
+        id: usize,
+    },
+    /// Unpin a previously pinned task
+    Unpin {
+        /// The ID of the task to unpin
+        #[arg(required = true)]
+        id: usize,
+    },
+    /// Star a task you want to keep an eye on (shown with ★ in the list)
+    Mark {
+        /// The ID of the task to star
+        #[arg(required = true)]
+        id: usize,
+    },
+    /// Remove the star from a task
+    Unmark {
+        /// The ID of the task to unstar
+        #[arg(required = true)]
+        id: usize,
+    },
+    /// Set the task's priority
+    #[command(visible_alias = "prio")]
+    Priority {
+        /// The ID of the task who's priority should be set
+        id: usize,
+        /// The new priority: low, medium or high (or l, m, h)
+        priority: String,
+    },
+    /// Set a time estimate for a task
+    Estimate {
+        /// The ID of the task to estimate
+        id: usize,
+        /// The estimated duration, e.g. 30m, 2h or 1.5h
+        duration: String,
+    },
+    /// Start tracking the time spent on a task
+    Start {
+        /// The ID of the task
+        id: usize,
+    },
+    /// Stop tracking the time of a task
+    Stop {
+        /// The ID of the task
+        id: usize,
+    },
+    /// Log time spent on a task afterwards (ending now)
+    Log {
+        /// The ID of the task
+        id: usize,
+        /// The time spent, e.g. 30m, 2h or 1.5h
+        #[arg(long, short)]
+        duration: String,
+        /// What was done
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Show all details of a task, including its notes and time log
+    Show {
+        /// The ID of the task
+        id: usize,
+    },
+    /// Set or remove the due date of a task
+    #[command(group(ArgGroup::new("due_date").required(true).args(["date", "clear"])))]
+    Due {
+        /// The ID of the task
+        id: usize,
+        /// The due date (YYYY-MM-DD)
+        date: Option<NaiveDate>,
+        /// Remove the due date
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Hide a task from the list until a day in the future
+    Snooze {
+        /// The ID of the task
+        id: usize,
+        /// The day the task shows up again (YYYY-MM-DD)
+        date: NaiveDate,
+    },
+    /// Show a snoozed task in the list again
+    Unsnooze {
+        /// The ID of the task
+        id: usize,
+    },
+    /// Show the tasks due in the next days, grouped by day
+    #[command(visible_alias = "wk")]
+    Week {
+        /// Number of days to show
+        #[arg(long, value_name = "N", default_value_t = 7)]
+        days: u32,
+    },
+    /// Show the tasks due in the next 30 days (same as week --days 30)
+    Month,
+    /// Show how many tasks were completed per day as a bar chart
+    Report {
+        /// Number of days to look back
+        #[arg(long, value_name = "N", default_value_t = 30)]
+        days: u32,
+        /// Sum up the completions per week (starting on Monday)
+        #[arg(long)]
+        weeks: bool,
+    },
+    /// Assign a task to a person
+    Assign {
+        /// The ID of the task to assign
+        id: usize,
+        /// The name of the person; username from the configuration or $USER if not given
+        username: Option<String>,
+    },
+    /// Remove the assignee of a task
+    Unassign {
+        /// The ID of the task to unassign
+        id: usize,
+    },
+    /// Attach a link (http or https URL) to a task
+    Link {
+        /// The ID of the task
+        id: usize,
+        /// The URL, e.g. of a ticket or document
+        url: String,
+    },
+    /// Remove the link of a task
+    Unlink {
+        /// The ID of the task
+        id: usize,
+    },
+    /// Open the link of a task in the browser
+    Open {
+        /// The ID of the task
+        id: usize,
+    },
+    /// Show statistics about your tasks
+    Stats {
+        /// Count the tasks per "+tag" of their description instead
+        #[arg(long, conflicts_with = "by_context")]
+        by_tag: bool,
+        /// Count the tasks per "@context" of their description instead
+        #[arg(long)]
+        by_context: bool,
+    },
+    /// Exchange the positions of two tasks in the list
+    Swap {
+        /// The ID of the first task
+        id1: usize,
+        /// The ID of the second task
+        id2: usize,
+    },
+    /// Move a task before or after another task in the list, or into another workspace
+    #[command(group(ArgGroup::new("position").required(true).args(["before", "after", "to_workspace"])))]
+    Move {
+        /// The ID of the task to move
+        id: usize,
+        /// Move the task in front of the task with this ID
+        #[arg(long, value_name = "TARGET_ID")]
+        before: Option<usize>,
+        /// Move the task behind the task with this ID
+        #[arg(long, value_name = "TARGET_ID")]
+        after: Option<usize>,
+        /// Move the task to the end of this workspace, where it gets a new ID
+        #[arg(long, value_name = "NAME")]
+        to_workspace: Option<String>,
+    },
+    /// Renumber all tasks 1, 2, 3, ... in their list order
+    #[command(visible_alias = "reindex")]
+    SortId {
+        /// Don't ask for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Delete a task, or all tasks matching the given filters
     #[command(visible_alias = "d")]
+    #[command(group(ArgGroup::new("target").required(true).args(["id", "desc", "completed", "priority", "interactive"])))]
     Delete {
         /// The ID of the task to delete
-        #[arg(required = true)]
-        id: usize,
+        #[arg(conflicts_with_all = ["completed", "priority"])]
+        id: Option<usize>,
+        /// Choose the task by (the start of) its description instead of its ID
+        #[arg(long, value_name = "TEXT")]
+        desc: Option<String>,
+        /// Delete all completed tasks
+        #[arg(long)]
+        completed: bool,
+        /// Delete all tasks with this priority (low, medium or high; or l, m, h)
+        #[arg(long)]
+        priority: Option<Priority>,
+        /// Show the tasks and ask for the ID of the one to delete
+        #[arg(long, short)]
+        interactive: bool,
     },
     /// Clear all completed task from the list
     #[command(visible_alias = "clr")]
     Clear,
+    /// Run commands read from stdin, one per line (e.g. "add Buy milk", "complete 3"),
+    /// and save once at the end
+    Batch,
+    /// Delete duplicate tasks: the same description, or similar ones with --fuzzy
+    Dedup {
+        /// Also delete tasks with a similar description (similarity 0.0 - 1.0, default 0.8);
+        /// the task with the highest priority is kept
+        #[arg(long, value_name = "THRESHOLD", num_args = 0..=1, default_missing_value = "0.8")]
+        fuzzy: Option<f32>,
+    },
+    /// Delete the completed tasks that weren't changed for some time
+    Purge {
+        /// Completed longer ago than this, e.g. 30d, 2w or 1m (30 days)
+        #[arg(long, value_name = "AGE", value_parser = parse_duration)]
+        older_than: Duration,
+    },
+    /// Show the task list and refresh it whenever the task file changes
+    #[command(visible_alias = "w")]
+    Watch {
+        /// Seconds between checks for changes
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+    },
+    /// Create a backup of the task file, or list and restore backups
+    Backup {
+        /// List all backups, oldest first
+        #[arg(long, conflicts_with = "restore")]
+        list: bool,
+        /// Restore the backup with this timestamp (as shown by --list)
+        #[arg(long, value_name = "TIMESTAMP")]
+        restore: Option<String>,
+    },
+    /// Convert the task file to another storage format
+    Convert {
+        /// The target format (json, toml or yaml)
+        #[arg(long, value_name = "FORMAT")]
+        to: StorageFormat,
+    },
+    /// View and edit the notes of tasks
+    #[command(subcommand_required = true)]
+    Notes {
+        #[command(subcommand)]
+        action: NotesCommand,
+    },
+    /// Show the differences between two task files (exit code 1 if they differ)
+    Diff {
+        /// The older task file
+        file1: PathBuf,
+        /// The newer task file
+        file2: PathBuf,
+    },
+    /// Export the tasks into another format
+    #[command(subcommand_required = true)]
+    Export {
+        #[command(subcommand)]
+        format: ExportCommand,
+    },
+    /// Import tasks from another application
+    #[command(subcommand_required = true)]
+    Import {
+        #[command(subcommand)]
+        source: ImportCommand,
+    },
+    /// Inspect the audit log
+    #[command(subcommand_required = true)]
+    Audit {
+        #[command(subcommand)]
+        action: AuditCommand,
+    },
+    /// Check the task file and its disk for problems
+    Doctor,
+    /// Print the shell completion script, e.g. `source <(taskmaster completions bash)`
+    Completions {
+        /// bash, zsh, fish, elvish or powershell
+        shell: Shell,
+        /// Complete task IDs (with their description) from the current task list
+        /// (bash, zsh and fish)
+        #[arg(long)]
+        dynamic_ids: bool,
+    },
+    /* prints "<id>\t<description>" per task for the completion scripts */
+    #[command(name = completions::COMPLETE_IDS_COMMAND, hide = true)]
+    CompleteIds,
+    /* prints the ID of the first pending task, for scripts like
+     * `taskmaster complete $(taskmaster _first-pending-id)`; exit code 1 if there is none */
+    #[command(name = "_first-pending-id", hide = true)]
+    FirstPendingId,
+    /// Warn about task management anti-patterns, e.g. urgent tasks without due date
+    Lint {
+        /// Fix what can be fixed: move long descriptions into the notes, clear old completed
+        /// tasks
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Save and reuse patterns for new tasks
+    #[command(subcommand_required = true)]
+    Template {
+        #[command(subcommand)]
+        action: TemplateCommand,
+    },
+    /// Manage workspaces: separate task lists
+    #[command(subcommand_required = true)]
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceCommand,
+    },
     /// Changes into an interactive mode
     #[command(visible_alias = "i")]
     Interactive,
 }
 
+#[derive(Subcommand)]
+enum NotesCommand {
+    /// Show the notes of a task
+    Show {
+        /// The ID of the task
+        id: usize,
+    },
+    /// Replace the notes of a task
+    Set {
+        /// The ID of the task
+        id: usize,
+        /// The new notes
+        #[arg(required = true)]
+        text: Vec<String>,
+    },
+    /// Add a line to the notes of a task
+    Append {
+        /// The ID of the task
+        id: usize,
+        /// The line to add
+        #[arg(required = true)]
+        text: Vec<String>,
+    },
+    /// Remove the notes of a task
+    Clear {
+        /// The ID of the task
+        id: usize,
+    },
+    /// List all tasks that have notes, with the first line of their notes
+    List,
+}
+
+#[derive(Subcommand)]
+enum TemplateCommand {
+    /// Save a template, replacing one with the same name
+    #[command(group(ArgGroup::new("source").required(true).args(["description", "from_task"])))]
+    Save {
+        /// Name of the template (letters, digits, '-' and '_')
+        name: String,
+        /// Description of the new tasks; {date} and {user} are filled in when it's used
+        description: Vec<String>,
+        /// Take the description and priority of this task
+        #[arg(long, value_name = "ID")]
+        from_task: Option<usize>,
+        /// Priority of the new tasks (low, medium or high; or l, m, h)
+        #[arg(long)]
+        priority: Option<Priority>,
+        /// Tag added to the description as +TAG; can be given more than once
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Context added to the description as @CONTEXT
+        #[arg(long)]
+        context: Option<String>,
+    },
+    /// Add a task from a template
+    Use {
+        /// Name of the template
+        name: String,
+        /// Use this description instead of the one of the template
+        #[arg(long, value_name = "TEXT")]
+        description_override: Option<String>,
+    },
+    /// List all templates
+    List,
+    /// Delete a template
+    Delete {
+        /// Name of the template
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum WorkspaceCommand {
+    /// List all workspaces, the active one is marked
+    List,
+    /// Create a new, empty workspace
+    New {
+        /// Name of the workspace (letters, digits, '-' and '_')
+        name: String,
+    },
+    /// Make another workspace the active one
+    Switch {
+        /// Name of the workspace
+        name: String,
+    },
+    /// Delete a workspace with all its tasks
+    Delete {
+        /// Name of the workspace
+        name: String,
+        /// Don't ask for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    /// HTML page with a sortable, searchable table of all tasks
+    Html {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// iCalendar file with one VTODO per task
+    Ical {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Only export tasks with a due date, e.g. for a calendar
+        #[arg(long)]
+        due_only: bool,
+    },
+    /// JSON array with all fields of the tasks, like the task file
+    Json {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Indent the JSON over several lines (the default)
+        #[arg(long, conflicts_with = "compact")]
+        pretty: bool,
+        /// Write the JSON on a single line without spaces, e.g. for piping
+        #[arg(long)]
+        compact: bool,
+    },
+    /// YAML document with all fields of the tasks
+    Yaml {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// CSV table for spreadsheets (without time entries)
+    Csv {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Emacs Org-mode file with a TODO or DONE headline per task
+    Orgmode {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Plain text, one line per task: "[x] #5 (High) Buy groceries due:2024-11-10 #shopping"
+    Text {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Only the completed tasks, e.g. to archive them before clearing them
+    Completed {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// json or csv
+        #[arg(long, default_value = "json")]
+        format: ArchiveFormat,
+        /// Remove the completed tasks from the list once the file is written
+        #[arg(long, requires = "output")]
+        then_clear: bool,
+    },
+}
+
+/// File formats of `export completed`
+#[derive(Debug, Clone, Copy)]
+enum ArchiveFormat {
+    Json,
+    Csv,
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ArchiveFormat::Json),
+            "csv" => Ok(ArchiveFormat::Csv),
+            _ => Err(TaskError::ArgumentMismatch(format!(
+                "unknown format '{}', expected json or csv",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ImportCommand {
+    /// Issues of a GitHub repository; issues that were imported before are skipped
+    GithubIssues {
+        /// The repository as <owner>/<repo>
+        repo: String,
+        /// Access token for private repositories (default: $GITHUB_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
+        /// Only import issues with this label
+        #[arg(long)]
+        label: Option<String>,
+        /// Only import issues assigned to this user
+        #[arg(long)]
+        assignee: Option<String>,
+    },
+    /// Tasks from a YAML file as written by `export yaml`; they are added with new IDs
+    Yaml {
+        /// The YAML file
+        file: PathBuf,
+    },
+    /// Tasks from a CSV file with a header row, e.g. written by `export csv`; only the
+    /// description column is required. They are added with new IDs.
+    Csv {
+        /// The CSV file
+        file: PathBuf,
+    },
+    /// Headlines of an Emacs Org-mode file, e.g. written by `export orgmode`; they are added
+    /// with new IDs
+    Orgmode {
+        /// The .org file
+        file: PathBuf,
+    },
+    /// Plain text with one task per line as written by `export text`; they are added with new
+    /// IDs
+    Text {
+        /// The text file
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditCommand {
+    /// Show the recorded changes
+    Show {
+        /// Only show changes from this day on (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        since: Option<NaiveDate>,
+        /// Only show changes of the task with this ID
+        #[arg(long, value_name = "ID")]
+        task_id: Option<usize>,
+    },
+}
+
 /* the work is done in run_app - main just encapsulates it and makes
  * sure the Display value of the returned Error is printed (instead of Debug)
  */
@@ -86,34 +808,260 @@ fn main() {
 
 fn run_app() -> Result<(), TaskError> {
     // 0. parse Arguments
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    let verbosity = Verbosity::from_flag(cli.verbose);
 
     // 1. determine file path and create new TaskManager from it
-    let todo_file_path = get_todo_file_path()?;
+    let config = load_config()?;
+    if let Commands::Workspace { action } = &cli.command {
+        // workspace commands work on the workspaces, not on a task list
+        return run_workspace_command(action, &config, cli.dry_run);
+    }
+    if let Commands::Completions { shell, dynamic_ids } = &cli.command {
+        return completions::print_completions(&mut Cli::command(), *shell, *dynamic_ids);
+    }
+    let workspace_name = match &cli.workspace {
+        Some(name) => name.clone(),
+        None => workspace::active_workspace(&config)?,
+    };
+    if !workspace::workspace_exists(&workspace_name, &config)? {
+        return Err(TaskError::ArgumentMismatch(format!(
+            "workspace '{}' doesn't exist, create it with: taskmaster workspace new {}",
+            workspace_name, workspace_name
+        )));
+    }
+    let todo_file_path = workspace::workspace_path(&workspace_name, &config)?;
     let mut task_manager = TaskManager::new(todo_file_path);
+    task_manager.set_config(config);
+    // with the telemetry feature, errors of the task file go to Sentry (if SENTRY_DSN is set)
+    #[cfg(feature = "telemetry")]
+    let _sentry_guard = telemetry::init(&mut task_manager);
+    if let Commands::Doctor = cli.command {
+        // runs before loading, which fails on many of the problems it finds
+        println!(
+            "Checking {} (workspace {})",
+            task_manager.get_file_path().display(),
+            workspace_name
+        );
+        print_health_issues(&task_manager.health_check());
+        return Ok(());
+    }
+    if verbosity == Verbosity::Verbose {
+        print_health_issues(&task_manager.health_check());
+        task_manager.set_verbose(true);
+    }
     task_manager.load_tasks()?;
+    task_manager.wake_snoozed_tasks();
+    resolve_desc(&mut cli.command, &task_manager)?;
+    let add_form = fill_in_interactively(&mut cli.command, &task_manager)?;
+    if cli.audit {
+        task_manager.set_audit_log(Some(TaskAuditLog::new(get_audit_log_path()?)));
+    }
+
+    if cli.dry_run {
+        task_manager.set_dry_run(true);
+        if let Some((action, saved_count)) = dry_run_action(&cli.command, &task_manager)? {
+            println!("Would {}", action);
+            if let Some(count) = saved_count {
+                println!(
+                    "Would save {} tasks to {}",
+                    count,
+                    task_manager.get_file_path().display()
+                );
+            }
+            return Ok(());
+        }
+    }
 
     // 2. work on given command
     match &cli.command {
-        Commands::Add { description } => {
-            let desc_str = build_description(description)?;
-            let new_index = task_manager.add_task(desc_str);
+        Commands::Add {
+            description,
+            parent,
+            ..
+        } => {
+            let desc_str = build_description(description, &task_manager)?;
+            if let Some(parent) = parent {
+                task_manager
+                    .at(*parent)
+                    .ok_or(TaskError::TaskNotFound(*parent))?;
+            }
+            let new_index = task_manager.add_task(desc_str)?;
             println!(
                 "Added Task #{}: {}",
                 new_index,
                 task_manager.at(new_index).unwrap().get_description()
             );
+            if let Some(form) = add_form {
+                if let Some(priority) = form.priority {
+                    println!("{}", task_manager.set_priority(new_index, priority)?);
+                }
+                if let Some(due) = form.due {
+                    println!("{}", task_manager.set_due(new_index, Some(due))?);
+                }
+            }
+            if parent.is_some() {
+                println!("{}", task_manager.set_parent(new_index, *parent)?);
+            }
         }
-        Commands::Change { id, description } => {
-            let desc_str = build_description(description)?;
-            let msg = task_manager.change_description(*id, desc_str)?;
+        Commands::Change {
+            id, description, ..
+        } => {
+            let desc_str = build_description(description, &task_manager)?;
+            let msg = task_manager.change_description(given_id(*id)?, desc_str)?;
             println!("{}", msg);
         }
-        Commands::List => {
-            task_manager.list_tasks();
+        Commands::Sanitize { id, .. } => {
+            let changed = match id {
+                Some(id) => match task_manager.sanitize_task(*id)? {
+                    true => vec![*id],
+                    false => Vec::new(),
+                },
+                None => task_manager.sanitize_all(),
+            };
+            for id in &changed {
+                if let Some(task) = task_manager.at(*id) {
+                    println!("Cleaned up #{}: '{}'", id, task.get_description());
+                }
+            }
+            println!("Cleaned up {} descriptions", changed.len());
+        }
+        Commands::List {
+            also,
+            limit,
+            assignee,
+            mine,
+            has_link,
+            min_words,
+            max_words,
+            since,
+            before,
+            completed_since,
+            completed_before,
+            group_by,
+            sort_by,
+            page_size,
+            page,
+            format,
+            long,
+            show_snoozed,
+            starred,
+            priority,
+        } => {
+            for path in also {
+                task_manager.load_additional(path)?;
+            }
+            let mut filter = TaskFilter {
+                assignee: assignee.clone(),
+                has_link: *has_link,
+                min_words: *min_words,
+                max_words: *max_words,
+                created_since: *since,
+                created_before: *before,
+                completed_since: *completed_since,
+                completed_before: *completed_before,
+                include_snoozed: *show_snoozed,
+                starred: *starred,
+                priority: *priority,
+                ..TaskFilter::default()
+            };
+            if *mine {
+                match current_username(task_manager.get_config()) {
+                    Some(user) => filter.assignee = Some(user),
+                    None => eprintln!(
+                        "{}",
+                        "Warning: --mine needs a username, but neither username in the \
+                         configuration nor $USER is set. Showing all tasks."
+                            .yellow()
+                    ),
+                }
+            }
+            let group_by = group_by
+                .or(task_manager.get_config().default_group_by)
+                .unwrap_or_default();
+            if !sort_by.is_empty() {
+                task_manager.sort_tasks(sort_by);
+            }
+            task_manager.set_long_list(*long);
+            if let Some(template) = format {
+                print_with_template(&task_manager, &filter, *limit, template);
+                // nothing changed
+                return Ok(());
+            }
+            let list = if *page_size > 0 {
+                task_manager.format_task_list_page(&filter, group_by, *page, *page_size)
+            } else {
+                task_manager.format_task_list_grouped(&filter, *limit, group_by)
+            };
+            print!("{}", list);
+            if !sort_by.is_empty() {
+                // listing doesn't change anything - don't store the sorted order
+                return Ok(());
+            }
+        }
+        Commands::Find { fuzzy, query } => {
+            let query = query.join(" ").trim().to_string();
+            if query.is_empty() {
+                return Err(TaskError::Empty("Search text".to_string()));
+            }
+            let found: Vec<&Task> = if *fuzzy {
+                task_manager
+                    .fuzzy_find(&query)
+                    .into_iter()
+                    .map(|(task, _)| task)
+                    .collect()
+            } else {
+                task_manager.find_by_description(&query)
+            };
+            if found.is_empty() {
+                println!("No tasks match '{}'", query);
+            } else {
+                task_manager.print_tasks(&found);
+            }
+        }
+        Commands::Today => {
+            print_today(&task_manager);
+        }
+        Commands::Random { priority, weighted } => {
+            print_random_task(&task_manager, *priority, *weighted);
+            // picking doesn't change anything
+            return Ok(());
         }
-        Commands::Complete { id } => {
-            let msg = task_manager.complete_task(*id)?;
+        Commands::Stale { older_than } => {
+            print_stale(
+                &task_manager,
+                older_than.unwrap_or(days_duration(DEFAULT_STALE_DAYS)),
+            );
+            return Ok(());
+        }
+        Commands::Complete { id, force, .. } => {
+            let id = given_id(*id)?;
+            let msg = match task_manager.complete_task(id) {
+                Err(TaskError::HasPendingChildren { .. }) if *force => {
+                    task_manager.complete_task_with_subtasks(id)?
+                }
+                Err(TaskError::HasPendingChildren {
+                    parent_id,
+                    pending_child_ids,
+                }) => {
+                    let noun = if pending_child_ids.len() == 1 {
+                        "subtask"
+                    } else {
+                        "subtasks"
+                    };
+                    if !confirm(&format!(
+                        "Task #{} has {} pending {}. Complete anyway? [y/N] ",
+                        parent_id,
+                        pending_child_ids.len(),
+                        noun
+                    ))? {
+                        println!("Nothing completed.");
+                        return Ok(());
+                    }
+                    task_manager.complete_task_with_subtasks(id)?
+                }
+                result => result?,
+            };
             println!("{}", msg);
         }
         Commands::Up { id } => {
@@ -124,14 +1072,656 @@ fn run_app() -> Result<(), TaskError> {
             let msg = task_manager.deprioritize_task(*id)?;
             println!("{}", msg);
         }
-        Commands::Delete { id } => {
+        Commands::Focus { id } => {
+            let msg = task_manager.focus_task(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Unfocus => {
+            println!("{}", task_manager.unfocus_task());
+        }
+        Commands::Pin { id } => {
+            let msg = task_manager.pin_task(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Unpin { id } => {
+            let msg = task_manager.unpin_task(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Mark { id } => {
+            let msg = task_manager.star_task(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Unmark { id } => {
+            let msg = task_manager.unstar_task(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Priority { id, priority } => {
+            let msg = task_manager.set_priority_by_name(*id, priority)?;
+            println!("{}", msg);
+        }
+        Commands::Estimate { id, duration } => {
+            let minutes = parse_estimate(duration)?;
+            let msg = task_manager.estimate_task(*id, minutes)?;
+            println!("{}", msg);
+        }
+        Commands::Start { id } => {
+            let msg = task_manager.start_timer(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Stop { id } => {
+            let msg = task_manager.stop_timer(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Log { id, duration, note } => {
+            let minutes = parse_estimate(duration)?;
+            let msg = task_manager.log_time(*id, minutes, note.clone())?;
+            println!("{}", msg);
+        }
+        Commands::Show { id } => {
+            let task = task_manager.at(*id).ok_or(TaskError::TaskNotFound(*id))?;
+            print_task_details(task, &task_manager.get_config().theme);
+            return Ok(());
+        }
+        Commands::Due { id, date, clear: _ } => {
+            let msg = task_manager.set_due(*id, *date)?;
+            println!("{}", msg);
+        }
+        Commands::Snooze { id, date } => {
+            let msg = task_manager.snooze_task(*id, *date)?;
+            println!("{}", msg);
+        }
+        Commands::Unsnooze { id } => {
+            let msg = task_manager.unsnooze_task(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Week { days } => {
+            print_week(&task_manager, *days);
+        }
+        Commands::Month => {
+            print_week(&task_manager, 30);
+        }
+        Commands::Report { days, weeks } => {
+            print_report(&task_manager, *days, *weeks);
+        }
+        Commands::Assign { id, username } => {
+            let username = match username {
+                Some(name) => name.clone(),
+                None => current_username(task_manager.get_config())
+                    .ok_or_else(|| TaskError::Empty("Username".to_string()))?,
+            };
+            let msg = task_manager.assign_task(*id, username)?;
+            println!("{}", msg);
+        }
+        Commands::Unassign { id } => {
+            let msg = task_manager.unassign_task(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Link { id, url } => {
+            let msg = task_manager.link_task(*id, url.trim())?;
+            println!("{}", msg);
+        }
+        Commands::Unlink { id } => {
+            let msg = task_manager.unlink_task(*id)?;
+            println!("{}", msg);
+        }
+        Commands::Open { id } => {
+            open_url(task_manager.task_url(*id)?)?;
+            return Ok(());
+        }
+        Commands::Stats { by_tag, by_context } => {
+            if *by_tag {
+                print_tag_stats(&task_manager.statistics_by_tag(), '+');
+            } else if *by_context {
+                print_tag_stats(&task_manager.statistics_by_context(), '@');
+            } else {
+                print_stats(&task_manager);
+            }
+        }
+        Commands::Swap { id1, id2 } => {
+            let msg = task_manager.swap_positions(*id1, *id2)?;
+            println!("{}", msg);
+        }
+        Commands::SortId { yes } => {
+            let mapping = task_manager.reindex_mapping();
+            if mapping.is_empty() {
+                println!("The task IDs are already sequential.");
+                return Ok(());
+            }
+            if !yes
+                && !confirm(&format!(
+                    "Renumber {} tasks? References to the old IDs elsewhere won't match anymore. [y/N] ",
+                    mapping.len()
+                ))?
+            {
+                println!("Nothing renumbered.");
+                return Ok(());
+            }
+            task_manager.reindex();
+            println!("{:>6}    {}", "Old ID".bold(), "New ID".bold());
+            for (old_id, new_id) in mapping {
+                println!("{:>6} →  {}", old_id, new_id.to_string().cyan());
+            }
+        }
+        Commands::Move {
+            id,
+            before,
+            after,
+            to_workspace,
+        } => {
+            if let Some(target) = before {
+                task_manager.move_before(*id, *target)?;
+                println!("Moved #{} before #{}", id, target);
+            } else if let Some(target) = after {
+                task_manager.move_after(*id, *target)?;
+                println!("Moved #{} after #{}", id, target);
+            } else if let Some(name) = to_workspace {
+                let path = existing_workspace_path(name, task_manager.get_config())?;
+                let new_id = task_manager.move_task_to_file(*id, &path)?;
+                println!("Moved task #{} to workspace '{}' as #{}", id, name, new_id);
+                // both files are saved already
+                return Ok(());
+            }
+        }
+        Commands::Delete { id: Some(id), .. } => {
             let msg = task_manager.delete_task(*id)?;
             println!("{}", msg);
         }
+        Commands::Delete {
+            id: None,
+            completed,
+            priority,
+            ..
+        } => {
+            let removed = task_manager.remove_where(|t| {
+                (!*completed || t.get_completed()) && priority.is_none_or(|p| t.priority() == p)
+            });
+            println!("Deleted {} tasks", removed.len());
+            for task in removed {
+                println!("\t'{}'", task.get_description());
+            }
+        }
         Commands::Clear => {
             let cleared_count = task_manager.clear_completed_tasks();
             println!("Cleared {} completed tasks", cleared_count);
         }
+        Commands::Batch => {
+            for (line, result) in task_manager.apply_from_stdin()? {
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("{} {}", format!("line {}:", line).red(), e),
+                }
+            }
+        }
+        Commands::Dedup { fuzzy } => {
+            remove_duplicates(&mut task_manager, dedup_strategy(*fuzzy)?);
+        }
+        Commands::Purge { older_than } => {
+            let purged = task_manager.purge_old_completed(*older_than);
+            println!(
+                "Purged {} tasks completed more than {} days ago",
+                purged,
+                whole_days(*older_than)
+            );
+        }
+        Commands::Watch { interval } => {
+            // watching only reads - return early, so nothing is written back to the file
+            return watch_tasks(&mut task_manager, *interval);
+        }
+        Commands::Backup { list, restore } => {
+            let backup_dir = task_manager.backup_dir();
+            if *list {
+                let backups = task_manager.list_backups(&backup_dir)?;
+                if backups.is_empty() {
+                    println!("No backups in {}", backup_dir.display());
+                }
+                for backup in backups {
+                    println!("{}", backup.display());
+                }
+                // listing doesn't change anything, so don't save (which might create a backup)
+                return Ok(());
+            } else if let Some(timestamp) = restore {
+                let backup = task_manager.restore_backup(&backup_dir, timestamp)?;
+                println!("Restored tasks from {}", backup.display());
+            } else {
+                let backup = task_manager.backup(&backup_dir)?;
+                println!("Created backup {}", backup.display());
+                return Ok(());
+            }
+        }
+        Commands::Convert { to } => {
+            let configured = task_manager.get_config().format;
+            let path = task_manager.convert_to(*to)?;
+            println!("Converted tasks to {}", path.display());
+            if configured.is_some_and(|f| f != *to) {
+                println!(
+                    "{}",
+                    format!(
+                        "Note: ~/.taskmasterrc sets another format, change it to \"{}\" to use the new file.",
+                        to.extension()
+                    )
+                    .yellow()
+                );
+            }
+            // the new file was just written
+            return Ok(());
+        }
+        Commands::Notes {
+            action: NotesCommand::Show { id },
+        } => {
+            let task = task_manager.at(*id).ok_or(TaskError::TaskNotFound(*id))?;
+            print_notes_box(task);
+            return Ok(());
+        }
+        Commands::Notes {
+            action: NotesCommand::Set { id, text },
+        } => {
+            let msg = task_manager.set_notes(*id, Some(text.join(" ")))?;
+            println!("{}", msg);
+        }
+        Commands::Notes {
+            action: NotesCommand::Append { id, text },
+        } => {
+            let msg = task_manager.append_note(*id, &text.join(" "))?;
+            println!("{}", msg);
+        }
+        Commands::Notes {
+            action: NotesCommand::Clear { id },
+        } => {
+            let msg = task_manager.set_notes(*id, None)?;
+            println!("{}", msg);
+        }
+        Commands::Notes {
+            action: NotesCommand::List,
+        } => {
+            let mut any = false;
+            for task in task_manager.tasks_matching(|t| t.get_notes().is_some()) {
+                let first_line = task
+                    .get_notes()
+                    .and_then(|n| n.lines().next())
+                    .unwrap_or("");
+                println!(
+                    "{:>3}: {} {}",
+                    task.get_id(),
+                    task.get_description(),
+                    format!("— {}", first_line).dimmed()
+                );
+                any = true;
+            }
+            if !any {
+                println!("No tasks with notes.");
+            }
+            return Ok(());
+        }
+        Commands::Diff { file1, file2 } => {
+            let load = |path: &PathBuf| -> Result<Vec<Task>, TaskError> {
+                if !path.exists() {
+                    return Err(TaskError::ArgumentMismatch(format!(
+                        "{} doesn't exist",
+                        path.display()
+                    )));
+                }
+                let mut manager = TaskManager::new(path.clone());
+                manager.load_tasks()?;
+                Ok(manager.iter().cloned().collect())
+            };
+            let (before, after) = (load(file1)?, load(file2)?);
+            let diff = TaskManager::diff(&before, &after);
+            if diff.is_empty() {
+                println!("{}", "No differences.".green());
+                return Ok(());
+            }
+            for line in diff.to_string().lines() {
+                match line.chars().next() {
+                    Some('+') => println!("{}", line.green()),
+                    Some('-') => println!("{}", line.red()),
+                    _ => println!("{}", line.yellow()),
+                }
+            }
+            // like diff(1): exit code 1 means the files differ
+            std::process::exit(1);
+        }
+        Commands::Export {
+            format: ExportCommand::Html { output },
+        } => {
+            let html = task_manager.to_html();
+            match output {
+                Some(path) => {
+                    fs::write(path, html)?;
+                    println!(
+                        "Exported {} tasks to {}",
+                        task_manager.iter().count(),
+                        path.display()
+                    );
+                }
+                None => print!("{}", html),
+            }
+            return Ok(());
+        }
+        Commands::Export {
+            format: ExportCommand::Ical { output, due_only },
+        } => {
+            let tasks: Vec<Task> = task_manager
+                .iter()
+                .filter(|t| !*due_only || t.get_due().is_some())
+                .cloned()
+                .collect();
+            let ics = export_ical_with_uid_base(&tasks, &task_manager.get_config().ical_uid_base);
+            match output {
+                Some(path) => {
+                    fs::write(path, ics)?;
+                    println!("Exported {} tasks to {}", tasks.len(), path.display());
+                }
+                None => print!("{}", ics),
+            }
+            // exporting doesn't change the tasks
+            return Ok(());
+        }
+        Commands::Export {
+            format:
+                ExportCommand::Json {
+                    output,
+                    pretty: _,
+                    compact,
+                },
+        } => {
+            let tasks: Vec<Task> = task_manager
+                .iter()
+                .filter(|t| t.get_source_file().is_none())
+                .cloned()
+                .collect();
+            let serializer = if *compact {
+                JsonSerializer::compact()
+            } else {
+                JsonSerializer::pretty()
+            };
+            let json = serializer.serialize(&tasks)?;
+            match output {
+                Some(path) => {
+                    fs::write(path, json)?;
+                    println!("Exported {} tasks to {}", tasks.len(), path.display());
+                }
+                None => io::stdout().write_all(&json)?,
+            }
+            return Ok(());
+        }
+        Commands::Export {
+            format: ExportCommand::Yaml { output },
+        } => {
+            let yaml = task_manager.to_yaml()?;
+            match output {
+                Some(path) => {
+                    fs::write(path, yaml)?;
+                    println!(
+                        "Exported {} tasks to {}",
+                        task_manager
+                            .iter()
+                            .filter(|t| t.get_source_file().is_none())
+                            .count(),
+                        path.display()
+                    );
+                }
+                None => print!("{}", yaml),
+            }
+            return Ok(());
+        }
+        Commands::Export {
+            format: ExportCommand::Csv { output },
+        } => {
+            match output {
+                Some(path) => {
+                    task_manager.export_with(fs::File::create(path)?, CsvSerializer)?;
+                    println!(
+                        "Exported {} tasks to {}",
+                        task_manager
+                            .iter()
+                            .filter(|t| t.get_source_file().is_none())
+                            .count(),
+                        path.display()
+                    );
+                }
+                None => task_manager.export_with(io::stdout().lock(), CsvSerializer)?,
+            }
+            return Ok(());
+        }
+        Commands::Export {
+            format: ExportCommand::Orgmode { output },
+        } => {
+            let tasks: Vec<Task> = task_manager.iter().cloned().collect();
+            let org = to_orgmode(&tasks);
+            match output {
+                Some(path) => {
+                    fs::write(path, org)?;
+                    println!("Exported {} tasks to {}", tasks.len(), path.display());
+                }
+                None => print!("{}", org),
+            }
+            return Ok(());
+        }
+        Commands::Export {
+            format: ExportCommand::Text { output },
+        } => {
+            let text = task_manager.to_plain_text();
+            match output {
+                Some(path) => {
+                    fs::write(path, text)?;
+                    println!(
+                        "Exported {} tasks to {}",
+                        task_manager
+                            .iter()
+                            .filter(|t| t.get_source_file().is_none())
+                            .count(),
+                        path.display()
+                    );
+                }
+                None => print!("{}", text),
+            }
+            return Ok(());
+        }
+        Commands::Export {
+            format:
+                ExportCommand::Completed {
+                    output,
+                    format,
+                    then_clear,
+                },
+        } => {
+            let completed: Vec<Task> = task_manager
+                .iter()
+                .filter(|t| t.get_completed())
+                .cloned()
+                .collect();
+            let data = match format {
+                ArchiveFormat::Json => JsonSerializer::pretty().serialize(&completed)?,
+                ArchiveFormat::Csv => CsvSerializer.serialize(&completed)?,
+            };
+            match output {
+                Some(path) => {
+                    fs::write(path, data)?;
+                    println!(
+                        "Exported {} completed tasks to {}",
+                        completed.len(),
+                        path.display()
+                    );
+                }
+                None => io::stdout().write_all(&data)?,
+            }
+            if !then_clear {
+                return Ok(());
+            }
+            /* only reached once the file is written, so no task gets lost */
+            let cleared = task_manager.drain_completed();
+            println!("Cleared {} completed tasks", cleared.len());
+        }
+        Commands::Import {
+            source: ImportCommand::Orgmode { file },
+        } => {
+            let tasks = import_orgmode(&fs::read_to_string(file)?)?;
+            let added = task_manager.import_tasks(tasks);
+            for id in &added {
+                if let Some(task) = task_manager.at(*id) {
+                    println!("Imported #{}: {}", id, task.get_description());
+                }
+            }
+            println!("Imported {} tasks from {}", added.len(), file.display());
+        }
+        Commands::Import {
+            source: ImportCommand::Text { file },
+        } => {
+            let tasks = TaskManager::from_plain_text(&fs::read_to_string(file)?)?;
+            let added = task_manager.import_tasks(tasks);
+            for id in &added {
+                if let Some(task) = task_manager.at(*id) {
+                    println!("Imported #{}: {}", id, task.get_description());
+                }
+            }
+            println!("Imported {} tasks from {}", added.len(), file.display());
+        }
+        Commands::Import {
+            source: ImportCommand::Yaml { file },
+        } => {
+            let tasks = TaskManager::from_yaml(&fs::read_to_string(file)?)?;
+            let added = task_manager.import_tasks(tasks);
+            for id in &added {
+                if let Some(task) = task_manager.at(*id) {
+                    println!("Imported #{}: {}", id, task.get_description());
+                }
+            }
+            println!("Imported {} tasks from {}", added.len(), file.display());
+        }
+        Commands::Import {
+            source: ImportCommand::Csv { file },
+        } => {
+            let added = task_manager.import_with(fs::File::open(file)?, CsvSerializer)?;
+            for id in &added {
+                if let Some(task) = task_manager.at(*id) {
+                    println!("Imported #{}: {}", id, task.get_description());
+                }
+            }
+            println!("Imported {} tasks from {}", added.len(), file.display());
+        }
+        Commands::Import {
+            source:
+                ImportCommand::GithubIssues {
+                    repo,
+                    token,
+                    label,
+                    assignee,
+                },
+        } => {
+            let token = token.clone().or_else(|| std::env::var("GITHUB_TOKEN").ok());
+            let issues = fetch_github_issues(
+                repo,
+                token.as_deref(),
+                label.as_deref(),
+                assignee.as_deref(),
+            )?;
+            let added = import_issues(&mut task_manager, &issues)?;
+            for id in &added {
+                if let Some(task) = task_manager.at(*id) {
+                    println!("Imported #{}: {}", id, task.get_description());
+                }
+            }
+            println!(
+                "Imported {} of {} issues from {}",
+                added.len(),
+                issues.iter().filter(|i| !i.is_pull_request()).count(),
+                repo
+            );
+        }
+        Commands::Audit {
+            action: AuditCommand::Show { since, task_id },
+        } => {
+            let audit_log = TaskAuditLog::new(get_audit_log_path()?);
+            let entries: Vec<AuditEntry> = audit_log
+                .read_entries()?
+                .into_iter()
+                .filter(|e| {
+                    since.is_none_or(|d| e.timestamp.with_timezone(&Local).date_naive() >= d)
+                })
+                .filter(|e| task_id.is_none_or(|id| e.task_id == id))
+                .collect();
+            if entries.is_empty() {
+                println!("No changes recorded.");
+            }
+            for entry in entries {
+                println!(
+                    "{} {:<18} {} {}",
+                    entry
+                        .timestamp
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                        .dimmed(),
+                    format!("{:?}", entry.operation),
+                    format!("#{}", entry.task_id).cyan().bold(),
+                    entry.detail
+                );
+            }
+        }
+        Commands::Template { action } => {
+            // only `template use` changes the tasks, the others work on the template file
+            let TemplateCommand::Use {
+                name,
+                description_override,
+            } = action
+            else {
+                return run_template_command(action, &task_manager);
+            };
+            let template = find_template(name)?;
+            let description = template_description(&template, description_override.as_deref());
+            let desc_str = build_description(&[description], &task_manager)?;
+            let new_index = task_manager.add_task(desc_str)?;
+            println!(
+                "Added Task #{}: {}",
+                new_index,
+                task_manager.at(new_index).unwrap().get_description()
+            );
+            if let Some(priority) = template.default_priority {
+                println!("{}", task_manager.set_priority(new_index, priority)?);
+            }
+        }
+        Commands::Lint { fix } => {
+            let tasks: Vec<Task> = task_manager.iter().cloned().collect();
+            let warnings = TaskLinter::run(&tasks);
+            if !fix {
+                print_lint_warnings(&warnings);
+                // only looking - nothing to save
+                return Ok(());
+            }
+            let mut fixed = 0;
+            for warning in &warnings {
+                match task_manager.fix_lint_warning(warning)? {
+                    Some(msg) => {
+                        println!("{} {}", "✓".green(), msg);
+                        fixed += 1;
+                    }
+                    None => {
+                        println!("{} {}", "•".bold(), warning.to_string().yellow());
+                        println!("  {}", warning.suggestion().dimmed());
+                    }
+                }
+            }
+            println!("Fixed {} of {} warnings", fixed, warnings.len());
+        }
+        Commands::CompleteIds => {
+            for task in task_manager.iter() {
+                println!("{}\t{}", task.get_id(), task.get_description());
+            }
+            return Ok(());
+        }
+        Commands::FirstPendingId => match task_manager.first_pending() {
+            Some(task) => {
+                println!("{}", task.get_id());
+                return Ok(());
+            }
+            None => {
+                eprintln!("No pending tasks");
+                std::process::exit(1);
+            }
+        },
+        Commands::Workspace { .. } | Commands::Doctor | Commands::Completions { .. } => {
+            unreachable!("workspace, doctor and completions are handled before loading")
+        }
         Commands::Interactive => {
             let mut interactive_mode = InteractiveMode::new(&mut task_manager)?;
             interactive_mode.start_interactive_mode()?;
@@ -144,18 +1734,1250 @@ fn run_app() -> Result<(), TaskError> {
     Ok(()) // indicate succesful execution
 }
 
-fn get_todo_file_path() -> Result<PathBuf, TaskError> {
+/// Describes what a changing command would do, e.g. "complete: Buy milk", together with the
+/// number of tasks that would be saved afterwards (None if the task file isn't saved).
+/// Returns None for commands that don't change the tasks (those run normally in a dry run).
+fn dry_run_action(
+    command: &Commands,
+    task_manager: &TaskManager,
+) -> Result<Option<(String, Option<usize>)>, TaskError> {
+    let task_count = task_manager.iter().count();
+    let desc = |id: &usize| -> Result<String, TaskError> {
+        let task = task_manager.at(*id).ok_or(TaskError::TaskNotFound(*id))?;
+        Ok(format!("#{} '{}'", id, task.get_description()))
+    };
+    let action = match command {
+        Commands::Add { description, .. } => {
+            let action = format!("add: {}", build_description(description, task_manager)?);
+            return Ok(Some((action, Some(task_count + 1))));
+        }
+        Commands::Change {
+            id, description, ..
+        } => format!(
+            "change {}: to '{}'",
+            desc(&given_id(*id)?)?,
+            build_description(description, task_manager)?
+        ),
+        Commands::Sanitize { id, .. } => {
+            let count = task_manager
+                .iter()
+                .filter(|t| id.is_none_or(|id| t.get_id() == id))
+                .filter(|t| {
+                    let sanitized = sanitize_description(t.get_description());
+                    !sanitized.is_empty() && sanitized != t.get_description()
+                })
+                .count();
+            if let Some(id) = id {
+                desc(id)?;
+            }
+            format!("sanitize: {} descriptions", count)
+        }
+        Commands::Complete { id, .. } => {
+            let id = given_id(*id)?;
+            match task_manager.pending_subtasks(id).len() {
+                0 => format!("complete: {}", desc(&id)?),
+                1 => format!("complete: {} and 1 pending subtask", desc(&id)?),
+                n => format!("complete: {} and {} pending subtasks", desc(&id)?, n),
+            }
+        }
+        Commands::Up { id } => format!("raise the priority: {}", desc(id)?),
+        Commands::Down { id } => format!("lower the priority: {}", desc(id)?),
+        Commands::Focus { id } => format!("focus: {}", desc(id)?),
+        Commands::Unfocus => "unfocus: the focused task".to_string(),
+        Commands::Pin { id } => format!("pin: {}", desc(id)?),
+        Commands::Unpin { id } => format!("unpin: {}", desc(id)?),
+        Commands::Mark { id } => format!("star: {}", desc(id)?),
+        Commands::Unmark { id } => format!("unstar: {}", desc(id)?),
+        Commands::Priority { id, priority } => {
+            format!("set priority {}: {}", priority, desc(id)?)
+        }
+        Commands::Start { id } => format!("start the timer: {}", desc(id)?),
+        Commands::Stop { id } => format!("stop the timer: {}", desc(id)?),
+        Commands::Log { id, duration, .. } => format!(
+            "log {}: {}",
+            format_minutes(parse_estimate(duration)? as u64),
+            desc(id)?
+        ),
+        Commands::Estimate { id, duration } => format!(
+            "estimate {}: {}",
+            format_minutes(parse_estimate(duration)? as u64),
+            desc(id)?
+        ),
+        Commands::Due { id, date, .. } => match date {
+            Some(date) => format!("set due date {}: {}", date, desc(id)?),
+            None => format!("remove the due date: {}", desc(id)?),
+        },
+        Commands::Snooze { id, date } => format!("snooze until {}: {}", date, desc(id)?),
+        Commands::Unsnooze { id } => format!("unsnooze: {}", desc(id)?),
+        Commands::Assign { id, username } => {
+            let username = username
+                .clone()
+                .or_else(|| current_username(task_manager.get_config()))
+                .ok_or_else(|| TaskError::Empty("Username".to_string()))?;
+            format!("assign to {}: {}", username, desc(id)?)
+        }
+        Commands::Unassign { id } => format!("unassign: {}", desc(id)?),
+        Commands::Link { id, url } => format!("link to {}: {}", url, desc(id)?),
+        Commands::Notes { action } => match action {
+            NotesCommand::Set { id, .. } => format!("replace the notes: {}", desc(id)?),
+            NotesCommand::Append { id, text } => {
+                format!("add the note '{}': {}", text.join(" "), desc(id)?)
+            }
+            NotesCommand::Clear { id } => format!("clear the notes: {}", desc(id)?),
+            NotesCommand::Show { .. } | NotesCommand::List => return Ok(None),
+        },
+        Commands::Template { action } => match action {
+            TemplateCommand::Use {
+                name,
+                description_override,
+            } => {
+                let template = find_template(name)?;
+                let description = template_description(&template, description_override.as_deref());
+                let action = format!("add: {}", build_description(&[description], task_manager)?);
+                return Ok(Some((action, Some(task_count + 1))));
+            }
+            TemplateCommand::Save { name, .. } => format!("save the template {}", name),
+            TemplateCommand::Delete { name } => {
+                find_template(name)?;
+                format!("delete the template {}", name)
+            }
+            TemplateCommand::List => return Ok(None),
+        },
+        Commands::Lint { fix: true } => {
+            let tasks: Vec<Task> = task_manager.iter().cloned().collect();
+            let warnings = TaskLinter::run(&tasks);
+            let fixable = warnings.iter().filter(|w| w.is_fixable()).count();
+            let cleared = warnings
+                .iter()
+                .filter(|w| matches!(w, LintWarning::OldCompleted { .. }))
+                .count();
+            let action = format!("fix {} of {} lint warnings", fixable, warnings.len());
+            return Ok(Some((action, Some(task_count - cleared))));
+        }
+        Commands::Unlink { id } => format!("unlink: {}", desc(id)?),
+        Commands::Swap { id1, id2 } => format!("swap: {} and {}", desc(id1)?, desc(id2)?),
+        Commands::SortId { .. } => {
+            format!("renumber: {} tasks", task_manager.reindex_mapping().len())
+        }
+        Commands::Move {
+            id,
+            to_workspace: Some(name),
+            ..
+        } => {
+            existing_workspace_path(name, task_manager.get_config())?;
+            let action = format!("move to workspace '{}': {}", name, desc(id)?);
+            return Ok(Some((action, Some(task_count - 1))));
+        }
+        Commands::Move {
+            id, before, after, ..
+        } => match (before, after) {
+            (Some(target), _) => format!("move: {} before {}", desc(id)?, desc(target)?),
+            (_, Some(target)) => format!("move: {} after {}", desc(id)?, desc(target)?),
+            _ => format!("move: {}", desc(id)?),
+        },
+        Commands::Delete { id: Some(id), .. } => {
+            let action = format!("delete: {}", desc(id)?);
+            return Ok(Some((action, Some(task_count - 1))));
+        }
+        Commands::Delete {
+            id: None,
+            completed,
+            priority,
+            ..
+        } => {
+            let count = task_manager
+                .iter()
+                .filter(|t| {
+                    (!*completed || t.get_completed()) && priority.is_none_or(|p| t.priority() == p)
+                })
+                .count();
+            let action = format!("delete: {} tasks", count);
+            return Ok(Some((action, Some(task_count - count))));
+        }
+        Commands::Clear => {
+            let count = task_manager.iter().filter(|t| t.get_completed()).count();
+            let action = format!("clear: {} completed tasks", count);
+            return Ok(Some((action, Some(task_count - count))));
+        }
+        Commands::Dedup { fuzzy } => {
+            let count = task_manager.find_duplicates(dedup_strategy(*fuzzy)?).len();
+            let action = format!("dedup: {} duplicate tasks", count);
+            return Ok(Some((action, Some(task_count - count))));
+        }
+        Commands::Purge { older_than } => {
+            let count = task_manager
+                .iter()
+                .filter(|t| t.get_completed() && t.is_stale(*older_than))
+                .count();
+            let action = format!(
+                "purge: {} tasks completed more than {} days ago",
+                count,
+                whole_days(*older_than)
+            );
+            return Ok(Some((action, Some(task_count - count))));
+        }
+        Commands::Backup {
+            list: false,
+            restore,
+        } => {
+            let action = match restore {
+                Some(timestamp) => format!("restore: the backup from {}", timestamp),
+                None => format!("back up: {}", task_manager.get_file_path().display()),
+            };
+            return Ok(Some((action, None)));
+        }
+        Commands::Export {
+            format:
+                ExportCommand::Completed {
+                    output: Some(path),
+                    then_clear: true,
+                    ..
+                },
+        } => {
+            let count = task_manager.iter().filter(|t| t.get_completed()).count();
+            let action = format!(
+                "export to {} and clear: {} completed tasks",
+                path.display(),
+                count
+            );
+            return Ok(Some((action, Some(task_count - count))));
+        }
+        Commands::Import {
+            source: ImportCommand::GithubIssues { repo, .. },
+        } => {
+            let action = format!("import: the issues of {}", repo);
+            return Ok(Some((action, None)));
+        }
+        Commands::Import {
+            source:
+                ImportCommand::Yaml { file }
+                | ImportCommand::Csv { file }
+                | ImportCommand::Orgmode { file }
+                | ImportCommand::Text { file },
+        } => {
+            let action = format!("import: the tasks of {}", file.display());
+            return Ok(Some((action, None)));
+        }
+        Commands::Convert { to } => format!(
+            "convert: {} to {}",
+            task_manager.get_file_path().display(),
+            to.extension()
+        ),
+        _ => return Ok(None),
+    };
+    Ok(Some((action, Some(task_count))))
+}
+
+/* one line per tag or context (shown with its marker), most tasks first:
+ * "[+work]   12 tasks, 4 done, 3 high-priority" */
+fn print_tag_stats(stats: &HashMap<String, TagStats>, marker: char) {
+    if stats.is_empty() {
+        println!("No tasks with {}words in their description", marker);
+        return;
+    }
+    let mut sorted: Vec<(&String, &TagStats)> = stats.iter().collect();
+    sorted.sort_by(|(a_name, a), (b_name, b)| b.total.cmp(&a.total).then(a_name.cmp(b_name)));
+    let width = sorted
+        .iter()
+        .map(|(name, _)| name.chars().count() + 3)
+        .max()
+        .unwrap_or(0);
+    for (name, tag) in sorted {
+        let label = format!("{:<width$}", format!("[{}{}]", marker, name));
+        println!(
+            "{} {:>3} tasks, {} done, {} high-priority",
+            label.cyan(),
+            tag.total,
+            tag.completed,
+            tag.high_priority
+        );
+    }
+}
+
+/// Prints an overview of the task list
+fn print_stats(task_manager: &TaskManager) {
+    let total = task_manager.iter().count();
+    let completed = task_manager.iter().filter(|t| t.get_completed()).count();
+    println!("{}", "Task statistics:".bold().underline());
+    println!("  {:<12} {}", "Total:", total);
+    println!("  {:<12} {}", "Pending:", total - completed);
+    println!("  {:<12} {}", "Completed:", completed);
+    println!(
+        "  {:<12} {}",
+        "With due:",
+        task_manager.count_with_due_date()
+    );
+    println!("  {:<12} {}", "Overdue:", task_manager.count_overdue());
+    let by_status = task_manager.count_by_status();
+    println!("{}", "By status (as shown in the list):".bold());
+    for status in TaskStatus::ALL {
+        println!(
+            "  {:<16} {}",
+            format!("{} {}:", status, status.name()),
+            by_status.get(&status).copied().unwrap_or(0)
+        );
+    }
+    let all = task_manager.count_by_priority();
+    let pending = task_manager.count_by_priority_pending();
+    println!("{}", "By priority (pending/total):".bold());
+    println!("  {:<12} {}/{}", "High:", pending.high, all.high);
+    println!("  {:<12} {}/{}", "Medium:", pending.medium, all.medium);
+    println!("  {:<12} {}/{}", "Low:", pending.low, all.low);
+    let stale = task_manager
+        .stale_tasks(days_duration(DEFAULT_STALE_DAYS))
+        .len();
+    let stale_line = format!(
+        "  {:<12} {} (untouched for more than {} days)",
+        "Stale:", stale, DEFAULT_STALE_DAYS
+    );
+    if stale > 0 {
+        println!("{}", stale_line.yellow());
+    } else {
+        println!("{}", stale_line);
+    }
+    let logged = task_manager.total_time_spent();
+    if !logged.is_zero() {
+        println!(
+            "Total time logged: {}",
+            format_minutes(logged.as_secs() / 60)
+        );
+    }
+    if let Some(remaining) = task_manager.total_estimated_pending() {
+        println!(
+            "Total estimated work remaining: {}",
+            format_minutes(remaining.as_secs() / 60)
+        );
+    }
+}
+
+/// Prints the pending tasks that weren't changed for more than the given number of days, with
+/// how long ago they were changed
+fn print_stale(task_manager: &TaskManager, threshold: Duration) {
+    let days = whole_days(threshold);
+    let stale = task_manager.stale_tasks(threshold);
+    if stale.is_empty() {
+        println!(
+            "{}",
+            format!("No tasks untouched for more than {} days.", days).green()
+        );
+        return;
+    }
+    println!(
+        "{}",
+        format!(
+            "{} tasks untouched for more than {} days - finish, reschedule or delete them:",
+            stale.len(),
+            days
+        )
+        .yellow()
+        .bold()
+    );
+    let now = chrono::Utc::now();
+    for task in stale {
+        let age = task
+            .get_updated_at()
+            .map(|t| (now - t).num_days())
+            .unwrap_or_default();
+        println!(
+            "{:>4}: {} {} {}",
+            task.get_id(),
+            task.get_priority(&task_manager.get_config().theme),
+            task.get_description(),
+            format!("({} days)", age).dimmed()
+        );
+    }
+}
+
+fn days_duration(days: u64) -> Duration {
+    Duration::from_secs(days.saturating_mul(24 * 60 * 60))
+}
+
+/// Prints the tasks for today below a date header, followed by a short count
+fn print_today(task_manager: &TaskManager) {
+    let header = format!("── Today, {} ──", Local::now().format("%A %Y-%m-%d"));
+    println!("{}", header.bold());
+    let today = task_manager.tasks_for_today();
+    if today.is_empty() {
+        println!("{}", "Nothing urgent today.".green());
+    } else {
+        task_manager.print_tasks(&today);
+    }
+    let now = Local::now().date_naive();
+    let high = today
+        .iter()
+        .filter(|t| t.priority() == Priority::High)
+        .count();
+    let overdue = today.iter().filter(|t| t.is_overdue(now)).count();
+    println!("{}", format!("{} high, {} overdue", high, overdue).dimmed());
+}
+
+/// Prints a random pending task (with the given priority), see TaskManager::random_task
+fn print_random_task(task_manager: &TaskManager, priority: Option<Priority>, weighted: bool) {
+    let filter = TaskFilter {
+        priority,
+        ..TaskFilter::default()
+    };
+    let task = if weighted {
+        task_manager.random_task_weighted(Some(filter))
+    } else {
+        task_manager.random_task(Some(filter))
+    };
+    match task {
+        Some(task) => task_manager.print_tasks(&[task]),
+        None => println!("{}", "No pending tasks to pick from.".yellow()),
+    }
+}
+
+/// the strategy for the --fuzzy threshold of dedup; exact duplicates without one
+fn dedup_strategy(fuzzy: Option<f32>) -> Result<DeduplicateStrategy, TaskError> {
+    match fuzzy {
+        None => Ok(DeduplicateStrategy::Exact),
+        Some(threshold) if (0.0..=1.0).contains(&threshold) => {
+            Ok(DeduplicateStrategy::Fuzzy(threshold))
+        }
+        Some(threshold) => Err(TaskError::ArgumentMismatch(format!(
+            "the similarity threshold must be between 0.0 and 1.0, not {}",
+            threshold
+        ))),
+    }
+}
+
+/// Deletes the duplicate tasks and prints each one with the task that was kept
+fn remove_duplicates(task_manager: &mut TaskManager, strategy: DeduplicateStrategy) -> usize {
+    let description = |id: usize| {
+        task_manager
+            .at(id)
+            .map(|t| t.get_description().to_string())
+            .unwrap_or_default()
+    };
+    let duplicates: Vec<(usize, String, usize, String)> = task_manager
+        .find_duplicates(strategy)
+        .into_iter()
+        .map(|(id, kept)| (id, description(id), kept, description(kept)))
+        .collect();
+    let removed = task_manager.deduplicate(strategy);
+    for (id, desc, kept, kept_desc) in &duplicates {
+        println!(
+            "Deleted #{} '{}' {}",
+            id,
+            desc,
+            format!("(duplicate of #{} '{}')", kept, kept_desc).dimmed()
+        );
+    }
+    println!("Deleted {} duplicate tasks", removed);
+    removed
+}
+
+/// Prints the pending tasks due in the next days under a header per day, overdue tasks first
+fn print_week(task_manager: &TaskManager, days: u32) {
+    let width = terminal_width();
+    let theme = &task_manager.get_config().theme;
+    let today = Local::now().date_naive();
+    let overdue = task_manager.overdue_tasks(today);
+    if !overdue.is_empty() {
+        println!("{}", day_header("Overdue", width).red().bold());
+        for task in overdue {
+            println!("{}", week_task_line(task, width, theme));
+        }
+    }
+    for (day, tasks) in task_manager.tasks_by_day(today, days) {
+        let label = if day == today {
+            format!("Today, {}", day.format("%a %Y-%m-%d"))
+        } else {
+            day.format("%a %Y-%m-%d").to_string()
+        };
+        println!("{}", day_header(&label, width).bold());
+        if tasks.is_empty() {
+            println!("{}", "  — nothing due —".dimmed());
+        }
+        for task in tasks {
+            println!("{}", week_task_line(task, width, theme));
+        }
+    }
+}
+
+/* "── label ─────" filling the whole line */
+fn day_header(label: &str, width: usize) -> String {
+    let header = format!("── {} ", label);
+    let fill = width.saturating_sub(header.chars().count());
+    format!("{}{}", header, "─".repeat(fill))
+}
+
+/* ID, priority and description, cut off so the line fits into the terminal */
+fn week_task_line(task: &Task, width: usize, theme: &Theme) -> String {
+    let prefix = format!("{:>5} ", format!("#{}", task.get_id()));
+    /* the priority symbol and the space after it take two columns */
+    let available = width.saturating_sub(prefix.chars().count() + 2).max(10);
+    let desc = task.get_description();
+    let desc = if desc.chars().count() > available {
+        let cut: String = desc.chars().take(available - 1).collect();
+        format!("{}…", cut)
+    } else {
+        desc.to_string()
+    };
+    format!(
+        "{}{} {}",
+        theme.index_color.paint(&prefix).bold(),
+        task.get_priority(theme),
+        desc
+    )
+}
+
+/// Prints the completions per day (or week) as horizontal bars, scaled to the terminal width
+fn print_report(task_manager: &TaskManager, days: u32, weeks: bool) {
+    let history = task_manager.completion_history(days);
+    let total: usize = history.values().sum();
+    let rows: BTreeMap<NaiveDate, usize> = if weeks {
+        let mut by_week = BTreeMap::new();
+        for (day, count) in &history {
+            let monday = *day - chrono::Duration::days(day.weekday().num_days_from_monday() as i64);
+            *by_week.entry(monday).or_insert(0) += count;
+        }
+        by_week
+    } else {
+        history
+    };
+
+    let max = rows.values().copied().max().unwrap_or(0);
+    let label = |day: &NaiveDate| {
+        if weeks {
+            format!("{} (week)", day.format("%b %d"))
+        } else {
+            day.format("%b %d").to_string()
+        }
+    };
+    /* label, " │", the bar and " <count>" have to fit into one line */
+    let label_width = rows
+        .keys()
+        .next()
+        .map(|d| label(d).chars().count())
+        .unwrap_or(0);
+    let count_width = max.to_string().len();
+    let bar_space = terminal_width()
+        .saturating_sub(label_width + count_width + 3)
+        .max(10);
+    for (day, count) in &rows {
+        let bar_len = (count * bar_space).checked_div(max).unwrap_or(0);
+        println!("{} │{} {}", label(day), "█".repeat(bar_len).green(), count);
+    }
+    let average = if days == 0 {
+        0.0
+    } else {
+        total as f64 / days as f64
+    };
+    println!(
+        "{}",
+        format!(
+            "{} tasks completed in {} days, {:.1} per day",
+            total, days, average
+        )
+        .dimmed()
+    );
+}
+
+/// width of the terminal from $COLUMNS, 80 if it isn't set
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse::<usize>().ok())
+        .filter(|&c| c > 0)
+        .unwrap_or(80)
+}
+
+/// Shows the task list and reloads it whenever the modification time of the task file changes.
+/// Runs until the user enters 'q' (or presses Ctrl+C).
+fn watch_tasks(task_manager: &mut TaskManager, interval: u64) -> Result<(), TaskError> {
+    /* stdin is read in its own thread, so waiting for input doesn't block the refreshes */
+    let (quit_tx, quit_rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lines() {
+            match line {
+                Ok(l) if l.trim().eq_ignore_ascii_case("q") => {
+                    let _ = quit_tx.send(());
+                    break;
+                }
+                Ok(_) => continue,
+                Err(_) => break,
+            }
+        }
+    });
+
+    let mut last_modified: Option<SystemTime> = None;
+    let mut first_run = true;
+    loop {
+        let modified = fs::metadata(task_manager.get_file_path())
+            .and_then(|m| m.modified())
+            .ok();
+        if first_run || modified != last_modified {
+            task_manager.load_tasks()?;
+            // clear screen and move the cursor to the top left corner
+            print!("\x1B[2J\x1B[H");
+            task_manager.list_tasks();
+            println!(
+                "\n{} {}",
+                format!("Last updated: {}", Local::now().format("%H:%M:%S")).dimmed(),
+                "(enter 'q' to quit)".dimmed()
+            );
+            last_modified = modified;
+            first_run = false;
+        }
+        /* waiting for the quit signal doubles as the sleep between two checks */
+        let pause = Duration::from_secs(interval.max(1));
+        match quit_rx.recv_timeout(pause) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => continue,
+            // stdin was closed, only Ctrl+C can stop watching now
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(pause),
+        }
+    }
+    Ok(())
+}
+
+/// Opens the URL with the default application of the platform (usually the browser)
+fn open_url(url: &str) -> Result<(), TaskError> {
+    /* the link may come from a shared or imported task file, not only from `link` */
+    check_url(url)?;
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        /* not `cmd /C start`: cmd would split the URL at & and run the rest as a command */
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = Command::new("xdg-open");
+
+    let status = command.arg(url).status()?;
+    if !status.success() {
+        return Err(TaskError::Unknown(format!("could not open {}", url)));
+    }
+    Ok(())
+}
+
+/* Config::username if set, otherwise $USER (or $USERNAME on Windows) */
+fn current_username(config: &Config) -> Option<String> {
+    config
+        .username
+        .clone()
+        .or_else(|| {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok()
+        })
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+}
+
+/// asks a yes/no question on the terminal; only "y" or "yes" counts as yes
+fn confirm(question: &str) -> Result<bool, TaskError> {
+    print!("{}", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// pending tasks not changed for this many days are stale, if no other number is given
+const DEFAULT_STALE_DAYS: u64 = 30;
+/// the similarity above which `dedup --fuzzy` treats descriptions as duplicates
+const DEFAULT_FUZZY_THRESHOLD: f32 = 0.8;
+
+/* GitHub returns at most 100 issues per page */
+const GITHUB_PAGE_SIZE: usize = 100;
+
+/// Fetches all issues (open and closed) of the repository `<owner>/<repo>`, page by page.
+/// Note that the issues endpoint also returns pull requests.
+fn fetch_github_issues(
+    repo: &str,
+    token: Option<&str>,
+    label: Option<&str>,
+    assignee: Option<&str>,
+) -> Result<Vec<GithubIssue>, TaskError> {
+    /* reqwest's message alone doesn't say why a request failed, the cause does */
+    let network_error = |e: reqwest::Error| match std::error::Error::source(&e) {
+        Some(cause) => TaskError::Network(format!("{} ({})", e, cause)),
+        None => TaskError::Network(e.to_string()),
+    };
+    match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {}
+        _ => {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "'{}' is not a repository, expected <owner>/<repo>",
+                repo
+            )));
+        }
+    }
+    let url = format!("https://api.github.com/repos/{}/issues", repo);
+    let client = reqwest::blocking::Client::new();
+    let mut issues = Vec::new();
+    for page in 1.. {
+        let mut query = vec![
+            ("state", "all".to_string()),
+            ("per_page", GITHUB_PAGE_SIZE.to_string()),
+            ("page", page.to_string()),
+        ];
+        if let Some(label) = label {
+            query.push(("labels", label.to_string()));
+        }
+        if let Some(assignee) = assignee {
+            query.push(("assignee", assignee.to_string()));
+        }
+        let mut request = client
+            .get(&url)
+            .query(&query)
+            .header("User-Agent", "taskmaster")
+            .header("Accept", "application/vnd.github+json");
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().map_err(network_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(TaskError::Network(format!(
+                "GitHub answered {} for {}",
+                status, repo
+            )));
+        }
+        let page_issues: Vec<GithubIssue> = response.json().map_err(network_error)?;
+        let last_page = page_issues.len() < GITHUB_PAGE_SIZE;
+        issues.extend(page_issues);
+        if last_page {
+            break;
+        }
+    }
+    Ok(issues)
+}
+
+/// Loads ~/.taskmasterrc; a leading ~/ in Config::backup_dir is expanded to the home directory
+fn load_config() -> Result<Config, TaskError> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| TaskError::Unknown("Could not determine home directory".to_string()))?;
+    let mut config = Config::load(&home.join(".taskmasterrc"))?;
+    if let Some(dir) = &config.backup_dir
+        && let Some(rest) = dir.strip_prefix("~/")
+    {
+        config.backup_dir = Some(home.join(rest).to_string_lossy().to_string());
+    }
+    Ok(config)
+}
+
+fn get_audit_log_path() -> Result<PathBuf, TaskError> {
     let mut path = dirs::home_dir()
         .ok_or_else(|| TaskError::Unknown("Could not determine home directory".to_string()))?;
-    path.push(".tasks.json");
+    path.push(".taskmaster_audit.log");
     Ok(path)
 }
 
-fn build_description(description: &Vec<String>) -> Result<String, TaskError> {
-    let desc_str = description.join(" ").trim().to_string();
-    if !desc_str.is_empty() {
-        Ok(desc_str)
+/// Prints the tasks matching the filter in list order, one line per task formatted with the
+/// template; \t and \n in the template stand for tab and line break
+fn print_with_template(
+    task_manager: &TaskManager,
+    filter: &TaskFilter,
+    limit: usize,
+    template: &str,
+) {
+    let template = template.replace("\\t", "\t").replace("\\n", "\n");
+    for name in unknown_placeholders(&template) {
+        eprintln!(
+            "{}",
+            format!(
+                "Warning: unknown placeholder {{{}}} is printed as it is",
+                name
+            )
+            .yellow()
+        );
+    }
+    let tasks = task_manager.tasks_matching_filter(filter);
+    let shown = if limit == 0 { usize::MAX } else { limit };
+    for task in tasks.take(shown) {
+        println!("{}", task.format_with_template(&template));
+    }
+}
+
+/// Prints all fields of a task, its notes and its time entries
+fn print_task_details(task: &Task, theme: &Theme) {
+    println!(
+        "{} {}",
+        format!("#{}", task.get_id()).cyan().bold(),
+        task.get_description().bold()
+    );
+    let status = if task.get_completed() {
+        "done"
     } else {
-        Err(TaskError::Empty("Description".to_string()))
+        "pending"
+    };
+    println!("  {:<10} {}", "Status:", status);
+    println!(
+        "  {:<10} {} {}",
+        "Priority:",
+        task.get_priority(theme),
+        task.priority().name()
+    );
+    if let (Some(due), Some(relative)) = (task.get_due(), task.format_due_date_relative()) {
+        println!(
+            "  {:<10} {} ({})",
+            "Due:",
+            due,
+            relative.color(task.due_date_color())
+        );
+    }
+    if let Some(minutes) = task.get_estimate_minutes() {
+        println!("  {:<10} {}", "Estimate:", format_minutes(minutes as u64));
+    }
+    if let Some(assignee) = task.get_assignee() {
+        println!("  {:<10} {}", "Assignee:", assignee);
+    }
+    if let Some(url) = task.get_url() {
+        println!("  {:<10} {}", "Link:", url);
+    }
+    println!(
+        "  {:<10} {}",
+        "Reading:",
+        task.estimated_reading_time_display()
+    );
+    if let Some(notes) = task.get_notes() {
+        println!("  {}", "Notes:".bold());
+        for line in notes.lines() {
+            println!("    {}", line);
+        }
+    }
+    let entries = task.get_time_entries();
+    if !entries.is_empty() {
+        println!(
+            "  {} {}",
+            "Time log:".bold(),
+            format!(
+                "({} total)",
+                format_minutes(task.total_time_spent().as_secs() / 60)
+            )
+            .dimmed()
+        );
+        for entry in entries {
+            let start = entry.start.with_timezone(&Local);
+            let (end, length) = match (entry.end, entry.duration()) {
+                (Some(end), Some(length)) => (
+                    end.with_timezone(&Local).format("%H:%M").to_string(),
+                    format!("{:>7}", format_minutes(length.as_secs() / 60)),
+                ),
+                _ => (
+                    format!("{:<5}", "…"),
+                    format!("{:>7}", "running").yellow().to_string(),
+                ),
+            };
+            println!(
+                "    {} {}–{}  {}  {}",
+                start.format("%Y-%m-%d"),
+                start.format("%H:%M"),
+                end,
+                length,
+                entry.note.as_deref().unwrap_or("").dimmed()
+            );
+        }
+    }
+}
+
+/// Prints the notes of the task in a box, with the task as title
+fn print_notes_box(task: &Task) {
+    let title = format!("#{} {}", task.get_id(), task.get_description());
+    let lines: Vec<&str> = match task.get_notes() {
+        Some(notes) => notes.lines().collect(),
+        None => vec!["(no notes)"],
+    };
+    let width = lines
+        .iter()
+        .map(|l| l.chars().count())
+        .chain(std::iter::once(title.chars().count()))
+        .max()
+        .unwrap_or(0);
+    println!("┌─{}─┐", "─".repeat(width));
+    println!("│ {:<width$} │", title.bold(), width = width);
+    println!("├─{}─┤", "─".repeat(width));
+    for line in lines {
+        println!("│ {:<width$} │", line, width = width);
+    }
+    println!("└─{}─┘", "─".repeat(width));
+}
+
+/// Prints the warnings of TaskLinter with what to do about them
+fn print_lint_warnings(warnings: &[LintWarning]) {
+    if warnings.is_empty() {
+        println!("{}", "No anti-patterns found.".green());
+        return;
+    }
+    for warning in warnings {
+        println!("{} {}", "•".bold(), warning.to_string().yellow());
+        println!("  {}", warning.suggestion().dimmed());
+    }
+    let fixable = warnings.iter().filter(|w| w.is_fixable()).count();
+    if fixable > 0 {
+        println!("{} can be fixed with: taskmaster lint --fix", fixable);
+    }
+}
+
+/// Prints the problems found by TaskManager::health_check, each with a suggested fix
+fn print_health_issues(issues: &[HealthIssue]) {
+    if issues.is_empty() {
+        println!("{}", "No problems found.".green());
+    }
+    for issue in issues {
+        let color = match issue {
+            HealthIssue::FileMissing => issue.to_string().yellow(),
+            _ => issue.to_string().red(),
+        };
+        println!("{} {}", "•".bold(), color);
+        println!("  {}", issue.suggestion().dimmed());
+    }
+}
+
+/// Runs the `template` subcommands that don't add a task: save, list and delete
+fn run_template_command(
+    action: &TemplateCommand,
+    task_manager: &TaskManager,
+) -> Result<(), TaskError> {
+    let path = workspace::templates_path()?;
+    let mut templates = TaskTemplate::load_all(&path)?;
+    match action {
+        TemplateCommand::Save {
+            name,
+            description,
+            from_task,
+            priority,
+            tags,
+            context,
+        } => {
+            let mut template = match from_task {
+                Some(id) => {
+                    let task = task_manager.at(*id).ok_or(TaskError::TaskNotFound(*id))?;
+                    TaskTemplate::from_task(name, task)?
+                }
+                None => TaskTemplate::new(name, &description.join(" "))?,
+            };
+            if priority.is_some() {
+                template.default_priority = *priority;
+            }
+            template.default_tags = tags.clone();
+            template.default_context = context.clone();
+            templates.retain(|t| t.name != *name);
+            templates.push(template);
+            TaskTemplate::save_all(&path, &templates)?;
+            println!("Saved template {}", name.cyan().bold());
+        }
+        TemplateCommand::List => {
+            if templates.is_empty() {
+                println!(
+                    "No templates, save one with: taskmaster template save <NAME> <DESCRIPTION>"
+                );
+            }
+            for template in &templates {
+                let mut defaults = String::new();
+                if let Some(context) = &template.default_context {
+                    defaults.push_str(&format!(" @{}", context));
+                }
+                for tag in &template.default_tags {
+                    defaults.push_str(&format!(" +{}", tag));
+                }
+                if let Some(priority) = template.default_priority {
+                    defaults.push_str(&format!(" [{}]", priority.name()));
+                }
+                println!(
+                    "{:<15} {}{}",
+                    template.name.cyan().bold(),
+                    template.description_template,
+                    defaults.dimmed()
+                );
+            }
+        }
+        TemplateCommand::Delete { name } => {
+            find_template(name)?;
+            templates.retain(|t| t.name != *name);
+            TaskTemplate::save_all(&path, &templates)?;
+            println!("Deleted template {}", name);
+        }
+        TemplateCommand::Use { .. } => unreachable!("template use adds a task in run_app"),
+    }
+    Ok(())
+}
+
+/// the saved template with this name
+fn find_template(name: &str) -> Result<TaskTemplate, TaskError> {
+    TaskTemplate::load_all(&workspace::templates_path()?)?
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| {
+            TaskError::ArgumentMismatch(format!(
+                "template '{}' doesn't exist, see: taskmaster template list",
+                name
+            ))
+        })
+}
+
+/* the description of a task from the template, for today and the user of the system */
+fn template_description(template: &TaskTemplate, description_override: Option<&str>) -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    template.render_description(description_override, Local::now().date_naive(), &user)
+}
+
+/// the task file of a workspace; an error if the workspace doesn't exist
+fn existing_workspace_path(name: &str, config: &Config) -> Result<PathBuf, TaskError> {
+    if !workspace::workspace_exists(name, config)? {
+        return Err(TaskError::ArgumentMismatch(format!(
+            "workspace '{}' doesn't exist, see: taskmaster workspace list",
+            name
+        )));
+    }
+    workspace::workspace_path(name, config)
+}
+
+/// Runs a `workspace` subcommand; with dry_run, only tells what it would do
+fn run_workspace_command(
+    action: &WorkspaceCommand,
+    config: &Config,
+    dry_run: bool,
+) -> Result<(), TaskError> {
+    let active = workspace::active_workspace(config)?;
+    let not_found = |name: &str| {
+        TaskError::ArgumentMismatch(format!(
+            "workspace '{}' doesn't exist, see: taskmaster workspace list",
+            name
+        ))
+    };
+    match action {
+        WorkspaceCommand::List => {
+            for name in workspace::list_workspaces()? {
+                if name == active {
+                    println!("* {}", name.cyan().bold());
+                } else {
+                    println!("  {}", name);
+                }
+            }
+        }
+        WorkspaceCommand::New { name } => {
+            if workspace::workspace_exists(name, config)? {
+                return Err(TaskError::ArgumentMismatch(format!(
+                    "workspace '{}' already exists",
+                    name
+                )));
+            }
+            let path = workspace::workspace_path(name, config)?;
+            if dry_run {
+                println!("Would create workspace {} ({})", name, path.display());
+                return Ok(());
+            }
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut task_manager = TaskManager::new(path);
+            task_manager.set_config(config.clone());
+            task_manager.save_tasks()?;
+            println!("Created workspace {}", name);
+            println!("Switch to it with: taskmaster workspace switch {}", name);
+        }
+        WorkspaceCommand::Switch { name } => {
+            if !workspace::workspace_exists(name, config)? {
+                return Err(not_found(name));
+            }
+            if dry_run {
+                println!("Would switch to workspace {}", name);
+                return Ok(());
+            }
+            workspace::set_active_workspace(name)?;
+            println!("Switched to workspace {}", name.cyan().bold());
+        }
+        WorkspaceCommand::Delete { name, yes } => {
+            if name == workspace::LEGACY_WORKSPACE {
+                return Err(TaskError::ArgumentMismatch(
+                    "the default workspace can't be deleted".to_string(),
+                ));
+            }
+            if *name == active {
+                return Err(TaskError::ArgumentMismatch(format!(
+                    "workspace '{}' is active, switch to another one first",
+                    name
+                )));
+            }
+            if !workspace::workspace_exists(name, config)? {
+                return Err(not_found(name));
+            }
+            let path = workspace::workspace_path(name, config)?;
+            if dry_run {
+                println!("Would delete workspace {} ({})", name, path.display());
+                return Ok(());
+            }
+            if !yes
+                && !confirm(&format!(
+                    "Delete workspace {} with all its tasks? [y/N] ",
+                    name
+                ))?
+            {
+                println!("Nothing deleted.");
+                return Ok(());
+            }
+            fs::remove_file(&path)?;
+            println!("Deleted workspace {}", name);
+        }
+    }
+    Ok(())
+}
+
+/// Joins the words of a description and checks it, including the length limits of the
+/// configuration; all problems with it are reported together
+/* --interactive: asks for the arguments that weren't given on the command line and puts them
+ * into the command. For add, the form also has a priority and due date, which are returned to
+ * be set after the task is added. */
+fn fill_in_interactively(
+    command: &mut Commands,
+    task_manager: &TaskManager,
+) -> Result<Option<AddForm>, TaskError> {
+    let theme = &task_manager.get_config().theme;
+    let pending: Vec<&Task> = task_manager
+        .tasks_matching(|t| !t.get_completed())
+        .collect();
+    match command {
+        Commands::Add {
+            description,
+            interactive: true,
+            ..
+        } => {
+            let given = description.join(" ").trim().to_string();
+            let form = FieldPrompt::new()?.add_form((!given.is_empty()).then_some(given))?;
+            *description = vec![form.description.clone()];
+            Ok(Some(form))
+        }
+        Commands::Change {
+            id,
+            description,
+            interactive: true,
+            ..
+        } => {
+            let mut prompt = FieldPrompt::new()?;
+            if id.is_none() {
+                let tasks: Vec<&Task> = task_manager.iter().collect();
+                *id = Some(prompt.ask_task_id("ID of the task to change", &tasks, theme)?);
+            }
+            if description.is_empty() {
+                *description = vec![prompt.ask_required("New description")?];
+            }
+            Ok(None)
+        }
+        Commands::Complete {
+            id: id @ None,
+            interactive: true,
+            ..
+        } => {
+            *id = Some(FieldPrompt::new()?.ask_task_id(
+                "ID of the task to complete",
+                &pending,
+                theme,
+            )?);
+            Ok(None)
+        }
+        Commands::Delete {
+            id: id @ None,
+            interactive: true,
+            ..
+        } => {
+            let tasks: Vec<&Task> = task_manager.iter().collect();
+            *id =
+                Some(FieldPrompt::new()?.ask_task_id("ID of the task to delete", &tasks, theme)?);
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
+/* --desc: puts the ID of the task the text describes into the command */
+fn resolve_desc(command: &mut Commands, task_manager: &TaskManager) -> Result<(), TaskError> {
+    if let Commands::Change {
+        id_arg,
+        id,
+        desc,
+        description,
+        ..
+    } = command
+    {
+        match desc {
+            Some(desc) => {
+                if let Some(word) = id_arg.take() {
+                    description.insert(0, word);
+                }
+                *id = Some(task_id_by_description(task_manager, desc)?);
+            }
+            None => {
+                *id = match id_arg {
+                    Some(arg) => Some(arg.parse().map_err(|_| {
+                        TaskError::ArgumentMismatch(format!("'{}' is not a task ID", arg))
+                    })?),
+                    None => None,
+                }
+            }
+        }
+    } else if let Commands::Complete {
+        id,
+        desc: Some(desc),
+        ..
+    }
+    | Commands::Delete {
+        id,
+        desc: Some(desc),
+        ..
+    } = command
+    {
+        *id = Some(task_id_by_description(task_manager, desc)?);
+    }
+    Ok(())
+}
+
+/* An exact description (ignoring case) wins; otherwise the text has to be the start of the
+ * description of exactly one task */
+fn task_id_by_description(task_manager: &TaskManager, text: &str) -> Result<usize, TaskError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(TaskError::Empty("Description".to_string()));
+    }
+    if let Some(task) = task_manager.get_by_description_exact(text) {
+        return Ok(task.get_id());
+    }
+    match task_manager.get_by_description_prefix(text).as_slice() {
+        [task] => Ok(task.get_id()),
+        [] => Err(TaskError::ArgumentMismatch(format!(
+            "no task starts with '{}'",
+            text
+        ))),
+        matches => Err(TaskError::Ambiguous(
+            matches.iter().map(|t| t.get_id()).collect(),
+        )),
+    }
+}
+
+/* the ID of a command whose ID is optional because of --interactive or --desc */
+fn given_id(id: Option<usize>) -> Result<usize, TaskError> {
+    id.ok_or_else(|| TaskError::Empty("ID".to_string()))
+}
+
+fn build_description(
+    description: &[String],
+    task_manager: &TaskManager,
+) -> Result<String, TaskError> {
+    let desc_str = description.join(" ").trim().to_string();
+    if desc_str.is_empty() {
+        return Err(TaskError::Empty("Description".to_string()));
+    }
+    let mut violations = Vec::new();
+    /* line breaks, tabs etc. would break the layout of the task list; auto_sanitize turns them
+     * into spaces */
+    if !task_manager.get_config().auto_sanitize && desc_str.chars().any(char::is_control) {
+        violations.push("Description contains control characters (like line breaks)".to_string());
+    }
+    match task_manager.check_description(desc_str) {
+        Ok(desc_str) if violations.is_empty() => Ok(desc_str),
+        Ok(_) => Err(TaskError::ValidationErrors(violations)),
+        Err(length_violations) => {
+            violations.extend(length_violations);
+            Err(TaskError::ValidationErrors(violations))
+        }
     }
 }
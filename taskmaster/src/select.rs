@@ -0,0 +1,123 @@
+use colored::Colorize;
+use crossterm::cursor::{Hide, MoveToColumn, MoveUp, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{execute, queue};
+use std::collections::HashSet;
+use std::io::{self, Write};
+use tasks::{Task, TaskError, Theme};
+
+/* switches the terminal back to normal mode when the selection ends, also on errors */
+struct RawModeGuard;
+
+impl RawModeGuard {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), Hide)?;
+        Ok(RawModeGuard)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Shows the tasks with a cursor (►) and a checkbox each: ↑/↓ (or k/j) move the cursor, space
+/// toggles the task under it, 'a' toggles all and Enter confirms. Esc, q and Ctrl-C cancel,
+/// which returns None. Tasks in `preselected` start out checked.
+pub fn select_tasks(
+    tasks: &[&Task],
+    preselected: &HashSet<usize>,
+    theme: &Theme,
+) -> Result<Option<HashSet<usize>>, TaskError> {
+    let mut selected: HashSet<usize> = tasks
+        .iter()
+        .map(|t| t.get_id())
+        .filter(|id| preselected.contains(id))
+        .collect();
+    let mut cursor = 0;
+    let mut stdout = io::stdout();
+    println!(
+        "{}",
+        "↑/↓ move, space selects, a selects all, Enter confirms, Esc cancels".dimmed()
+    );
+
+    let _guard = RawModeGuard::enable()?;
+    draw(&mut stdout, tasks, &selected, cursor, theme, false)?;
+    let confirmed = loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => cursor = cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                cursor = (cursor + 1).min(tasks.len().saturating_sub(1))
+            }
+            KeyCode::Char(' ') => {
+                if let Some(task) = tasks.get(cursor)
+                    && !selected.remove(&task.get_id())
+                {
+                    selected.insert(task.get_id());
+                }
+            }
+            KeyCode::Char('a') => {
+                if selected.len() == tasks.len() {
+                    selected.clear();
+                } else {
+                    selected = tasks.iter().map(|t| t.get_id()).collect();
+                }
+            }
+            KeyCode::Enter => break true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break false,
+            KeyCode::Esc | KeyCode::Char('q') => break false,
+            _ => continue,
+        }
+        draw(&mut stdout, tasks, &selected, cursor, theme, true)?;
+    };
+    Ok(confirmed.then_some(selected))
+}
+
+/* prints the list; with redraw, the previously printed list is overwritten.
+ * Raw mode doesn't translate \n, so every line ends with \r\n */
+fn draw(
+    stdout: &mut io::Stdout,
+    tasks: &[&Task],
+    selected: &HashSet<usize>,
+    cursor: usize,
+    theme: &Theme,
+    redraw: bool,
+) -> io::Result<()> {
+    if redraw && !tasks.is_empty() {
+        queue!(stdout, MoveUp(tasks.len() as u16))?;
+    }
+    for (index, task) in tasks.iter().enumerate() {
+        let pointer = if index == cursor { "►" } else { " " };
+        let checkbox = if selected.contains(&task.get_id()) {
+            "☑"
+        } else {
+            "☐"
+        };
+        let line = format!(
+            "{} {} {:>3}: {} {}",
+            pointer,
+            checkbox,
+            task.get_id(),
+            task.get_priority(theme),
+            task.get_description()
+        );
+        let line = if index == cursor {
+            line.bold().to_string()
+        } else {
+            line
+        };
+        queue!(stdout, MoveToColumn(0), Clear(ClearType::CurrentLine))?;
+        write!(stdout, "{}\r\n", line)?;
+    }
+    stdout.flush()
+}
@@ -0,0 +1,326 @@
+use colored::Colorize;
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::{Hint, Hinter, HistoryHinter};
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::borrow::Cow;
+use tasks::Task;
+
+/// all command names known to interactive mode, used for completion and highlighting
+pub const COMMANDS: &[&str] = &[
+    "l", "list", "a", "add", "c", "complete", "+", "up", "-", "down", "prio", "priority", "d",
+    "delete", "ch", "change", "f", "find", "today", "random", "stale", "hist", "history", "swap",
+    "sel", "select", "pin", "unpin", "*", "mark", "unmark", "link", "unlink", "snooze", "unsnooze",
+    "open", "e", "edit", "replace", "alias", "unalias", "clr", "clear", "dedup", "undo", "r",
+    "redo", "script", "h", "help", "?", "q", "quit", "x", "exit",
+];
+
+/// commands which expect a task ID as their first argument
+const ID_COMMANDS: &[&str] = &[
+    "c", "complete", "+", "up", "-", "down", "prio", "priority", "d", "delete", "ch", "change",
+    "swap", "pin", "unpin", "*", "mark", "unmark", "link", "unlink", "snooze", "unsnooze", "open",
+    "e", "edit",
+];
+
+/// maximum number of characters of a description that is shown next to a completed ID
+const DESCRIPTION_PREVIEW_LEN: usize = 30;
+
+/// maximum number of characters of a description in the preview below the prompt
+const DESCRIPTION_HINT_LEN: usize = 50;
+
+/* The helper can't borrow the TaskManager (interactive mode holds the mutable borrow), so it keeps
+ * its own copy of the task list, which is refreshed before every prompt */
+pub struct TaskHelper {
+    tasks: Vec<KnownTask>,
+    hinter: HistoryHinter,
+    highlighter: TaskHighlighter,
+}
+
+impl TaskHelper {
+    pub fn new() -> Self {
+        TaskHelper {
+            tasks: Vec::new(),
+            hinter: HistoryHinter::new(),
+            highlighter: TaskHighlighter::default(),
+        }
+    }
+
+    /// replaces the known task IDs and descriptions
+    pub fn update_tasks<'t>(&mut self, tasks: impl Iterator<Item = &'t Task>) {
+        self.tasks = tasks
+            .map(|t| KnownTask {
+                id: t.get_id(),
+                description: t.get_description().to_string(),
+                markers: format!("[{}] {}", t.priority(), t.status()),
+            })
+            .collect();
+    }
+
+    /// replaces the user-defined command aliases
+    pub fn update_aliases<'s>(&mut self, aliases: impl Iterator<Item = &'s String>) {
+        self.highlighter.aliases = aliases.cloned().collect();
+    }
+
+    fn complete_id(&self, word: &str) -> Vec<Pair> {
+        self.tasks
+            .iter()
+            .filter(|task| task.id.to_string().starts_with(word))
+            .map(|task| Pair {
+                display: format!(
+                    "{}:{}",
+                    task.id,
+                    shorten(&task.description, DESCRIPTION_PREVIEW_LEN)
+                ),
+                replacement: task.id.to_string(),
+            })
+            .collect()
+    }
+
+    /* "Preview: #5 [▲] [·] Fix the login bug" while the ID argument of an ID command is typed */
+    fn preview_id(&self, line: &str, pos: usize) -> Option<String> {
+        if pos < line.len() || line.ends_with(char::is_whitespace) {
+            return None;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let command = words.first()?.to_lowercase();
+        let is_id_argument = match words.len() {
+            2 => ID_COMMANDS.contains(&command.as_str()),
+            /* swap takes two IDs */
+            3 => command == "swap",
+            _ => false,
+        };
+        if !is_id_argument {
+            return None;
+        }
+        let id = words.last()?.parse::<usize>().ok()?;
+        let preview = match self.tasks.iter().find(|task| task.id == id) {
+            Some(task) => format!(
+                "#{} {} {}",
+                id,
+                task.markers,
+                shorten(&task.description, DESCRIPTION_HINT_LEN)
+            ),
+            None => format!("(no task with ID {})", id),
+        };
+        Some(format!("\nPreview: {}", preview))
+    }
+}
+
+/* what the helper needs to know about a task */
+struct KnownTask {
+    id: usize,
+    description: String,
+    /* priority and status, e.g. "[▲] [·]" */
+    markers: String,
+}
+
+/* the text cut down to max_chars characters, ending in "…" if it was longer */
+fn shorten(text: &str, max_chars: usize) -> String {
+    let mut short: String = text.chars().take(max_chars).collect();
+    if text.chars().count() > max_chars {
+        short.push('…');
+    }
+    short
+}
+
+/// A hint shown after the input: the rest of a matching history entry, which the right arrow
+/// key inserts, or the preview of the typed task ID below the prompt, which is only shown
+pub enum TaskHint {
+    History(String),
+    Preview(String),
+}
+
+impl Hint for TaskHint {
+    fn display(&self) -> &str {
+        match self {
+            TaskHint::History(hint) | TaskHint::Preview(hint) => hint,
+        }
+    }
+
+    fn completion(&self) -> Option<&str> {
+        match self {
+            TaskHint::History(hint) => Some(hint),
+            TaskHint::Preview(_) => None,
+        }
+    }
+}
+
+impl Completer for TaskHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        /* the word that is completed starts after the last whitespace before the cursor */
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        let word = &before[start..];
+        let preceding: Vec<&str> = before[..start].split_whitespace().collect();
+
+        let candidates = match preceding.as_slice() {
+            [] => COMMANDS
+                .iter()
+                .copied()
+                .chain(self.highlighter.aliases.iter().map(String::as_str))
+                .filter(|c| c.starts_with(word))
+                .map(|c| Pair {
+                    display: c.to_string(),
+                    replacement: format!("{} ", c),
+                })
+                .collect(),
+            [command] if ID_COMMANDS.contains(&command.to_lowercase().as_str()) => {
+                self.complete_id(word)
+            }
+            _ => Vec::new(),
+        };
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for TaskHelper {
+    type Hint = TaskHint;
+
+    /* the ID preview wins over the history, whose hint would only be the rest of an old line */
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<TaskHint> {
+        match self.preview_id(line, pos) {
+            Some(preview) => Some(TaskHint::Preview(preview)),
+            None => self.hinter.hint(line, pos, ctx).map(TaskHint::History),
+        }
+    }
+}
+
+impl Highlighter for TaskHelper {
+    fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+        self.highlighter.highlight(line, pos)
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        self.highlighter.highlight_hint(hint)
+    }
+
+    fn highlight_char(&self, line: &str, pos: usize, kind: CmdKind) -> bool {
+        self.highlighter.highlight_char(line, pos, kind)
+    }
+}
+
+/// Colors the input line while typing: known commands and aliases cyan, unknown commands red and
+/// numeric arguments (task IDs) yellow, in every command of a `;`-separated line. Other
+/// arguments are left as they are.
+#[derive(Default)]
+pub struct TaskHighlighter {
+    aliases: Vec<String>,
+}
+
+impl Highlighter for TaskHighlighter {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if line.trim().is_empty() {
+            return Cow::Borrowed(line);
+        }
+        let mut highlighted = String::with_capacity(line.len() * 2);
+        /* every command of a ';'-separated line starts with a command name */
+        for (i, command) in split_commands(line).into_iter().enumerate() {
+            if i > 0 {
+                highlighted.push(';');
+            }
+            self.highlight_command(command, &mut highlighted);
+        }
+        Cow::Owned(highlighted)
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.dimmed().to_string())
+    }
+
+    /* every typed character can change the coloring of its token */
+    fn highlight_char(&self, _line: &str, _pos: usize, _kind: CmdKind) -> bool {
+        true
+    }
+}
+
+impl TaskHighlighter {
+    /* appends one command of the line, colored */
+    fn highlight_command(&self, command_line: &str, highlighted: &mut String) {
+        let mut is_first_token = true;
+        /* split_inclusive keeps the whitespace, so the highlighted line has the same layout */
+        for chunk in command_line.split_inclusive(char::is_whitespace) {
+            let token = chunk.trim_end();
+            let whitespace = &chunk[token.len()..];
+            if token.is_empty() {
+                highlighted.push_str(whitespace);
+                continue;
+            }
+            let colored_token = if is_first_token {
+                let command = token.to_lowercase();
+                /* "!cmd" runs a shell command */
+                if COMMANDS.contains(&command.as_str())
+                    || self.aliases.contains(&command)
+                    || command.starts_with('!')
+                {
+                    token.cyan().to_string()
+                } else {
+                    token.red().to_string()
+                }
+            } else if token.parse::<usize>().is_ok() {
+                token.yellow().to_string()
+            } else {
+                token.to_string()
+            };
+            is_first_token = false;
+            highlighted.push_str(&colored_token);
+            highlighted.push_str(whitespace);
+        }
+    }
+}
+
+/// Splits an input line at the `;` that aren't inside single or double quotes. The parts are
+/// left as they are, so joining them with `;` gives the line again; an unclosed quote runs to
+/// the end of the line.
+pub fn split_commands(line: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut start = 0;
+    let mut quote: Option<char> = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            (';', None) => {
+                commands.push(&line[start..i]);
+                start = i + 1;
+            }
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            _ => {}
+        }
+    }
+    commands.push(&line[start..]);
+    commands
+}
+
+impl Validator for TaskHelper {}
+
+impl Helper for TaskHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_command_of_a_line_is_highlighted() {
+        let highlighter = TaskHighlighter::default();
+        assert_eq!(
+            highlighter.highlight("add foo; lst 2", 0),
+            format!("{} foo; {} {}", "add".cyan(), "lst".red(), "2".yellow())
+        );
+        assert_eq!(
+            highlighter.highlight(r#"add "a;b";list"#, 0),
+            format!(r#"{} "a;b";{}"#, "add".cyan(), "list".cyan())
+        );
+    }
+}
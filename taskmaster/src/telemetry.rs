@@ -0,0 +1,27 @@
+use std::sync::Arc;
+use tasks::{ErrorReporter, TaskError, TaskManager};
+
+/// environment variable with the Sentry DSN; without it nothing is reported
+const SENTRY_DSN_ENV: &str = "SENTRY_DSN";
+
+/// Sends the errors of the TaskManager to Sentry
+#[derive(Debug)]
+struct SentryReporter;
+
+impl ErrorReporter for SentryReporter {
+    fn report_error(&self, err: &TaskError) {
+        sentry::capture_error(err);
+    }
+}
+
+/// Starts the Sentry client if SENTRY_DSN is set and makes the manager report its errors to
+/// it. Panics are reported as well. The returned guard sends the remaining events when it is
+/// dropped, so it has to live until the end of the program.
+pub fn init(task_manager: &mut TaskManager) -> Option<sentry::ClientInitGuard> {
+    let dsn = std::env::var(SENTRY_DSN_ENV).ok()?;
+    let mut options = sentry::ClientOptions::default();
+    options.release = sentry::release_name!();
+    let guard = sentry::init((dsn, options));
+    task_manager.set_error_reporter(Arc::new(SentryReporter));
+    Some(guard)
+}
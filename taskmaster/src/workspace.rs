@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tasks::{Config, StorageFormat, TaskError};
+
+/* the workspace that uses the task file of versions without workspaces, ~/.tasks.json */
+pub const LEGACY_WORKSPACE: &str = "default";
+
+/* name of the file (in the workspace directory) that holds the name of the active workspace */
+const ACTIVE_WORKSPACE_FILE: &str = ".active_workspace";
+
+/* the templates file lies next to the task files of the workspaces, so no workspace can have
+ * its name */
+const TEMPLATES_NAME: &str = "templates";
+
+fn home_dir() -> Result<PathBuf, TaskError> {
+    dirs::home_dir()
+        .ok_or_else(|| TaskError::Unknown("Could not determine home directory".to_string()))
+}
+
+/// directory of the workspace files: ~/.local/share/taskmaster on Linux
+pub fn workspace_dir() -> Result<PathBuf, TaskError> {
+    let data_dir = match dirs::data_dir() {
+        Some(dir) => dir,
+        None => home_dir()?.join(".local").join("share"),
+    };
+    Ok(data_dir.join("taskmaster"))
+}
+
+/// file of the saved task templates, shared by all workspaces
+pub fn templates_path() -> Result<PathBuf, TaskError> {
+    Ok(workspace_dir()?.join(format!("{}.json", TEMPLATES_NAME)))
+}
+
+/// Workspace names become file names, so only letters, digits, '-' and '_' are allowed.
+/// "templates" is taken by the file of the task templates.
+pub fn check_name(name: &str) -> Result<(), TaskError> {
+    if name.is_empty() {
+        return Err(TaskError::Empty("Workspace name".to_string()));
+    }
+    if name.eq_ignore_ascii_case(TEMPLATES_NAME) {
+        return Err(TaskError::ArgumentMismatch(format!(
+            "'{}' can't be a workspace, the task templates are stored under that name",
+            name
+        )));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(TaskError::ArgumentMismatch(format!(
+            "'{}' is not a valid workspace name, use only letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// the workspace commands work on: the one chosen with `workspace switch`, otherwise the
+/// default_workspace of the configuration
+pub fn active_workspace(config: &Config) -> Result<String, TaskError> {
+    let path = workspace_dir()?.join(ACTIVE_WORKSPACE_FILE);
+    if path.exists() {
+        let name = fs::read_to_string(&path)?.trim().to_string();
+        if !name.is_empty() {
+            return Ok(name);
+        }
+    }
+    Ok(config.default_workspace.clone())
+}
+
+pub fn set_active_workspace(name: &str) -> Result<(), TaskError> {
+    let dir = workspace_dir()?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(ACTIVE_WORKSPACE_FILE), format!("{}\n", name))?;
+    Ok(())
+}
+
+/// Task file of a workspace. The "default" workspace keeps using ~/.tasks.json (or .toml/.yaml),
+/// all others are stored as <name>.json (or .toml/.yaml) in the workspace directory.
+pub fn workspace_path(name: &str, config: &Config) -> Result<PathBuf, TaskError> {
+    check_name(name)?;
+    if name == LEGACY_WORKSPACE {
+        Ok(task_file_path(&home_dir()?, ".tasks", config))
+    } else {
+        Ok(task_file_path(&workspace_dir()?, name, config))
+    }
+}
+
+/// true if the workspace has a task file; the default workspace always exists
+pub fn workspace_exists(name: &str, config: &Config) -> Result<bool, TaskError> {
+    Ok(name == LEGACY_WORKSPACE || workspace_path(name, config)?.exists())
+}
+
+/// names of all workspaces, sorted; the default workspace is always included
+pub fn list_workspaces() -> Result<Vec<String>, TaskError> {
+    let mut names = vec![LEGACY_WORKSPACE.to_string()];
+    let dir = workspace_dir()?;
+    if dir.exists() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let is_task_file = path
+                .extension()
+                .is_some_and(|ext| ext == "json" || ext == "toml" || ext == "yaml");
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+                && is_task_file
+                && check_name(stem).is_ok()
+            {
+                names.push(stem.to_string());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/* <dir>/<stem>.json, .toml or .yaml: the format from the config, otherwise the first of them
+ * that exists (JSON if none exists) */
+fn task_file_path(dir: &Path, stem: &str, config: &Config) -> PathBuf {
+    let path_for = |format: StorageFormat| dir.join(format!("{}.{}", stem, format.extension()));
+    match config.format {
+        Some(format) => path_for(format),
+        None => [
+            StorageFormat::Json,
+            StorageFormat::Toml,
+            StorageFormat::Yaml,
+        ]
+        .into_iter()
+        .map(path_for)
+        .find(|path| path.exists())
+        .unwrap_or_else(|| path_for(StorageFormat::Json)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_is_not_a_workspace_name() {
+        assert!(check_name("work").is_ok());
+        assert!(check_name("templates").is_err());
+        assert!(check_name("Templates").is_err());
+        assert_eq!(
+            templates_path().unwrap().file_stem().unwrap(),
+            TEMPLATES_NAME
+        );
+        let config = Config::default();
+        assert!(workspace_path("templates", &config).is_err());
+        assert!(workspace_exists("templates", &config).is_err());
+    }
+}
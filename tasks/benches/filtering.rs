@@ -0,0 +1,81 @@
+//! Compares collecting the matching tasks into a Vec (how `find_by_description` and friends
+//! used to work) with the lazy `TaskManager::tasks_matching` on 10,000 tasks.
+//! Run with `cargo bench -p tasks`.
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use tasks::{Priority, Task, TaskManager};
+
+const TASK_COUNT: usize = 10_000;
+
+/* every third task is high priority, every fifth one completed */
+fn manager() -> TaskManager {
+    let tasks = (1..=TASK_COUNT)
+        .map(|id| {
+            let priority = if id % 3 == 0 {
+                Priority::High
+            } else {
+                Priority::Medium
+            };
+            let mut task = Task::new_task(format!("task number {} +bench", id), id, priority);
+            if id % 5 == 0 {
+                task.mark_completed();
+            }
+            task
+        })
+        .collect();
+    TaskManager::from_vec(tasks)
+}
+
+fn is_open_and_high(task: &Task) -> bool {
+    !task.get_completed() && task.priority() == Priority::High
+}
+
+fn count_matching(c: &mut Criterion) {
+    let manager = manager();
+    let mut group = c.benchmark_group("count matching tasks");
+    group.bench_function("collect into Vec", |b| {
+        b.iter(|| {
+            let matching: Vec<&Task> = manager.iter().filter(|t| is_open_and_high(t)).collect();
+            black_box(matching.len())
+        })
+    });
+    group.bench_function("tasks_matching", |b| {
+        b.iter(|| black_box(manager.tasks_matching(is_open_and_high).count()))
+    });
+    group.finish();
+}
+
+fn first_matching(c: &mut Criterion) {
+    let manager = manager();
+    let mut group = c.benchmark_group("first matching task");
+    group.bench_function("collect into Vec", |b| {
+        b.iter(|| {
+            let matching: Vec<&Task> = manager.iter().filter(|t| is_open_and_high(t)).collect();
+            black_box(matching.first().map(|t| t.get_id()))
+        })
+    });
+    group.bench_function("tasks_matching", |b| {
+        b.iter(|| {
+            black_box(
+                manager
+                    .tasks_matching(is_open_and_high)
+                    .next()
+                    .map(|t| t.get_id()),
+            )
+        })
+    });
+    group.finish();
+}
+
+fn clear_completed(c: &mut Criterion) {
+    c.bench_function("clear_completed_tasks", |b| {
+        b.iter_batched(
+            manager,
+            |mut manager| black_box(manager.clear_completed_tasks()),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, count_matching, first_matching, clear_completed);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+use super::task_error::TaskError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// The kind of change an audit entry records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditOperation {
+    Add,
+    Complete,
+    Delete,
+    ChangeDescription,
+    ChangePriority,
+}
+
+/// A single change to a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub operation: AuditOperation,
+    pub task_id: usize,
+    pub detail: String,
+}
+
+/// Append-only log of all changes, stored as one JSON object per line (NDJSON).
+/// Entries are collected in memory and only written by `flush`, which `TaskManager::save_tasks`
+/// calls - so the log only contains changes that were actually saved.
+#[derive(Debug)]
+pub struct TaskAuditLog {
+    path: PathBuf,
+    pending: Vec<AuditEntry>,
+    /* number of entries written by flush so far, so positions stay valid across flushes */
+    flushed: usize,
+}
+
+impl TaskAuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        TaskAuditLog {
+            path,
+            pending: Vec::new(),
+            flushed: 0,
+        }
+    }
+
+    /// remembers a change, to be written with the next flush
+    pub fn record<S: Into<String>>(
+        &mut self,
+        operation: AuditOperation,
+        task_id: usize,
+        detail: S,
+    ) {
+        self.pending.push(AuditEntry {
+            timestamp: Utc::now(),
+            operation,
+            task_id,
+            detail: detail.into(),
+        });
+    }
+
+    /* number of changes recorded so far (written or not), for discard_from */
+    pub(crate) fn position(&self) -> usize {
+        self.flushed + self.pending.len()
+    }
+
+    /* forgets the changes recorded after the given position, e.g. when they were rolled back.
+     * Changes that were written already stay in the log */
+    pub(crate) fn discard_from(&mut self, position: usize) {
+        self.pending.truncate(position.saturating_sub(self.flushed));
+    }
+
+    /// appends all recorded changes to the log file
+    pub fn flush(&mut self) -> Result<(), TaskError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        for entry in &self.pending {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        self.flushed += self.pending.len();
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// reads all entries from the log file; a missing file means an empty log
+    pub fn read_entries(&self) -> Result<Vec<AuditEntry>, TaskError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&self.path)?;
+        let mut entries = Vec::new();
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            entries.push(serde_json::from_str(line)?);
+        }
+        Ok(entries)
+    }
+}
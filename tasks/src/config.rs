@@ -0,0 +1,165 @@
+use super::interop;
+use super::task::Priority;
+use super::task_error::TaskError;
+use super::task_manager::GroupBy;
+use super::theme::Theme;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// File format of the task file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl StorageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StorageFormat::Json => "json",
+            StorageFormat::Toml => "toml",
+            StorageFormat::Yaml => "yaml",
+        }
+    }
+
+    /// guesses the format from the file extension; everything but .toml and .yaml/.yml is JSON
+    pub fn from_path(path: &Path) -> StorageFormat {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => StorageFormat::Toml,
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                StorageFormat::Yaml
+            }
+            _ => StorageFormat::Json,
+        }
+    }
+}
+
+impl FromStr for StorageFormat {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(StorageFormat::Json),
+            "toml" => Ok(StorageFormat::Toml),
+            "yaml" | "yml" => Ok(StorageFormat::Yaml),
+            _ => Err(TaskError::ArgumentMismatch(format!(
+                "unknown format '{}', expected json, toml or yaml",
+                s
+            ))),
+        }
+    }
+}
+
+/// How likely `random --weighted` picks a task of each priority, relative to each other.
+/// The default makes High 3 times as likely as Medium and 9 times as likely as Low.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriorityWeights {
+    pub high: u32,
+    pub medium: u32,
+    pub low: u32,
+}
+
+impl PriorityWeights {
+    pub fn weight(&self, priority: Priority) -> u32 {
+        match priority {
+            Priority::High => self.high,
+            Priority::Medium => self.medium,
+            Priority::Low => self.low,
+        }
+    }
+}
+
+impl Default for PriorityWeights {
+    fn default() -> Self {
+        PriorityWeights {
+            high: 9,
+            medium: 3,
+            low: 1,
+        }
+    }
+}
+
+/// User settings, read from a JSON file (~/.taskmasterrc for the CLI).
+/// Every field is optional in the file; missing fields get their default value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// create a backup of the task file before every save
+    pub auto_backup: bool,
+    /// where backups are stored; defaults to the directory of the task file
+    pub backup_dir: Option<String>,
+    /// how many backups are kept, older ones are deleted
+    pub backup_keep: usize,
+    /// exported iCalendar tasks get the UID `<id>@<ical_uid_base>`
+    pub ical_uid_base: String,
+    /// format of the task file; if not set, it's taken from the file extension
+    pub format: Option<StorageFormat>,
+    /// write a JSON task file pretty-printed (the default) or compact on a single line
+    pub json_pretty: bool,
+    /// shortcuts for interactive mode commands, e.g. "del" -> "delete" or "lp" -> "list
+    /// --group-by=priority"; also read as `interactive_aliases`
+    #[serde(alias = "interactive_aliases")]
+    pub aliases: HashMap<String, String>,
+    /// grouping of the task list if none is given on the command line
+    pub default_group_by: Option<GroupBy>,
+    /// longest allowed description in characters; None means unlimited
+    pub max_description_len: Option<usize>,
+    /// shortest allowed description in characters
+    pub min_description_len: Option<usize>,
+    /// cut descriptions down to max_description_len instead of rejecting them
+    pub truncate_description: bool,
+    /// clean up new and changed descriptions, see sanitize_description
+    pub auto_sanitize: bool,
+    /// workspace used until another one is chosen with `workspace switch`
+    pub default_workspace: String,
+    /// colors of the task list: a preset name or the single colors, see Theme
+    pub theme: Theme,
+    /// chances of the priorities for `random --weighted`
+    pub priority_weights: PriorityWeights,
+    /// your name for `list --mine` and `assign` without a name; $USER is used if it's not set
+    pub username: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            auto_backup: false,
+            backup_dir: None,
+            backup_keep: 10,
+            ical_uid_base: interop::ical::DEFAULT_UID_BASE.to_string(),
+            format: None,
+            aliases: HashMap::new(),
+            default_group_by: None,
+            max_description_len: None,
+            min_description_len: Some(1),
+            truncate_description: false,
+            auto_sanitize: true,
+            json_pretty: true,
+            default_workspace: "default".to_string(),
+            theme: Theme::dark(),
+            priority_weights: PriorityWeights::default(),
+            username: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the configuration from the given file. A missing or empty file gives the default
+    /// configuration.
+    pub fn load(path: &Path) -> Result<Config, TaskError> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        if contents.trim().is_empty() {
+            return Ok(Config::default());
+        }
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
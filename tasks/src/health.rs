@@ -0,0 +1,87 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// below this much free space on the disk of the task file, saving might fail
+pub const LOW_DISK_SPACE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// A problem with the task file found by `TaskManager::health_check`
+#[derive(Debug)]
+pub enum HealthIssue {
+    /// there is no task file yet
+    FileMissing,
+    /// the file can't be read: no permission, a directory, not UTF-8, ...
+    FileUnreadable(io::Error),
+    /// the file can be read, but doesn't contain a task list in the expected format
+    /// (JSON, TOML or YAML)
+    FileNotJson,
+    /// the disk the task file is on is almost full
+    DiskSpaceLow { free_bytes: u64 },
+    /// the file belongs to another user, so saving might not be allowed
+    FileOwnedByOtherUser,
+}
+
+impl HealthIssue {
+    /// what the user can do about the issue
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            HealthIssue::FileMissing => {
+                "Nothing to do for a new setup, the file is created when the first task is saved."
+            }
+            HealthIssue::FileUnreadable(_) => {
+                "Check that the path is a regular file you're allowed to read, or restore a backup."
+            }
+            HealthIssue::FileNotJson => {
+                "The file is damaged; restore a backup (taskmaster backup --list) or fix it by hand."
+            }
+            HealthIssue::DiskSpaceLow { .. } => {
+                "Free some disk space, otherwise saving the tasks might fail."
+            }
+            HealthIssue::FileOwnedByOtherUser => {
+                "Change the owner of the file (chown) to your user."
+            }
+        }
+    }
+}
+
+impl fmt::Display for HealthIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthIssue::FileMissing => write!(f, "The task file doesn't exist"),
+            HealthIssue::FileUnreadable(e) => write!(f, "The task file can't be read: {}", e),
+            HealthIssue::FileNotJson => write!(f, "The task file doesn't contain valid tasks"),
+            HealthIssue::DiskSpaceLow { free_bytes } => write!(
+                f,
+                "Only {:.1} MiB of disk space left",
+                *free_bytes as f64 / (1024.0 * 1024.0)
+            ),
+            HealthIssue::FileOwnedByOtherUser => {
+                write!(f, "The task file belongs to another user")
+            }
+        }
+    }
+}
+
+/* free bytes for unprivileged users on the file system of the path; None where unknown */
+#[cfg(unix)]
+pub(crate) fn free_disk_space(path: &Path) -> Option<u64> {
+    let stat = rustix::fs::statvfs(path).ok()?;
+    Some(stat.f_bavail.saturating_mul(stat.f_frsize))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn free_disk_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/* true if the file belongs to another user than the one running taskmaster */
+#[cfg(unix)]
+pub(crate) fn owned_by_other_user(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.uid() != rustix::process::getuid().as_raw()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn owned_by_other_user(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
@@ -0,0 +1,5 @@
+/* conversion of tasks from and to the formats of other applications */
+pub mod github;
+pub mod ical;
+pub mod orgmode;
+pub mod plaintext;
@@ -0,0 +1,85 @@
+use crate::task::Priority;
+use crate::task_error::TaskError;
+use crate::task_manager::TaskManager;
+use serde::Deserialize;
+
+/// An issue as returned by the GitHub REST API (`GET /repos/{owner}/{repo}/issues`).
+/// Only the fields taskmaster uses are read.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubIssue {
+    pub number: u64,
+    pub title: String,
+    pub html_url: String,
+    /// "open" or "closed"
+    pub state: String,
+    #[serde(default)]
+    pub labels: Vec<GithubLabel>,
+    #[serde(default)]
+    pub assignees: Vec<GithubUser>,
+    /// only present if the "issue" is really a pull request
+    #[serde(default)]
+    pub pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubLabel {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubUser {
+    pub login: String,
+}
+
+impl GithubIssue {
+    pub fn is_pull_request(&self) -> bool {
+        self.pull_request.is_some()
+    }
+
+    /// priority from the labels P0 (High), P1 (Medium) and P2 (Low); the highest one wins
+    pub fn priority(&self) -> Option<Priority> {
+        self.labels
+            .iter()
+            .filter_map(|l| match l.name.trim().to_uppercase().as_str() {
+                "P0" => Some(Priority::High),
+                "P1" => Some(Priority::Medium),
+                "P2" => Some(Priority::Low),
+                _ => None,
+            })
+            .max()
+    }
+}
+
+/// Adds the issues as tasks: the title becomes the description, the issue URL the link of the
+/// task, "GitHub #<number>" its note and the first assignee its assignee. Closed issues are
+/// added as completed. Pull requests and issues whose URL is already linked to a task are
+/// skipped, so importing the same repository again only adds the new issues.
+/// Returns the IDs of the added tasks.
+pub fn import_issues(
+    manager: &mut TaskManager,
+    issues: &[GithubIssue],
+) -> Result<Vec<usize>, TaskError> {
+    let mut added = Vec::new();
+    for issue in issues.iter().filter(|i| !i.is_pull_request()) {
+        if manager
+            .iter()
+            .any(|t| t.get_url() == Some(issue.html_url.as_str()))
+        {
+            continue;
+        }
+        let id = manager.add_task(issue.title.trim())?;
+        manager.link_task(id, issue.html_url.as_str())?;
+        manager.set_notes(id, Some(format!("GitHub #{}", issue.number)))?;
+        if let Some(priority) = issue.priority() {
+            manager.set_priority(id, priority)?;
+        }
+        if let Some(assignee) = issue.assignees.first() {
+            manager.assign_task(id, assignee.login.as_str())?;
+        }
+        if issue.state.eq_ignore_ascii_case("closed") {
+            manager.complete_task(id)?;
+        }
+        added.push(id);
+    }
+    Ok(added)
+}
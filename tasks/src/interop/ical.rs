@@ -0,0 +1,246 @@
+use crate::task::{Priority, Task};
+use crate::task_error::TaskError;
+use chrono::{NaiveDate, Utc};
+
+/// used in the UID of exported tasks if no other base is given
+pub const DEFAULT_UID_BASE: &str = "taskmaster.local";
+
+/* RFC 5545 wants content lines of at most 75 octets, longer lines are folded */
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Exports the tasks as an iCalendar (RFC 5545) file, every task becomes a VTODO (with a DUE
+/// date if the task has one). The UIDs are `<id>@taskmaster.local`.
+pub fn export_ical(tasks: &[Task]) -> String {
+    export_ical_with_uid_base(tasks, DEFAULT_UID_BASE)
+}
+
+/// Like export_ical, but the UIDs are `<id>@<uid_base>`
+pub fn export_ical_with_uid_base(tasks: &[Task], uid_base: &str) -> String {
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, "PRODID:-//taskmaster//taskmaster//EN");
+    for task in tasks {
+        let status = if task.get_completed() {
+            "COMPLETED"
+        } else {
+            "NEEDS-ACTION"
+        };
+        push_line(&mut ics, "BEGIN:VTODO");
+        push_line(&mut ics, &format!("UID:{}@{}", task.get_id(), uid_base));
+        push_line(&mut ics, &format!("DTSTAMP:{}", timestamp));
+        push_line(
+            &mut ics,
+            &format!("SUMMARY:{}", escape_text(task.get_description())),
+        );
+        push_line(
+            &mut ics,
+            &format!("DESCRIPTION:{}", escape_text(task.get_description())),
+        );
+        push_line(
+            &mut ics,
+            &format!("PRIORITY:{}", ical_priority(task.priority())),
+        );
+        if let Some(due) = task.get_due() {
+            push_line(
+                &mut ics,
+                &format!("DUE;VALUE=DATE:{}", due.format("%Y%m%d")),
+            );
+        }
+        push_line(&mut ics, &format!("STATUS:{}", status));
+        push_line(&mut ics, "END:VTODO");
+    }
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// Reads all VTODO components of an iCalendar file as tasks. The ID is taken from the UID if it
+/// was exported by taskmaster (`<id>@...`), other tasks are numbered after the highest ID.
+pub fn import_ical(s: &str) -> Result<Vec<Task>, TaskError> {
+    let mut tasks = Vec::new();
+    let mut unnumbered = Vec::new();
+    let mut current: Option<Vec<(String, String)>> = None;
+
+    for line in unfold_lines(s) {
+        let (name, value) = split_content_line(&line)?;
+        match (name.as_str(), value.as_str()) {
+            ("BEGIN", "VTODO") => current = Some(Vec::new()),
+            ("END", "VTODO") => {
+                let properties = current.take().ok_or_else(|| {
+                    TaskError::ArgumentMismatch("END:VTODO without BEGIN:VTODO".to_string())
+                })?;
+                match task_from_properties(&properties) {
+                    (Some(_), task) => tasks.push(task),
+                    (None, task) => unnumbered.push(task),
+                }
+            }
+            _ => {
+                if let Some(properties) = current.as_mut() {
+                    properties.push((name, value));
+                }
+            }
+        }
+    }
+    if current.is_some() {
+        return Err(TaskError::ArgumentMismatch(
+            "VTODO is missing its END:VTODO".to_string(),
+        ));
+    }
+
+    let first_free_id = tasks.iter().map(Task::get_id).max().unwrap_or(0) + 1;
+    for (id, mut task) in (first_free_id..).zip(unnumbered) {
+        task.set_id(id);
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+/* builds a task from the properties of one VTODO; the ID is only known if the UID has one */
+fn task_from_properties(properties: &[(String, String)]) -> (Option<usize>, Task) {
+    let mut id = None;
+    let mut summary = None;
+    let mut description = None;
+    let mut priority = Priority::Medium;
+    let mut completed = false;
+    let mut due = None;
+    for (name, value) in properties {
+        match name.as_str() {
+            "UID" => {
+                id = value
+                    .split_once('@')
+                    .and_then(|(id, _)| id.parse::<usize>().ok())
+            }
+            "SUMMARY" => summary = Some(unescape_text(value)),
+            "DESCRIPTION" => description = Some(unescape_text(value)),
+            "PRIORITY" => priority = priority_from_ical(value),
+            "STATUS" => completed = value.eq_ignore_ascii_case("COMPLETED"),
+            "DUE" => due = date_from_ical(value),
+            _ => {}
+        }
+    }
+    let mut task = Task::new_task(
+        summary.or(description).unwrap_or_default(),
+        id.unwrap_or(0),
+        priority,
+    );
+    task.set_due(due);
+    if completed {
+        task.mark_completed();
+    }
+    (id, task)
+}
+
+/* a DATE (20241110) or DATE-TIME (20241110T120000Z) value; only the day is kept */
+fn date_from_ical(value: &str) -> Option<NaiveDate> {
+    value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+}
+
+/* iCalendar priorities go from 1 (highest) to 9 (lowest) */
+fn ical_priority(priority: Priority) -> u8 {
+    match priority {
+        Priority::High => 1,
+        Priority::Medium => 5,
+        Priority::Low => 9,
+    }
+}
+
+/* 0 means "undefined", which is treated as the default priority */
+fn priority_from_ical(value: &str) -> Priority {
+    match value.trim().parse::<u8>() {
+        Ok(1..=4) => Priority::High,
+        Ok(6..=9) => Priority::Low,
+        _ => Priority::Medium,
+    }
+}
+
+/* appends a content line, folded to at most 75 octets per physical line */
+fn push_line(ics: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            ics.push_str("\r\n ");
+            /* the leading space of a continuation line counts as well */
+            octets = 1;
+        }
+        ics.push(c);
+        octets += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/* joins folded lines (continuation lines start with a space or tab) */
+fn unfold_lines(s: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in s.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/* "NAME;PARAM=x:value" -> ("NAME", "value"); parameters are ignored */
+fn split_content_line(line: &str) -> Result<(String, String), TaskError> {
+    let (head, value) = line
+        .split_once(':')
+        .ok_or_else(|| TaskError::ArgumentMismatch(format!("invalid iCalendar line '{}'", line)))?;
+    let name = head.split(';').next().unwrap_or(head);
+    Ok((name.to_ascii_uppercase(), value.to_string()))
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn due_date_round_trips() {
+        let mut task = Task::new_task("Pay rent", 3, Priority::High);
+        task.set_due(NaiveDate::from_ymd_opt(2024, 11, 10));
+        let plain = Task::new_task("Someday", 4, Priority::Low);
+        let ics = export_ical(&[task, plain]);
+        assert!(ics.contains("DUE;VALUE=DATE:20241110\r\n"));
+        assert_eq!(ics.matches("DUE").count(), 1);
+
+        let tasks = import_ical(&ics).unwrap();
+        assert_eq!(tasks[0].get_due(), NaiveDate::from_ymd_opt(2024, 11, 10));
+        assert_eq!(tasks[1].get_due(), None);
+    }
+
+    #[test]
+    fn due_date_time_keeps_the_day() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nSUMMARY:Call Bob\r\n\
+                   DUE:20250102T093000Z\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let tasks = import_ical(ics).unwrap();
+        assert_eq!(tasks[0].get_due(), NaiveDate::from_ymd_opt(2025, 1, 2));
+    }
+}
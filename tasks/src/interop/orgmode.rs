@@ -0,0 +1,248 @@
+use crate::task::{Priority, Task};
+use crate::task_error::TaskError;
+use chrono::NaiveDate;
+
+/* body lines are indented, so a note line starting with '*' isn't taken for a headline */
+const BODY_INDENT: &str = "  ";
+
+/// Exports the tasks as an Emacs Org-mode file: every task becomes a `* TODO` or `* DONE`
+/// headline with a priority cookie ([#A] High, [#B] Medium, [#C] Low), the due date as
+/// `DEADLINE: <2024-11-15 Fri>` and the notes as body text. Tasks have no tags of their own,
+/// so "+tag" words of the description become the Org tags `:tag:`.
+pub fn to_orgmode(tasks: &[Task]) -> String {
+    let mut org = String::from("#+TITLE: Tasks\n\n");
+    for task in tasks {
+        let keyword = if task.get_completed() { "DONE" } else { "TODO" };
+        let (title, tags) = split_tags(task.get_description());
+        org.push_str(&format!(
+            "* {} [#{}] {}",
+            keyword,
+            org_priority(task.priority()),
+            title
+        ));
+        if !tags.is_empty() {
+            org.push_str(&format!(" :{}:", tags.join(":")));
+        }
+        org.push('\n');
+        if let Some(due) = task.get_due() {
+            org.push_str(&format!(
+                "{}DEADLINE: <{}>\n",
+                BODY_INDENT,
+                due.format("%Y-%m-%d %a")
+            ));
+        }
+        if let Some(notes) = task.get_notes() {
+            for line in notes.lines() {
+                if !line.is_empty() {
+                    org.push_str(BODY_INDENT);
+                }
+                org.push_str(line);
+                org.push('\n');
+            }
+        }
+    }
+    org
+}
+
+/// Reads the headlines of an Org-mode file as tasks, the reverse of to_orgmode. Headlines
+/// without TODO or DONE are pending tasks, without priority cookie Medium ones; Org tags are
+/// added to the description as "+tag" words. The tasks are numbered from 1 in file order.
+pub fn import_orgmode(s: &str) -> Result<Vec<Task>, TaskError> {
+    let mut tasks: Vec<Task> = Vec::new();
+    let mut body: Vec<&str> = Vec::new();
+    for line in s.lines() {
+        let line = line.trim_end_matches('\r');
+        match headline_title(line) {
+            Some(headline) => {
+                if let Some(task) = tasks.last_mut() {
+                    apply_body(task, &body)?;
+                }
+                body.clear();
+                tasks.push(task_from_headline(headline, tasks.len() + 1)?);
+            }
+            /* lines before the first headline (#+TITLE etc.) don't belong to a task */
+            None if !tasks.is_empty() => body.push(line),
+            None => {}
+        }
+    }
+    if let Some(task) = tasks.last_mut() {
+        apply_body(task, &body)?;
+    }
+    Ok(tasks)
+}
+
+/* the headline without its stars, None if the line isn't a headline */
+fn headline_title(line: &str) -> Option<&str> {
+    let title = line.trim_start_matches('*');
+    if title.len() == line.len() {
+        return None;
+    }
+    title.strip_prefix(' ')
+}
+
+fn task_from_headline(headline: &str, id: usize) -> Result<Task, TaskError> {
+    let mut rest = headline.trim();
+    let mut completed = false;
+    for (keyword, done) in [("TODO", false), ("DONE", true)] {
+        if let Some(after) = rest.strip_prefix(keyword)
+            && (after.is_empty() || after.starts_with(' '))
+        {
+            completed = done;
+            rest = after.trim_start();
+        }
+    }
+    let mut priority = Priority::Medium;
+    if let Some(after) = rest.strip_prefix("[#")
+        && let Some((cookie, after)) = after.split_once(']')
+    {
+        priority = priority_from_org(cookie)?;
+        rest = after.trim_start();
+    }
+    let (title, tags) = match rest.rsplit_once(' ') {
+        Some((title, tags)) if is_tag_list(tags) => (title.trim_end(), tags),
+        _ if is_tag_list(rest) => ("", rest),
+        _ => (rest, ""),
+    };
+    let mut words: Vec<String> = title.split_whitespace().map(str::to_string).collect();
+    words.extend(
+        tags.split(':')
+            .filter(|t| !t.is_empty())
+            .map(|tag| format!("+{}", tag)),
+    );
+    let description = words.join(" ");
+    if description.is_empty() {
+        return Err(TaskError::Empty(format!("Headline of task {}", id)));
+    }
+    let mut task = Task::new_task(description, id, priority);
+    if completed {
+        task.mark_completed();
+    }
+    Ok(task)
+}
+
+/* the first body line may be the planning line with the deadline, the rest are the notes */
+fn apply_body(task: &mut Task, body: &[&str]) -> Result<(), TaskError> {
+    let mut lines = body;
+    if let Some(first) = lines.first()
+        && let Some(due) = deadline(first)?
+    {
+        task.set_due(Some(due));
+        lines = &lines[1..];
+    }
+    let notes: Vec<&str> = lines
+        .iter()
+        .map(|line| line.strip_prefix(BODY_INDENT).unwrap_or(line.trim_start()))
+        .collect();
+    let notes = notes.join("\n");
+    if !notes.trim().is_empty() {
+        task.set_notes(Some(notes.trim_end().to_string()));
+    }
+    Ok(())
+}
+
+/* the date of "DEADLINE: <2024-11-15 Fri>"; None if the line has no deadline */
+fn deadline(line: &str) -> Result<Option<NaiveDate>, TaskError> {
+    let Some((_, after)) = line.split_once("DEADLINE:") else {
+        return Ok(None);
+    };
+    let date = after
+        .trim_start()
+        .strip_prefix('<')
+        .and_then(|d| d.get(..10))
+        .ok_or_else(|| {
+            TaskError::ArgumentMismatch(format!("invalid deadline '{}'", line.trim()))
+        })?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| TaskError::ArgumentMismatch(format!("invalid deadline '{}'", line.trim())))
+}
+
+/* ":tag1:tag2:" */
+fn is_tag_list(word: &str) -> bool {
+    word.len() > 2
+        && word.starts_with(':')
+        && word.ends_with(':')
+        && word.split(':').all(|tag| tag.chars().all(is_tag_char))
+}
+
+/* Org tags may contain letters, numbers, '_' and '@' */
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '@'
+}
+
+/* the description without its "+tag" words, and the tags */
+fn split_tags(description: &str) -> (String, Vec<&str>) {
+    let mut words = Vec::new();
+    let mut tags = Vec::new();
+    for word in description.split_whitespace() {
+        match word.strip_prefix('+') {
+            Some(tag) if !tag.is_empty() && tag.chars().all(is_tag_char) => tags.push(tag),
+            _ => words.push(word),
+        }
+    }
+    (words.join(" "), tags)
+}
+
+fn org_priority(priority: Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+fn priority_from_org(cookie: &str) -> Result<Priority, TaskError> {
+    match cookie {
+        "A" => Ok(Priority::High),
+        "B" => Ok(Priority::Medium),
+        "C" => Ok(Priority::Low),
+        _ => Err(TaskError::ArgumentMismatch(format!(
+            "unknown priority [#{}], expected A, B or C",
+            cookie
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut report = Task::new_task("Write the report +work +q4", 1, Priority::High);
+        report.set_due(NaiveDate::from_ymd_opt(2024, 11, 15));
+        report.set_notes(Some("* not a headline\n\n  indented line".to_string()));
+        let mut call = Task::new_task("Call Bob", 2, Priority::Low);
+        call.mark_completed();
+        let plain = Task::new_task("Pay rent @home", 3, Priority::Medium);
+        let tasks = vec![report, call, plain];
+
+        let org = to_orgmode(&tasks);
+        assert!(org.contains("* TODO [#A] Write the report :work:q4:\n"));
+        assert!(org.contains("  DEADLINE: <2024-11-15 Fri>\n"));
+        assert!(org.contains("* DONE [#C] Call Bob\n"));
+
+        let imported = import_orgmode(&org).unwrap();
+        assert_eq!(imported.len(), tasks.len());
+        for (original, task) in tasks.iter().zip(&imported) {
+            assert_eq!(task.get_id(), original.get_id());
+            assert_eq!(task.get_description(), original.get_description());
+            assert_eq!(task.priority(), original.priority());
+            assert_eq!(task.get_completed(), original.get_completed());
+            assert_eq!(task.get_due(), original.get_due());
+            assert_eq!(task.get_notes(), original.get_notes());
+        }
+    }
+
+    #[test]
+    fn import_defaults_and_errors() {
+        let tasks = import_orgmode("#+TITLE: x\nintro\n** Plain headline\n*bold* text\n").unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].get_description(), "Plain headline");
+        assert_eq!(tasks[0].priority(), Priority::Medium);
+        assert!(!tasks[0].get_completed());
+        assert!(import_orgmode("* TODO [#D] Unknown priority").is_err());
+        assert!(import_orgmode("* TODO Bad\n  DEADLINE: <soon>").is_err());
+        assert!(import_orgmode("").unwrap().is_empty());
+    }
+}
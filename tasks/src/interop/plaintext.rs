@@ -0,0 +1,188 @@
+use crate::task::{Priority, Task};
+use crate::task_error::TaskError;
+use chrono::NaiveDate;
+
+/* prefix of the due date word, e.g. "due:2024-11-10" */
+const DUE_PREFIX: &str = "due:";
+
+/* put before description words that would be read as due date or tag, e.g. "\#hashtag" */
+const ESCAPE: char = '\\';
+
+/// Writes one line per task, e.g. `[x] #5 (High) Buy groceries @home due:2024-11-10 #shopping`:
+/// `[x]` for completed and `[ ]` for pending tasks, the ID, the priority and the description,
+/// followed by the due date and the "+tag" words of the description as `#tag`. Words of the
+/// description that look like a due date or a tag (`due:...`, `#word`) or start with `\` get a
+/// `\` in front, so they are read back as they are. Everything else (notes, links, time log) is
+/// left out.
+pub fn to_plain_text(tasks: &[Task]) -> String {
+    let mut text = String::new();
+    for task in tasks {
+        let check = if task.get_completed() { "[x]" } else { "[ ]" };
+        let mut words: Vec<String> = vec![
+            check.to_string(),
+            format!("#{}", task.get_id()),
+            format!("({})", task.priority().name()),
+        ];
+        let mut tags = Vec::new();
+        for word in task.get_description().split_whitespace() {
+            match word.strip_prefix('+') {
+                Some(tag) if is_tag(tag) => tags.push(format!("#{}", tag)),
+                _ => words.push(escape(word)),
+            }
+        }
+        if let Some(due) = task.get_due() {
+            words.push(format!("{}{}", DUE_PREFIX, due.format("%Y-%m-%d")));
+        }
+        words.extend(tags);
+        text.push_str(&words.join(" "));
+        text.push('\n');
+    }
+    text
+}
+
+/// Reads tasks in the format of to_plain_text. ID and priority may be left out (the tasks are
+/// then numbered by line, Medium priority); `#tag` words become "+tag" words at the end of the
+/// description, a leading `\` is removed from a word and keeps it in the description as it is.
+/// Empty lines are skipped.
+pub fn import_plain_text(s: &str) -> Result<Vec<Task>, TaskError> {
+    let mut tasks = Vec::new();
+    for (index, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        tasks.push(task_from_line(line, index + 1)?);
+    }
+    Ok(tasks)
+}
+
+fn task_from_line(line: &str, line_number: usize) -> Result<Task, TaskError> {
+    let invalid = |reason: &str| {
+        TaskError::ArgumentMismatch(format!("line {}: {} in '{}'", line_number, reason, line))
+    };
+    let (completed, rest) = if let Some(rest) = line.strip_prefix("[ ]") {
+        (false, rest)
+    } else if let Some(rest) = line
+        .strip_prefix("[x]")
+        .or_else(|| line.strip_prefix("[X]"))
+    {
+        (true, rest)
+    } else {
+        return Err(invalid("missing [ ] or [x]"));
+    };
+    let mut words = rest.split_whitespace().peekable();
+    let mut id = line_number;
+    if let Some(number) = words.peek().and_then(|w| w.strip_prefix('#'))
+        && let Ok(number) = number.parse::<usize>()
+    {
+        id = number;
+        words.next();
+    }
+    let mut priority = Priority::Medium;
+    if let Some(name) = words
+        .peek()
+        .and_then(|w| w.strip_prefix('('))
+        .and_then(|w| w.strip_suffix(')'))
+    {
+        priority = name.parse()?;
+        words.next();
+    }
+    let mut description = Vec::new();
+    let mut tags = Vec::new();
+    let mut due = None;
+    for word in words {
+        if let Some(literal) = word.strip_prefix(ESCAPE) {
+            description.push(literal);
+        } else if let Some(date) = word.strip_prefix(DUE_PREFIX) {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| invalid(&format!("invalid due date '{}'", date)))?;
+            due = Some(date);
+        } else if let Some(tag) = word.strip_prefix('#')
+            && is_tag(tag)
+        {
+            tags.push(format!("+{}", tag));
+        } else {
+            description.push(word);
+        }
+    }
+    if description.is_empty() && tags.is_empty() {
+        return Err(invalid("no description"));
+    }
+    description.extend(tags.iter().map(String::as_str));
+    let mut task = Task::new_task(description.join(" "), id, priority);
+    task.set_due(due);
+    if completed {
+        task.mark_completed();
+    }
+    Ok(task)
+}
+
+/* the description word with ESCAPE in front if import_plain_text wouldn't read it as it is */
+fn escape(word: &str) -> String {
+    if word.starts_with(ESCAPE)
+        || word.starts_with(DUE_PREFIX)
+        || word.strip_prefix('#').is_some_and(is_tag)
+    {
+        format!("{}{}", ESCAPE, word)
+    } else {
+        word.to_string()
+    }
+}
+
+/* a tag starts with a letter, so "#42" in a description stays as it is */
+fn is_tag(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_alphabetic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let descriptions = [
+            "Buy groceries @home +shopping",
+            "Ask about due:friday in the standup",
+            "Post #rustlang news +work",
+            "Fix #42 and C:\\temp\\due: paths",
+            "\\#literal backslash",
+            "Plain task",
+        ];
+        let mut tasks: Vec<Task> = descriptions
+            .iter()
+            .enumerate()
+            .map(|(i, d)| Task::new_task(*d, i + 1, Priority::Low))
+            .collect();
+        tasks[0].set_due(NaiveDate::from_ymd_opt(2024, 11, 10));
+        tasks[1].set_priority(Priority::High);
+        tasks[2].mark_completed();
+
+        let text = to_plain_text(&tasks);
+        assert!(text.starts_with("[ ] #1 (Low) Buy groceries @home due:2024-11-10 #shopping\n"));
+        assert!(text.contains("[ ] #2 (High) Ask about \\due:friday in the standup\n"));
+        assert!(text.contains("[x] #3 (Low) Post \\#rustlang news #work\n"));
+
+        let imported = import_plain_text(&text).unwrap();
+        assert_eq!(imported.len(), tasks.len());
+        for (original, task) in tasks.iter().zip(&imported) {
+            assert_eq!(task.get_id(), original.get_id());
+            assert_eq!(task.get_description(), original.get_description());
+            assert_eq!(task.priority(), original.priority());
+            assert_eq!(task.get_completed(), original.get_completed());
+            assert_eq!(task.get_due(), original.get_due());
+        }
+        assert_eq!(to_plain_text(&imported), text);
+    }
+
+    #[test]
+    fn hand_written_lines() {
+        let tasks = import_plain_text("\n[ ] Call Bob #phone due:2024-11-10\n[X] Done\n").unwrap();
+        assert_eq!(tasks[0].get_id(), 2);
+        assert_eq!(tasks[0].get_description(), "Call Bob +phone");
+        assert_eq!(tasks[0].get_due(), NaiveDate::from_ymd_opt(2024, 11, 10));
+        assert!(tasks[1].get_completed());
+        assert!(import_plain_text("Call Bob").is_err());
+        assert!(import_plain_text("[ ] Call Bob due:someday").is_err());
+        assert!(import_plain_text("[ ] #4 (High)").is_err());
+    }
+}
@@ -1,8 +1,76 @@
+//! Task list management: the tasks themselves, loading and saving the task file, filtering,
+//! sorting and everything else the `taskmaster` CLI does with them.
+//!
+//! Import the commonly needed types with the prelude:
+//!
+//! ```no_run
+//! use tasks::prelude::*;
+//!
+//! # fn main() -> Result<(), TaskError> {
+//! let mut manager = TaskManager::default();
+//! manager.load_tasks()?;
+//! let id = manager.add_task("Write the report")?;
+//! manager.set_priority(id, Priority::High)?;
+//! manager.save_tasks()?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod audit;
+pub mod config;
+pub mod health;
+pub mod interop;
+pub mod lint;
+pub mod serializer;
 pub mod task;
+pub mod task_diff;
 pub mod task_error;
+pub mod task_filter;
 pub mod task_manager;
+pub mod task_template;
+pub mod telemetry;
+pub mod theme;
+pub mod util;
 
 /* Re-Export for Convencience, for other crates to easier use them */
-pub use task::{Priority, Task};
+pub use audit::{AuditEntry, AuditOperation, TaskAuditLog};
+pub use config::{Config, PriorityWeights, StorageFormat};
+pub use health::HealthIssue;
+pub use lint::{LintWarning, TaskLinter};
+pub use serializer::{CsvSerializer, JsonSerializer, Serializer, TomlSerializer, YamlSerializer};
+pub use task::{
+    Priority, TEMPLATE_PLACEHOLDERS, Task, TaskStatus, TimeEntry, format_minutes, parse_estimate,
+    sanitize_description, unknown_placeholders,
+};
+pub use task_diff::TaskDiff;
 pub use task_error::TaskError;
-pub use task_manager::TaskManager;
+pub use task_filter::TaskFilter;
+pub use task_manager::{
+    BatchHandle, COMMAND_NAMES, DeduplicateStrategy, GroupBy, LineResult, PriorityCounts, SortKey,
+    TagStats, TaskComparator, TaskManager, TaskOperation, TaskSnapshot, platform_default_task_file,
+};
+pub use task_template::TaskTemplate;
+pub use telemetry::{ErrorReporter, LogReporter, NoopReporter};
+pub use theme::{Color, Theme};
+
+/// All public types of the crate, for `use tasks::prelude::*;`
+pub mod prelude {
+    pub use crate::audit::{AuditEntry, AuditOperation, TaskAuditLog};
+    pub use crate::config::{Config, PriorityWeights, StorageFormat};
+    pub use crate::health::HealthIssue;
+    pub use crate::lint::{LintWarning, TaskLinter};
+    pub use crate::serializer::{
+        CsvSerializer, JsonSerializer, Serializer, TomlSerializer, YamlSerializer,
+    };
+    pub use crate::task::{Priority, Task, TaskStatus, TimeEntry};
+    pub use crate::task_diff::TaskDiff;
+    pub use crate::task_error::TaskError;
+    pub use crate::task_filter::TaskFilter;
+    pub use crate::task_manager::{
+        BatchHandle, DeduplicateStrategy, GroupBy, LineResult, PriorityCounts, SortKey, TagStats,
+        TaskComparator, TaskManager, TaskOperation, TaskSnapshot,
+    };
+    pub use crate::task_template::TaskTemplate;
+    pub use crate::telemetry::{ErrorReporter, LogReporter, NoopReporter};
+    pub use crate::theme::{Color, Theme};
+}
@@ -0,0 +1,246 @@
+use super::task::{Priority, Task};
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// descriptions longer than this (in characters) should move their details into the notes
+pub const MAX_DESCRIPTION_CHARS: usize = 100;
+/// more pending High tasks than this and High doesn't mean anything anymore
+pub const MAX_HIGH_PRIORITY_TASKS: usize = 20;
+/// pending tasks not changed for this many days are probably abandoned
+pub const STALE_DAYS: i64 = 60;
+/// completed tasks older than this many days only clutter the list
+pub const OLD_COMPLETED_DAYS: i64 = 90;
+
+/// An anti-pattern in the task list found by `TaskLinter::run`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintWarning {
+    /// a pending High task without due date
+    UrgentButUndated { id: usize },
+    /// the description has more than MAX_DESCRIPTION_CHARS characters
+    LongDescription { id: usize, chars: usize },
+    /// more than MAX_HIGH_PRIORITY_TASKS pending tasks have High priority
+    PriorityInflation { count: usize },
+    /// a pending task that wasn't changed for STALE_DAYS days or more
+    Stale { id: usize, days: i64 },
+    /// a completed task that wasn't changed for OLD_COMPLETED_DAYS days or more
+    OldCompleted { id: usize, days: i64 },
+}
+
+impl LintWarning {
+    /// the task the warning is about; None for warnings about the whole list
+    pub fn task_id(&self) -> Option<usize> {
+        match self {
+            LintWarning::UrgentButUndated { id }
+            | LintWarning::LongDescription { id, .. }
+            | LintWarning::Stale { id, .. }
+            | LintWarning::OldCompleted { id, .. } => Some(*id),
+            LintWarning::PriorityInflation { .. } => None,
+        }
+    }
+
+    /// true if `TaskManager::fix_lint_warning` can fix it
+    pub fn is_fixable(&self) -> bool {
+        matches!(
+            self,
+            LintWarning::LongDescription { .. } | LintWarning::OldCompleted { .. }
+        )
+    }
+
+    /// what the user can do about the warning
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            LintWarning::UrgentButUndated { .. } => {
+                "Give it a due date (taskmaster due <ID> <DATE>) or lower its priority."
+            }
+            LintWarning::LongDescription { .. } => {
+                "Use notes instead: keep the description short (fixable)."
+            }
+            LintWarning::PriorityInflation { .. } => {
+                "If everything is urgent, nothing is: lower the priority of some tasks."
+            }
+            LintWarning::Stale { .. } => "Work on it, or delete it if it isn't needed anymore.",
+            LintWarning::OldCompleted { .. } => {
+                "Clear it from the list (taskmaster purge, fixable)."
+            }
+        }
+    }
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintWarning::UrgentButUndated { id } => {
+                write!(
+                    f,
+                    "#{} is urgent but undated: High priority without due date",
+                    id
+                )
+            }
+            LintWarning::LongDescription { id, chars } => write!(
+                f,
+                "#{} has a long description ({} characters, more than {})",
+                id, chars, MAX_DESCRIPTION_CHARS
+            ),
+            LintWarning::PriorityInflation { count } => write!(
+                f,
+                "Priority inflation: {} pending tasks have High priority (more than {})",
+                count, MAX_HIGH_PRIORITY_TASKS
+            ),
+            LintWarning::Stale { id, days } => {
+                write!(f, "#{} wasn't changed for {} days", id, days)
+            }
+            LintWarning::OldCompleted { id, days } => {
+                write!(
+                    f,
+                    "#{} was completed {} days ago and never cleared",
+                    id, days
+                )
+            }
+        }
+    }
+}
+
+/// Checks a task list for anti-patterns of task management
+pub struct TaskLinter;
+
+impl TaskLinter {
+    /// the warnings for the tasks, in list order; the list-wide ones come first
+    pub fn run(tasks: &[Task]) -> Vec<LintWarning> {
+        Self::run_at(tasks, Utc::now())
+    }
+
+    /// like run, with the ages of the tasks counted up to `now`
+    pub fn run_at(tasks: &[Task], now: DateTime<Utc>) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+        let high_count = tasks
+            .iter()
+            .filter(|t| !t.get_completed() && t.priority() == Priority::High)
+            .count();
+        if high_count > MAX_HIGH_PRIORITY_TASKS {
+            warnings.push(LintWarning::PriorityInflation { count: high_count });
+        }
+        for task in tasks {
+            let id = task.get_id();
+            /* tasks of files before updated_at existed have no age */
+            let days = task
+                .get_updated_at()
+                .map(|updated_at| (now - updated_at).num_days());
+            if task.get_completed() {
+                let days = task
+                    .get_completed_at()
+                    .map(|completed_at| (now - completed_at).num_days());
+                if let Some(days) = days.filter(|days| *days >= OLD_COMPLETED_DAYS) {
+                    warnings.push(LintWarning::OldCompleted { id, days });
+                }
+                continue;
+            }
+            if task.priority() == Priority::High && task.get_due().is_none() {
+                warnings.push(LintWarning::UrgentButUndated { id });
+            }
+            let chars = task.get_description().chars().count();
+            if chars > MAX_DESCRIPTION_CHARS {
+                warnings.push(LintWarning::LongDescription { id, chars });
+            }
+            if let Some(days) = days.filter(|days| *days >= STALE_DAYS) {
+                warnings.push(LintWarning::Stale { id, days });
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn task(id: usize, description: &str, priority: Priority) -> Task {
+        Task::new_task(description, id, priority)
+    }
+
+    #[test]
+    fn fresh_tasks_have_no_warnings() {
+        let mut done = task(2, "Call Bob", Priority::Low);
+        done.mark_completed();
+        let tasks = [task(1, "Write the report", Priority::Medium), done];
+        assert!(TaskLinter::run(&tasks).is_empty());
+    }
+
+    #[test]
+    fn urgent_and_long_tasks() {
+        let long = "x".repeat(MAX_DESCRIPTION_CHARS + 1);
+        let mut urgent_done = task(3, "Pay rent", Priority::High);
+        urgent_done.mark_completed();
+        let tasks = [
+            task(1, "Pay rent", Priority::High),
+            task(2, &long, Priority::Low),
+            urgent_done,
+            task(4, &"y".repeat(MAX_DESCRIPTION_CHARS), Priority::Low),
+        ];
+        assert_eq!(
+            TaskLinter::run(&tasks),
+            [
+                LintWarning::UrgentButUndated { id: 1 },
+                LintWarning::LongDescription {
+                    id: 2,
+                    chars: MAX_DESCRIPTION_CHARS + 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn priority_inflation_comes_first() {
+        let mut tasks: Vec<Task> = (1..=MAX_HIGH_PRIORITY_TASKS)
+            .map(|id| {
+                let mut task = task(id, "Pay rent", Priority::High);
+                task.set_due(chrono::NaiveDate::from_ymd_opt(2030, 1, 1));
+                task
+            })
+            .collect();
+        assert!(TaskLinter::run(&tasks).is_empty());
+        let mut extra = task(MAX_HIGH_PRIORITY_TASKS + 1, "Call Bob", Priority::High);
+        extra.set_due(chrono::NaiveDate::from_ymd_opt(2030, 1, 1));
+        tasks.push(extra);
+        let warnings = TaskLinter::run(&tasks);
+        assert_eq!(
+            warnings,
+            [LintWarning::PriorityInflation {
+                count: MAX_HIGH_PRIORITY_TASKS + 1
+            }]
+        );
+        assert_eq!(warnings[0].task_id(), None);
+    }
+
+    #[test]
+    fn stale_and_old_completed_tasks() {
+        let mut done = task(2, "Call Bob", Priority::Low);
+        done.mark_completed();
+        let tasks = [task(1, "Write the report", Priority::Low), done];
+        let later = |days| Utc::now() + Duration::days(days);
+        assert!(TaskLinter::run_at(&tasks, later(STALE_DAYS - 1)).is_empty());
+        assert_eq!(
+            TaskLinter::run_at(&tasks, later(STALE_DAYS)),
+            [LintWarning::Stale {
+                id: 1,
+                days: STALE_DAYS
+            }]
+        );
+        let warnings = TaskLinter::run_at(&tasks, later(OLD_COMPLETED_DAYS));
+        assert_eq!(
+            warnings,
+            [
+                LintWarning::Stale {
+                    id: 1,
+                    days: OLD_COMPLETED_DAYS
+                },
+                LintWarning::OldCompleted {
+                    id: 2,
+                    days: OLD_COMPLETED_DAYS
+                },
+            ]
+        );
+        assert!(!warnings[0].is_fixable());
+        assert!(warnings[1].is_fixable());
+    }
+}
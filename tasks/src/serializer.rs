@@ -0,0 +1,247 @@
+use super::config::StorageFormat;
+use super::task::{Priority, Task};
+use super::task_error::TaskError;
+use chrono::NaiveDate;
+
+/// A file format for tasks, used with TaskManager::export_with, import_with, save_tasks_with
+/// and load_tasks_with, e.g. `manager.export_with(file, CsvSerializer)`.
+pub trait Serializer {
+    fn serialize(&self, tasks: &[Task]) -> Result<Vec<u8>, TaskError>;
+    /// empty input gives no tasks
+    fn deserialize(&self, data: &[u8]) -> Result<Vec<Task>, TaskError>;
+}
+
+/// JSON array, the default format of the task file: pretty-printed, or with
+/// `JsonSerializer::compact()` on a single line without spaces, for smaller files or piping.
+/// Both read pretty and compact JSON.
+///
+/// ```
+/// use tasks::{JsonSerializer, Priority, Serializer, Task};
+///
+/// let tasks = vec![Task::new_task("Write the report", 1, Priority::High)];
+/// let compact = JsonSerializer::compact().serialize(&tasks).unwrap();
+/// assert!(!compact.contains(&b'\n'));
+/// let loaded = JsonSerializer::pretty().deserialize(&compact).unwrap();
+/// assert_eq!(loaded[0].get_description(), "Write the report");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct JsonSerializer {
+    pretty: bool,
+}
+
+impl JsonSerializer {
+    /// indented over several lines, the default
+    pub const fn pretty() -> Self {
+        JsonSerializer { pretty: true }
+    }
+
+    /// on a single line without spaces
+    pub const fn compact() -> Self {
+        JsonSerializer { pretty: false }
+    }
+
+    pub fn is_pretty(&self) -> bool {
+        self.pretty
+    }
+}
+
+impl Default for JsonSerializer {
+    fn default() -> Self {
+        Self::pretty()
+    }
+}
+
+/// TOML with a [[tasks]] table per task
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TomlSerializer;
+
+/// YAML document starting with `---`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct YamlSerializer;
+
+/// CSV with a header row, for spreadsheets. Only the columns id, description, priority,
+/// completed, pinned, due, estimate_minutes, assignee, url and notes are written; time entries
+/// are lost and imported tasks count as changed now.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvSerializer;
+
+/* TOML has no top-level arrays, so the tasks are stored as [[tasks]] tables */
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TomlTaskFile<T> {
+    #[serde(default)]
+    tasks: T,
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "description",
+    "priority",
+    "completed",
+    "pinned",
+    "due",
+    "estimate_minutes",
+    "assignee",
+    "url",
+    "notes",
+];
+
+/* the input as text; None if there is nothing but whitespace */
+fn non_empty_text(data: &[u8]) -> Result<Option<&str>, TaskError> {
+    let text = std::str::from_utf8(data)
+        .map_err(|e| TaskError::ArgumentMismatch(format!("input is not UTF-8: {}", e)))?;
+    Ok(Some(text).filter(|t| !t.trim().is_empty()))
+}
+
+impl Serializer for JsonSerializer {
+    fn serialize(&self, tasks: &[Task]) -> Result<Vec<u8>, TaskError> {
+        if self.pretty {
+            Ok(serde_json::to_vec_pretty(tasks)?)
+        } else {
+            Ok(serde_json::to_vec(tasks)?)
+        }
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Vec<Task>, TaskError> {
+        match non_empty_text(data)? {
+            Some(text) => Ok(serde_json::from_str(text)?),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+impl Serializer for TomlSerializer {
+    fn serialize(&self, tasks: &[Task]) -> Result<Vec<u8>, TaskError> {
+        let file = TomlTaskFile { tasks };
+        Ok(toml::to_string_pretty(&file)?.into_bytes())
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Vec<Task>, TaskError> {
+        match non_empty_text(data)? {
+            Some(text) => {
+                let file: TomlTaskFile<Vec<Task>> = toml::from_str(text)?;
+                Ok(file.tasks)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+impl Serializer for YamlSerializer {
+    /* serde_yaml leaves out the document marker, but the format detection on load needs it */
+    fn serialize(&self, tasks: &[Task]) -> Result<Vec<u8>, TaskError> {
+        Ok(format!("---\n{}", serde_yaml::to_string(tasks)?).into_bytes())
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<Vec<Task>, TaskError> {
+        /* an empty document or one with just the `---` marker parses as null, not as a list */
+        match non_empty_text(data)? {
+            Some(text) if !text.trim().trim_start_matches("---").trim().is_empty() => {
+                Ok(serde_yaml::from_str(text)?)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+impl Serializer for CsvSerializer {
+    fn serialize(&self, tasks: &[Task]) -> Result<Vec<u8>, TaskError> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(CSV_COLUMNS)?;
+        for task in tasks {
+            writer.write_record([
+                task.get_id().to_string(),
+                task.get_description().to_string(),
+                task.priority().name().to_string(),
+                task.get_completed().to_string(),
+                task.get_pinned().to_string(),
+                task.get_due().map(|d| d.to_string()).unwrap_or_default(),
+                task.get_estimate_minutes()
+                    .map(|m| m.to_string())
+                    .unwrap_or_default(),
+                task.get_assignee().unwrap_or_default().to_string(),
+                task.get_url().unwrap_or_default().to_string(),
+                task.get_notes().unwrap_or_default().to_string(),
+            ])?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| TaskError::Unknown(format!("writing CSV failed: {}", e)))
+    }
+
+    /// Reads the columns by their name in the header row, so they may come in any order and
+    /// all but description may be missing
+    fn deserialize(&self, data: &[u8]) -> Result<Vec<Task>, TaskError> {
+        let mut reader = csv::Reader::from_reader(data);
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|h| h.trim() == name);
+        let description_column = column("description").ok_or_else(|| {
+            TaskError::ArgumentMismatch("the CSV has no 'description' column".to_string())
+        })?;
+        let mut tasks = Vec::new();
+        for (index, record) in reader.records().enumerate() {
+            let record = record?;
+            let line = index + 2; // the header is line 1
+            let field = |name: &str| {
+                column(name)
+                    .and_then(|i| record.get(i))
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+            };
+            let invalid = |name: &str, value: &str| {
+                TaskError::ArgumentMismatch(format!(
+                    "CSV line {}: '{}' is not a valid {}",
+                    line, value, name
+                ))
+            };
+            let description = record.get(description_column).unwrap_or_default().trim();
+            if description.is_empty() {
+                return Err(TaskError::Empty(format!(
+                    "Description in CSV line {}",
+                    line
+                )));
+            }
+            let id = match field("id") {
+                Some(value) => value.parse().map_err(|_| invalid("id", value))?,
+                None => 0,
+            };
+            let priority = match field("priority") {
+                Some(value) => value.parse()?,
+                None => Priority::default(),
+            };
+            let mut task = Task::new_task(description, id, priority);
+            if let Some(value) = field("completed")
+                && value
+                    .parse::<bool>()
+                    .map_err(|_| invalid("completed", value))?
+            {
+                task.mark_completed();
+            }
+            if let Some(value) = field("pinned") {
+                task.set_pinned(value.parse().map_err(|_| invalid("pinned", value))?);
+            }
+            if let Some(value) = field("due") {
+                let due = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .map_err(|_| invalid("due date", value))?;
+                task.set_due(Some(due));
+            }
+            if let Some(value) = field("estimate_minutes") {
+                let minutes = value.parse().map_err(|_| invalid("estimate", value))?;
+                task.set_estimate_minutes(Some(minutes));
+            }
+            task.set_assignee(field("assignee").map(str::to_string));
+            task.set_url(field("url").map(str::to_string));
+            task.set_notes(field("notes").map(str::to_string));
+            tasks.push(task);
+        }
+        Ok(tasks)
+    }
+}
+
+/// the serializer for a storage format; JSON is pretty-printed
+pub(crate) fn serializer_for(format: StorageFormat) -> &'static dyn Serializer {
+    match format {
+        StorageFormat::Json => &JsonSerializer { pretty: true },
+        StorageFormat::Toml => &TomlSerializer,
+        StorageFormat::Yaml => &YamlSerializer,
+    }
+}
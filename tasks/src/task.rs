@@ -1,20 +1,21 @@
-use colored::Colorize;
+use super::task_error::TaskError;
+use super::task_filter::TaskFilter;
+use super::theme::Theme;
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize}; // import the traits
 use std::fmt; // Display trait
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+/* default is needed - Priority is a new field and might not exist in older JSON files.
+ * Ord follows the order of the variants: Low < Medium < High */
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default, Serialize, Deserialize)]
 pub enum Priority {
     Low,
+    #[default]
     Medium,
     High,
 }
 
-/* default is needed - Priority is a new field and might not exist in older JSON files */
-impl Default for Priority {
-    fn default() -> Self {
-        Priority::Medium
-    }
-}
 /* this is how the Priority will be displayed */
 impl fmt::Display for Priority {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -26,13 +27,167 @@ impl fmt::Display for Priority {
     }
 }
 
+impl Priority {
+    /// the name of the priority, as accepted by from_str
+    pub fn name(&self) -> &'static str {
+        match self {
+            Priority::Low => "Low",
+            Priority::Medium => "Medium",
+            Priority::High => "High",
+        }
+    }
+
+    /// the priority as a number: 0 (Low), 1 (Medium) or 2 (High)
+    pub fn value(&self) -> u8 {
+        match self {
+            Priority::Low => 0,
+            Priority::Medium => 1,
+            Priority::High => 2,
+        }
+    }
+
+    /// the priority for a number as returned by value
+    pub fn from_value(value: u8) -> Result<Priority, TaskError> {
+        match value {
+            0 => Ok(Priority::Low),
+            1 => Ok(Priority::Medium),
+            2 => Ok(Priority::High),
+            _ => Err(TaskError::ArgumentMismatch(format!(
+                "{} is not a priority value, use 0 (low), 1 (medium) or 2 (high)",
+                value
+            ))),
+        }
+    }
+
+    /// the priority for its first letter, ignoring case: `h` (High), `m` (Medium) or `l` (Low)
+    ///
+    /// ```
+    /// use tasks::Priority;
+    ///
+    /// assert_eq!(Priority::from_char('H').unwrap(), Priority::High);
+    /// assert_eq!(Priority::from_char('m').unwrap(), Priority::Medium);
+    /// assert_eq!(Priority::from_char('l').unwrap(), Priority::Low);
+    /// assert!(Priority::from_char('u').is_err());
+    /// assert!(Priority::from_char('1').is_err());
+    /// // from_str takes the shortcuts as well
+    /// assert_eq!("h".parse::<Priority>().unwrap(), Priority::High);
+    /// assert!("hi".parse::<Priority>().is_err());
+    /// ```
+    pub fn from_char(c: char) -> Result<Priority, TaskError> {
+        match c.to_ascii_lowercase() {
+            'l' => Ok(Priority::Low),
+            'm' => Ok(Priority::Medium),
+            'h' => Ok(Priority::High),
+            _ => Err(TaskError::ArgumentMismatch(format!(
+                "'{}' is not a priority, use l (low), m (medium) or h (high)",
+                c
+            ))),
+        }
+    }
+
+    /// the next higher priority; None for High
+    pub fn higher(self) -> Option<Priority> {
+        Priority::from_value(self.value() + 1).ok()
+    }
+
+    /// the next lower priority; None for Low
+    pub fn lower(self) -> Option<Priority> {
+        self.value()
+            .checked_sub(1)
+            .and_then(|v| Priority::from_value(v).ok())
+    }
+}
+
+/* parses the name of a priority or its first letter (see from_char), ignoring case */
+impl FromStr for Priority {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.trim().chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Priority::from_char(c);
+        }
+        match s.trim().to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            _ => Err(TaskError::ArgumentMismatch(format!(
+                "'{}' is not a priority, use low, medium or high (or l, m, h)",
+                s
+            ))),
+        }
+    }
+}
+
+/// The state of a task as shown in the list, derived from its fields by Task::status
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum TaskStatus {
+    Pending,
+    /// pending and the due date has passed
+    Overdue,
+    /// pending and hidden from the list until a later day
+    Snoozed,
+    Completed,
+}
+
+/* the status column of the task list */
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskStatus::Pending => write!(f, "[·]"),
+            TaskStatus::Overdue => write!(f, "[!]"),
+            TaskStatus::Snoozed => write!(f, "[z]"),
+            TaskStatus::Completed => write!(f, "[✓]"),
+        }
+    }
+}
+
+impl TaskStatus {
+    /// all states, in the order they are shown in stats
+    pub const ALL: [TaskStatus; 4] = [
+        TaskStatus::Pending,
+        TaskStatus::Overdue,
+        TaskStatus::Snoozed,
+        TaskStatus::Completed,
+    ];
+
+    /// the name of the state
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "Pending",
+            TaskStatus::Overdue => "Overdue",
+            TaskStatus::Snoozed => "Snoozed",
+            TaskStatus::Completed => "Completed",
+        }
+    }
+}
+
+/// A period of work on a task; an entry without end is still running
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub start: DateTime<Utc>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl TimeEntry {
+    /// the length of a closed entry; None while it's running
+    pub fn duration(&self) -> Option<std::time::Duration> {
+        self.end
+            .map(|end| (end - self.start).to_std().unwrap_or_default())
+    }
+}
+
 /**** task_id: new field in version 0.3.0 */
 /* default is needed for serde default, backwards compatibility */
 fn default_task_id() -> usize {
     0
 }
 
-#[derive(Debug, Serialize, Deserialize)] // add Debug trait for easy printing during development
+#[derive(Debug, Clone, Serialize, Deserialize)] // add Debug trait for easy printing during development
 pub struct Task {
     #[serde(default = "default_task_id")]
     id: usize,
@@ -40,6 +195,45 @@ pub struct Task {
     completed: bool,
     #[serde(default)]
     priority: Priority,
+    #[serde(default)]
+    focused: bool,
+    #[serde(default)]
+    pinned: bool,
+    /* an "important" flag for tasks to keep an eye on, independent of the priority */
+    #[serde(default)]
+    starred: bool,
+    /* file the task was loaded from, if it's not the main task file; never persisted */
+    #[serde(skip)]
+    source_file: Option<String>,
+    #[serde(default)]
+    estimate_minutes: Option<u32>,
+    #[serde(default)]
+    assignee: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    due: Option<NaiveDate>,
+    /* hidden from the list until this day */
+    #[serde(default)]
+    snoozed_until: Option<NaiveDate>,
+    /* free text, may span several lines */
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    time_entries: Vec<TimeEntry>,
+    /* last change of the task's content (not of focus, pin or star); None for tasks from older files */
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+    /* the task this one is a subtask of */
+    #[serde(default)]
+    parent_id: Option<usize>,
+    /* None for tasks from older files */
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    /* when the task was marked completed; None for pending tasks and ones completed in older
+     * versions */
+    #[serde(default)]
+    completed_at: Option<DateTime<Utc>>,
 }
 
 impl Task {
@@ -50,6 +244,21 @@ impl Task {
             description: description.into(),
             completed: false,
             priority,
+            focused: false,
+            pinned: false,
+            starred: false,
+            source_file: None,
+            estimate_minutes: None,
+            assignee: None,
+            url: None,
+            due: None,
+            snoozed_until: None,
+            notes: None,
+            time_entries: Vec::new(),
+            updated_at: Some(Utc::now()),
+            parent_id: None,
+            created_at: Some(Utc::now()),
+            completed_at: None,
         }
     }
 
@@ -63,52 +272,875 @@ impl Task {
 
     /// ranks priority up
     pub fn prio_up(&mut self) {
-        self.priority = match self.priority {
-            Priority::Low => Priority::Medium,
-            Priority::Medium => Priority::High,
-            Priority::High => Priority::High,
-        };
+        self.priority = self.priority.higher().unwrap_or(self.priority);
+        self.touch();
     }
 
     /// ranks priority down
     pub fn prio_down(&mut self) {
-        self.priority = match self.priority {
-            Priority::Low => Priority::Low,
-            Priority::Medium => Priority::Low,
-            Priority::High => Priority::Medium,
-        };
+        self.priority = self.priority.lower().unwrap_or(self.priority);
+        self.touch();
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+        self.touch();
+    }
+
+    /// returns the plain priority value (get_priority returns the colored symbol)
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// returns the priority as a number, 0 (Low) to 2 (High)
+    pub fn get_priority_value(&self) -> u8 {
+        self.priority.value()
     }
 
-    pub fn get_priority(&self) -> colored::ColoredString {
+    /// the priority symbol in the color the theme has for it
+    pub fn get_priority(&self, theme: &Theme) -> colored::ColoredString {
         let prio_string = self.priority.to_string();
-        match self.priority {
-            Priority::Low => prio_string.green(),
-            Priority::Medium => prio_string.yellow(),
-            Priority::High => prio_string.red(),
-        }
+        let color = match self.priority {
+            Priority::Low => theme.low_priority_color,
+            Priority::Medium => theme.medium_priority_color,
+            Priority::High => theme.high_priority_color,
+        };
+        color.paint(&prio_string)
     }
 
     pub fn set_description<S: Into<String>>(&mut self, description: S) {
         self.description = description.into();
+        self.touch();
     }
 
     pub fn get_description(&self) -> &str {
         &self.description
     }
 
+    /// Cleans up a description that was pasted: see sanitize_description. A description that
+    /// would end up empty is left as it is. Returns true if it changed.
+    pub fn sanitize_description(&mut self) -> bool {
+        let sanitized = sanitize_description(&self.description);
+        if sanitized.is_empty() || sanitized == self.description {
+            return false;
+        }
+        self.set_description(sanitized);
+        true
+    }
+
+    /// returns true if the task fulfills all criteria of the filter, see TaskFilter
+    pub fn matches_filter(&self, filter: &TaskFilter) -> bool {
+        filter.matches(self)
+    }
+
+    /// The "+tag" words of the description, without the '+' and each tag once. Tasks have no
+    /// separate tags, so they are written into the description (see TaskTemplate).
+    pub fn tags(&self) -> Vec<&str> {
+        self.marked_words('+')
+    }
+
+    /// the "@context" words of the description, without the '@' and each context once
+    pub fn contexts(&self) -> Vec<&str> {
+        self.marked_words('@')
+    }
+
+    /* the words starting with the marker, without it; a lone marker isn't a word */
+    fn marked_words(&self, marker: char) -> Vec<&str> {
+        let mut words: Vec<&str> = Vec::new();
+        for word in self.description.split_whitespace() {
+            if let Some(word) = word.strip_prefix(marker)
+                && !word.is_empty()
+                && !words.contains(&word)
+            {
+                words.push(word);
+            }
+        }
+        words
+    }
+
+    /// number of words (whitespace-separated) of the description
+    pub fn word_count(&self) -> usize {
+        self.description.split_whitespace().count()
+    }
+
+    /// seconds it takes to read the description and the notes, at 200 words per minute
+    pub fn estimated_reading_time_seconds(&self) -> u32 {
+        let notes_words = self
+            .notes
+            .as_deref()
+            .map_or(0, |n| n.split_whitespace().count());
+        let words = self.word_count() + notes_words;
+        (words * 60 / READING_WORDS_PER_MINUTE) as u32
+    }
+
+    /// the reading time in whole minutes, e.g. "< 1 min" or "2 min"
+    pub fn estimated_reading_time_display(&self) -> String {
+        match self.estimated_reading_time_seconds() {
+            0..60 => "< 1 min".to_string(),
+            seconds => format!("{} min", (seconds + 30) / 60),
+        }
+    }
+
     pub fn mark_completed(&mut self) {
+        /* completing a completed task again keeps the time it was completed first */
+        if !self.completed {
+            self.completed_at = Some(Utc::now());
+        }
         self.completed = true;
+        self.touch();
     }
 
     pub fn get_completed(&self) -> bool {
         self.completed
     }
 
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn get_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+
+    pub fn get_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    pub fn set_starred(&mut self, starred: bool) {
+        self.starred = starred;
+    }
+
+    pub fn get_starred(&self) -> bool {
+        self.starred
+    }
+
+    pub fn set_source_file(&mut self, source_file: Option<String>) {
+        self.source_file = source_file;
+    }
+
+    /// the file this task was loaded from; None for tasks of the main task file
+    pub fn get_source_file(&self) -> Option<&str> {
+        self.source_file.as_deref()
+    }
+
+    pub fn set_estimate_minutes(&mut self, minutes: Option<u32>) {
+        self.estimate_minutes = minutes;
+        self.touch();
+    }
+
+    pub fn get_estimate_minutes(&self) -> Option<u32> {
+        self.estimate_minutes
+    }
+
+    pub fn set_assignee(&mut self, assignee: Option<String>) {
+        self.assignee = assignee;
+        self.touch();
+    }
+
+    pub fn get_assignee(&self) -> Option<&str> {
+        self.assignee.as_deref()
+    }
+
+    pub fn set_url(&mut self, url: Option<String>) {
+        self.url = url;
+        self.touch();
+    }
+
+    pub fn get_url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// makes the task a subtask of the task with the given ID; see TaskManager::set_parent,
+    /// which checks that the parent exists
+    pub fn set_parent_id(&mut self, parent_id: Option<usize>) {
+        self.parent_id = parent_id;
+        self.touch();
+    }
+
+    /* the parent got another ID, e.g. when it was loaded or saved; the task itself is
+     * unchanged, so updated_at stays */
+    pub(crate) fn renumber_parent(&mut self, parent_id: Option<usize>) {
+        self.parent_id = parent_id;
+    }
+
+    /// the ID of the task this one is a subtask of
+    pub fn get_parent_id(&self) -> Option<usize> {
+        self.parent_id
+    }
+
+    pub fn set_due(&mut self, due: Option<NaiveDate>) {
+        self.due = due;
+        self.touch();
+    }
+
+    pub fn get_due(&self) -> Option<NaiveDate> {
+        self.due
+    }
+
+    /// hides the task from the list until the given day, or shows it again with None; like
+    /// pinning, this doesn't count as a change of the task
+    pub fn set_snoozed_until(&mut self, date: Option<NaiveDate>) {
+        self.snoozed_until = date;
+    }
+
+    pub fn get_snoozed_until(&self) -> Option<NaiveDate> {
+        self.snoozed_until
+    }
+
+    /// true if the task is snoozed until a day after today
+    pub fn is_snoozed(&self) -> bool {
+        self.is_snoozed_on(Local::now().date_naive())
+    }
+
+    /// true if the task is snoozed until a day after the given one
+    pub fn is_snoozed_on(&self, today: NaiveDate) -> bool {
+        self.snoozed_until.is_some_and(|until| until > today)
+    }
+
+    /// sets the notes; empty notes are stored as None
+    pub fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes.filter(|n| !n.trim().is_empty());
+        self.touch();
+    }
+
+    pub fn get_notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    /// adds a line to the notes
+    pub fn append_note(&mut self, line: &str) {
+        let notes = match self.notes.take() {
+            Some(notes) => format!("{}\n{}", notes, line),
+            None => line.to_string(),
+        };
+        self.set_notes(Some(notes));
+    }
+
+    pub fn get_time_entries(&self) -> &[TimeEntry] {
+        &self.time_entries
+    }
+
+    /// the entry that was started, but not stopped yet
+    pub fn running_time_entry(&self) -> Option<&TimeEntry> {
+        self.time_entries.iter().rev().find(|e| e.end.is_none())
+    }
+
+    /// starts a new time entry now
+    pub fn start_time_entry(&mut self) {
+        self.time_entries.push(TimeEntry {
+            start: Utc::now(),
+            end: None,
+            note: None,
+        });
+    }
+
+    /// ends the latest running time entry now and returns it; None if none is running
+    pub fn stop_time_entry(&mut self) -> Option<&TimeEntry> {
+        let entry = self
+            .time_entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.end.is_none())?;
+        entry.end = Some(Utc::now());
+        Some(entry)
+    }
+
+    /// adds a finished time entry
+    pub fn add_time_entry(&mut self, entry: TimeEntry) {
+        self.time_entries.push(entry);
+    }
+
+    /// the time of all finished entries together; running entries don't count yet
+    pub fn total_time_spent(&self) -> std::time::Duration {
+        self.time_entries
+            .iter()
+            .filter_map(TimeEntry::duration)
+            .sum()
+    }
+
+    /// true if the task is pending and its due date is before the given day
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.completed && self.due.is_some_and(|due| due < today)
+    }
+
+    /// The due date relative to today, see format_due_date_relative_on
+    pub fn format_due_date_relative(&self) -> Option<String> {
+        self.format_due_date_relative_on(Local::now().date_naive())
+    }
+
+    /// The due date as "overdue by 2 days", "due today", "due tomorrow", "due in 5 days",
+    /// "due in 3 weeks" (from 14 days on) or "due in 2 months" (from 60 days on, a month counts
+    /// as 30 days). None if the task has no due date.
+    pub fn format_due_date_relative_on(&self, today: NaiveDate) -> Option<String> {
+        let days = (self.due? - today).num_days();
+        let text = match days {
+            ..=-2 => format!("overdue by {} days", -days),
+            -1 => "overdue by 1 day".to_string(),
+            0 => "due today".to_string(),
+            1 => "due tomorrow".to_string(),
+            2..=13 => format!("due in {} days", days),
+            14..=59 => format!("due in {} weeks", days / 7),
+            _ => format!("due in {} months", days / 30),
+        };
+        Some(text)
+    }
+
+    /// The color of the due date today, see due_date_color_on
+    pub fn due_date_color(&self) -> colored::Color {
+        self.due_date_color_on(Local::now().date_naive())
+    }
+
+    /// red if the due date has passed, yellow if it is at most 3 days away, green otherwise
+    /// (also without due date)
+    pub fn due_date_color_on(&self, today: NaiveDate) -> colored::Color {
+        match self.due.map(|due| (due - today).num_days()) {
+            Some(..0) => colored::Color::Red,
+            Some(0..=3) => colored::Color::Yellow,
+            _ => colored::Color::Green,
+        }
+    }
+
+    /// time of the last change of description, priority, status, due date, estimate, assignee,
+    /// link or notes
+    pub fn get_updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+
+    /// time the task was added; None for tasks from files of older versions
+    pub fn get_created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+
+    /// time the task was marked completed; None for pending tasks. Tasks completed by older
+    /// versions, which didn't record it, give the time of their last change instead.
+    pub fn get_completed_at(&self) -> Option<DateTime<Utc>> {
+        if !self.completed {
+            return None;
+        }
+        self.completed_at.or(self.updated_at)
+    }
+
+    /// true if the task's content wasn't changed for longer than the threshold. Tasks from files
+    /// of older versions, which don't know when they were changed, are never stale, and neither
+    /// is any task if the threshold reaches back before the earliest time chrono knows.
+    pub fn is_stale(&self, threshold: std::time::Duration) -> bool {
+        let Some(limit) = chrono::Duration::from_std(threshold)
+            .ok()
+            .and_then(|threshold| Utc::now().checked_sub_signed(threshold))
+        else {
+            return false;
+        };
+        self.updated_at.is_some_and(|updated_at| updated_at < limit)
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Some(Utc::now());
+    }
+
+    /// How urgent the task is today, see urgency_score_on
+    pub fn urgency_score(&self) -> f64 {
+        self.urgency_score_on(Local::now().date_naive())
+    }
+
+    /// Priority (Low 1, Medium 2, High 3) times a due date factor: 1.0 without due date,
+    /// max(1, 10 / (N + 1)) if due in N days. That gives at most 30 (High, due today), and
+    /// overdue tasks score 30 + priority × days overdue, so they are always the most urgent.
+    /// Completed tasks score 0.
+    pub fn urgency_score_on(&self, today: NaiveDate) -> f64 {
+        if self.completed {
+            return 0.0;
+        }
+        let base = match self.priority {
+            Priority::Low => 1.0,
+            Priority::Medium => 2.0,
+            Priority::High => 3.0,
+        };
+        match self.due.map(|due| (due - today).num_days()) {
+            None => base,
+            Some(days) if days < 0 => MAX_URGENCY_NOT_OVERDUE + base * (-days) as f64,
+            Some(days) => base * (10.0 / (days + 1) as f64).max(1.0),
+        }
+    }
+
+    /// the status symbol without the due date: "[✓]" for completed tasks, "[·]" otherwise
     pub fn get_status(&self) -> &str {
+        if self.completed { "[✓]" } else { "[·]" }
+    }
+
+    /// The state of the task today, see status_on
+    pub fn status(&self) -> TaskStatus {
+        self.status_on(Local::now().date_naive())
+    }
+
+    /// Completed, Snoozed if pending and snoozed until after today, Overdue if pending and due
+    /// before today, Pending otherwise
+    pub fn status_on(&self, today: NaiveDate) -> TaskStatus {
         if self.completed {
-            "[✓]"
+            TaskStatus::Completed
+        } else if self.is_snoozed_on(today) {
+            TaskStatus::Snoozed
+        } else if self.is_overdue(today) {
+            TaskStatus::Overdue
         } else {
-            "[·]"
+            TaskStatus::Pending
+        }
+    }
+
+    /// Replaces the placeholders in the template with the values of the task, e.g.
+    /// "{id}: {description}" gives "3: Buy milk". See TEMPLATE_PLACEHOLDERS for the known
+    /// placeholders; fields that aren't set give an empty string, unknown placeholders are left
+    /// as they are.
+    pub fn format_with_template(&self, template: &str) -> String {
+        let mut output = String::with_capacity(template.len());
+        for part in template_parts(template) {
+            match part {
+                TemplatePart::Text(text) => output.push_str(text),
+                TemplatePart::Placeholder(name) => match self.placeholder_value(name) {
+                    Some(value) => output.push_str(&value),
+                    None => {
+                        output.push('{');
+                        output.push_str(name);
+                        output.push('}');
+                    }
+                },
+            }
+        }
+        output
+    }
+
+    /* the value of a template placeholder; None if there is no such placeholder */
+    fn placeholder_value(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "id" => self.id.to_string(),
+            "description" => self.description.clone(),
+            "priority" => self.priority.name().to_string(),
+            "status" => if self.completed { "done" } else { "pending" }.to_string(),
+            "due" => self.due.map(|d| d.to_string()).unwrap_or_default(),
+            "assignee" => self.assignee.clone().unwrap_or_default(),
+            "url" => self.url.clone().unwrap_or_default(),
+            "estimate" => self
+                .estimate_minutes
+                .map(|m| format_minutes(m as u64))
+                .unwrap_or_default(),
+            "updated" => self.updated_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            "created" => self.created_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            "tags" => self.tags().join(","),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// the highest urgency score of a task that isn't overdue: High (3) due today (10)
+const MAX_URGENCY_NOT_OVERDUE: f64 = 30.0;
+
+/// the placeholders Task::format_with_template knows, without braces
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "id",
+    "description",
+    "priority",
+    "status",
+    "due",
+    "assignee",
+    "url",
+    "estimate",
+    "updated",
+    "created",
+    "tags",
+];
+
+/// average reading speed for estimated_reading_time_seconds
+const READING_WORDS_PER_MINUTE: usize = 200;
+
+/// punctuation that is removed from the start and end of descriptions
+const EDGE_PUNCTUATION: &[char] = &['.', ',', ';'];
+
+/// The description without whitespace and punctuation (. , ;) at the start and end, with runs
+/// of whitespace (tabs, line breaks too) as single spaces and without other control characters
+pub fn sanitize_description(description: &str) -> String {
+    let printable: String = description
+        .chars()
+        .filter(|c| c.is_whitespace() || !c.is_control())
+        .collect();
+    printable
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_whitespace() || EDGE_PUNCTUATION.contains(&c))
+        .to_string()
+}
+
+/// returns the placeholders of the template that format_with_template doesn't know, each once
+pub fn unknown_placeholders(template: &str) -> Vec<&str> {
+    let mut unknown = Vec::new();
+    for part in template_parts(template) {
+        if let TemplatePart::Placeholder(name) = part
+            && !TEMPLATE_PLACEHOLDERS.contains(&name)
+            && !unknown.contains(&name)
+        {
+            unknown.push(name);
+        }
+    }
+    unknown
+}
+
+enum TemplatePart<'t> {
+    Text(&'t str),
+    Placeholder(&'t str),
+}
+
+/* splits a template into text and {placeholders}; a '{' without closing '}' is plain text */
+fn template_parts(template: &str) -> Vec<TemplatePart<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(len) = rest[open + 1..].find(['{', '}']) else {
+            break;
+        };
+        let close = open + 1 + len;
+        if rest[close..].starts_with('{') {
+            /* "{a{b}": the first brace is text, the placeholder starts at the second one */
+            parts.push(TemplatePart::Text(&rest[..close]));
+            rest = &rest[close..];
+            continue;
+        }
+        if open > 0 {
+            parts.push(TemplatePart::Text(&rest[..open]));
+        }
+        parts.push(TemplatePart::Placeholder(&rest[open + 1..close]));
+        rest = &rest[close + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Text(rest));
+    }
+    parts
+}
+
+/// Parses a time estimate like "30m", "2h" or "1.5h" into minutes
+pub fn parse_estimate(s: &str) -> Result<u32, TaskError> {
+    let s = s.trim().to_lowercase();
+    let invalid = || {
+        TaskError::ArgumentMismatch(format!(
+            "'{}' is not a valid estimate, use e.g. 30m, 2h or 1.5h",
+            s
+        ))
+    };
+    let (number, minutes_per_unit) = if let Some(n) = s.strip_suffix('h') {
+        (n, 60.0)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 1.0)
+    } else {
+        return Err(invalid());
+    };
+    let value: f64 = number.trim().parse().map_err(|_| invalid())?;
+    if !value.is_finite() || value <= 0.0 {
+        return Err(invalid());
+    }
+    Ok((value * minutes_per_unit).round() as u32)
+}
+
+/// Formats minutes as hours and minutes, e.g. "4h 30m", "2h" or "45m"
+pub fn format_minutes(minutes: u64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/* plain single-line representation without colors, e.g. for logging:
+ * #<id> [<priority>] (<status>) <description> due:<YYYY-MM-DD>, the due date only if there is one.
+ * "@context" words are part of the description already */
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.completed { "done" } else { "pending" };
+        write!(
+            f,
+            "#{} [{}] ({}) {}",
+            self.id,
+            self.priority.name(),
+            status,
+            self.description
+        )?;
+        if let Some(due) = self.due {
+            write!(f, " due:{}", due.format("%Y-%m-%d"))?;
+        }
+        Ok(())
+    }
+}
+
+/// A task as a JSON object, with the same fields as in the task file, so the conversion back with
+/// `Task::try_from` gives the same task.
+///
+/// ```
+/// use serde_json::Value;
+/// use tasks::{Priority, Task};
+///
+/// let task = Task::new_task("Write the report", 1, Priority::High);
+/// let value = Value::from(task.clone());
+/// assert_eq!(value, serde_json::to_value(&task).unwrap());
+/// let back = Task::try_from(&value).unwrap();
+/// assert_eq!(back.get_description(), "Write the report");
+/// assert_eq!(Value::from(back), value);
+/// ```
+impl From<Task> for serde_json::Value {
+    fn from(task: Task) -> Self {
+        /* a task has only string keys and plain values, so this can't fail */
+        serde_json::to_value(task).unwrap_or_default()
+    }
+}
+
+/// Reads a task from a JSON object like in the task file; fails with TaskError::Json if
+/// fields are missing or have the wrong type
+impl TryFrom<serde_json::Value> for Task {
+    type Error = TaskError;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// like `TryFrom<Value>`, without taking the value
+impl TryFrom<&serde_json::Value> for Task {
+    type Error = TaskError;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self, Self::Error> {
+        Ok(Task::deserialize(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn priority_from_char_takes_both_cases() {
+        for (lower, upper, priority) in [
+            ('l', 'L', Priority::Low),
+            ('m', 'M', Priority::Medium),
+            ('h', 'H', Priority::High),
+        ] {
+            assert_eq!(Priority::from_char(lower).unwrap(), priority);
+            assert_eq!(Priority::from_char(upper).unwrap(), priority);
+            assert_eq!(lower.to_string().parse::<Priority>().unwrap(), priority);
+            assert_eq!(upper.to_string().parse::<Priority>().unwrap(), priority);
+        }
+    }
+
+    #[test]
+    fn priority_from_char_rejects_other_characters() {
+        for c in ['u', 'U', '0', '1', '2', ' ', '\t', 'ä', 'é'] {
+            assert!(
+                matches!(Priority::from_char(c), Err(TaskError::ArgumentMismatch(_))),
+                "{:?}",
+                c
+            );
+        }
+        for s in ["hi", "", " ", "u", "3"] {
+            assert!(s.parse::<Priority>().is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn json_value_round_trip_is_lossless() {
+        let mut task = Task::new_task("Write the report +work", 7, Priority::High);
+        task.set_due(NaiveDate::from_ymd_opt(2024, 11, 15));
+        task.set_snoozed_until(NaiveDate::from_ymd_opt(2024, 11, 1));
+        task.set_notes(Some("first draft\nthen review".to_string()));
+        task.set_assignee(Some("alice".to_string()));
+        task.set_url(Some("https://example.com/7".to_string()));
+        task.set_estimate_minutes(Some(90));
+        task.set_pinned(true);
+        task.set_starred(true);
+        task.start_time_entry();
+        task.mark_completed();
+
+        let value = serde_json::Value::from(task.clone());
+        assert_eq!(value, serde_json::to_value(&task).unwrap());
+        assert_eq!(value["description"], "Write the report +work");
+        assert_eq!(value["id"], 7);
+
+        let by_ref = Task::try_from(&value).unwrap();
+        let owned = Task::try_from(value.clone()).unwrap();
+        assert_eq!(serde_json::Value::from(by_ref), value);
+        assert_eq!(serde_json::Value::from(owned), value);
+    }
+
+    #[test]
+    fn json_values_that_arent_tasks() {
+        let mut value = serde_json::Value::from(Task::new_task("Call Bob", 1, Priority::Low));
+        value["priority"] = serde_json::json!("Urgent");
+        assert!(matches!(Task::try_from(&value), Err(TaskError::Json(_))));
+        value.as_object_mut().unwrap().remove("priority");
+        value.as_object_mut().unwrap().remove("description");
+        assert!(matches!(Task::try_from(value), Err(TaskError::Json(_))));
+        for not_a_task in [
+            serde_json::json!([]),
+            serde_json::json!("task"),
+            serde_json::Value::Null,
+        ] {
+            assert!(matches!(
+                Task::try_from(not_a_task),
+                Err(TaskError::Json(_))
+            ));
         }
     }
+
+    #[test]
+    fn sanitize_edge_cases() {
+        assert_eq!(
+            sanitize_description("  Buy\tmilk \n\n and  bread.  "),
+            "Buy milk and bread"
+        );
+        assert_eq!(sanitize_description("\t\t"), "");
+        assert_eq!(sanitize_description(" .;, "), "");
+        assert_eq!(sanitize_description("Ship it 🚀🚀 ,"), "Ship it 🚀🚀");
+        assert_eq!(sanitize_description("🎉"), "🎉");
+        assert_eq!(sanitize_description("Call\u{7}\u{0} Bob"), "Call Bob");
+        assert_eq!(sanitize_description("Grüße an Zoë"), "Grüße an Zoë");
+        /* only . , ; are stripped */
+        assert_eq!(sanitize_description("Really?!"), "Really?!");
+    }
+
+    #[test]
+    fn sanitizing_a_task_never_empties_it() {
+        let mut task = Task::new_task("\t", 1, Priority::Low);
+        assert!(!task.sanitize_description());
+        assert_eq!(task.get_description(), "\t");
+        let mut task = Task::new_task(" 🚀  launch ", 2, Priority::Low);
+        assert!(task.sanitize_description());
+        assert_eq!(task.get_description(), "🚀 launch");
+        assert!(!task.sanitize_description());
+    }
+
+    /* a task due the given number of days after 2024-11-10 */
+    fn due_in(days: i64) -> (Task, NaiveDate) {
+        let today = NaiveDate::from_ymd_opt(2024, 11, 10).unwrap();
+        let mut task = Task::new_task("Pay rent", 1, Priority::Medium);
+        task.set_due(Some(today + chrono::Duration::days(days)));
+        (task, today)
+    }
+
+    #[test]
+    fn relative_due_date_branches() {
+        let cases = [
+            (-10, "overdue by 10 days"),
+            (-2, "overdue by 2 days"),
+            (-1, "overdue by 1 day"),
+            (0, "due today"),
+            (1, "due tomorrow"),
+            (2, "due in 2 days"),
+            (13, "due in 13 days"),
+            (14, "due in 2 weeks"),
+            (59, "due in 8 weeks"),
+            (60, "due in 2 months"),
+            (400, "due in 13 months"),
+        ];
+        for (days, text) in cases {
+            let (task, today) = due_in(days);
+            assert_eq!(
+                task.format_due_date_relative_on(today).as_deref(),
+                Some(text)
+            );
+        }
+        let task = Task::new_task("Pay rent", 1, Priority::Medium);
+        assert_eq!(task.format_due_date_relative(), None);
+    }
+
+    #[test]
+    fn due_date_color_branches() {
+        let cases = [
+            (-1, colored::Color::Red),
+            (0, colored::Color::Yellow),
+            (3, colored::Color::Yellow),
+            (4, colored::Color::Green),
+        ];
+        for (days, color) in cases {
+            let (task, today) = due_in(days);
+            assert_eq!(task.due_date_color_on(today), color, "{} days", days);
+        }
+        let task = Task::new_task("Pay rent", 1, Priority::Medium);
+        assert_eq!(task.due_date_color(), colored::Color::Green);
+    }
+
+    #[test]
+    fn template_has_tags_and_created() {
+        let task = Task::new_task("Send invoice +work +billing", 4, Priority::Low);
+        assert_eq!(
+            task.format_with_template("{id}\t{tags}\t{nope}"),
+            "4\twork,billing\t{nope}"
+        );
+        let created = task.format_with_template("{created}");
+        assert_eq!(
+            DateTime::parse_from_rfc3339(&created).unwrap(),
+            task.get_created_at().unwrap()
+        );
+        assert_eq!(
+            Task::new_task("Call Bob", 1, Priority::Low).format_with_template("[{tags}]"),
+            "[]"
+        );
+        assert!(unknown_placeholders("{tags} {created}").is_empty());
+    }
+
+    #[test]
+    fn display_is_plain_text_with_due_date() {
+        let mut task = Task::new_task("Buy groceries @home", 5, Priority::High);
+        assert_eq!(task.to_string(), "#5 [High] (pending) Buy groceries @home");
+        task.set_due(NaiveDate::from_ymd_opt(2024, 11, 10));
+        task.mark_completed();
+        assert_eq!(
+            task.to_string(),
+            "#5 [High] (done) Buy groceries @home due:2024-11-10"
+        );
+    }
+
+    #[test]
+    fn overdue_tasks_score_highest() {
+        let today = NaiveDate::from_ymd_opt(2024, 11, 10).unwrap();
+        let task = |priority, due: Option<i64>| {
+            let mut task = Task::new_task("task", 1, priority);
+            task.set_due(due.map(|days| today + chrono::Duration::days(days)));
+            task.urgency_score_on(today)
+        };
+        let overdue_low = task(Priority::Low, Some(-1));
+        assert!(overdue_low > task(Priority::High, Some(0)));
+        assert!(overdue_low > task(Priority::High, None));
+        assert!(task(Priority::High, Some(-1)) > overdue_low);
+        assert!(task(Priority::Low, Some(-5)) > overdue_low);
+        /* the closer the due date, the more urgent */
+        assert!(task(Priority::Medium, Some(1)) > task(Priority::High, Some(30)));
+        assert!(task(Priority::Medium, Some(0)) > task(Priority::Medium, Some(2)));
+    }
+
+    #[test]
+    fn completed_tasks_are_not_urgent() {
+        let mut task = Task::new_task("task", 1, Priority::High);
+        task.set_due(Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()));
+        task.mark_completed();
+        assert_eq!(task.urgency_score(), 0.0);
+    }
+
+    #[test]
+    fn completion_time_survives_later_changes() {
+        let mut task = Task::new_task("Write the report", 1, Priority::Medium);
+        assert_eq!(task.get_completed_at(), None);
+        task.mark_completed();
+        let completed_at = task.get_completed_at().unwrap();
+        task.set_notes(Some("sent to Bob".to_string()));
+        task.mark_completed();
+        assert_eq!(task.get_completed_at(), Some(completed_at));
+        assert!(task.get_updated_at().unwrap() >= completed_at);
+    }
+
+    #[test]
+    fn completion_time_of_older_files_is_the_last_change() {
+        let json = r#"{"id": 1, "description": "old", "completed": true,
+                       "updated_at": "2024-01-02T03:04:05Z"}"#;
+        let task: Task = serde_json::from_str(json).unwrap();
+        assert_eq!(task.get_completed_at(), task.get_updated_at());
+    }
 }
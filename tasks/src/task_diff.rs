@@ -0,0 +1,139 @@
+use super::task::Task;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The differences between two task lists, with tasks matched by their ID.
+/// Created by `TaskManager::diff`.
+#[derive(Debug, Clone, Default)]
+pub struct TaskDiff<'a> {
+    /// tasks whose ID only exists in the second list
+    pub added: Vec<&'a Task>,
+    /// tasks whose ID only exists in the first list
+    pub removed: Vec<&'a Task>,
+    /// (before, after) of tasks that exist in both lists, but differ
+    pub modified: Vec<(&'a Task, &'a Task)>,
+}
+
+impl<'a> TaskDiff<'a> {
+    pub fn new(before: &'a [Task], after: &'a [Task]) -> Self {
+        let before_by_id: HashMap<usize, &Task> = before.iter().map(|t| (t.get_id(), t)).collect();
+        let after_by_id: HashMap<usize, &Task> = after.iter().map(|t| (t.get_id(), t)).collect();
+        let mut diff = TaskDiff::default();
+        for task in after {
+            match before_by_id.get(&task.get_id()) {
+                None => diff.added.push(task),
+                Some(old) if !changed_fields(old, task).is_empty() => {
+                    diff.modified.push((old, task))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = before
+            .iter()
+            .filter(|t| !after_by_id.contains_key(&t.get_id()))
+            .collect();
+        diff
+    }
+
+    /// true if both lists contain the same tasks
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Names of the fields that differ between two versions of a task. The time of the last change
+/// isn't compared, it changes with every other field anyway.
+pub fn changed_fields(before: &Task, after: &Task) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if before.get_description() != after.get_description() {
+        fields.push("description");
+    }
+    if before.get_completed() != after.get_completed() {
+        fields.push("status");
+    }
+    if before.priority() != after.priority() {
+        fields.push("priority");
+    }
+    if before.get_due() != after.get_due() {
+        fields.push("due date");
+    }
+    if before.get_snoozed_until() != after.get_snoozed_until() {
+        fields.push("snooze");
+    }
+    if before.get_estimate_minutes() != after.get_estimate_minutes() {
+        fields.push("estimate");
+    }
+    if before.get_assignee() != after.get_assignee() {
+        fields.push("assignee");
+    }
+    if before.get_url() != after.get_url() {
+        fields.push("link");
+    }
+    if before.get_notes() != after.get_notes() {
+        fields.push("notes");
+    }
+    if before.get_time_entries() != after.get_time_entries() {
+        fields.push("time log");
+    }
+    if before.get_focused() != after.get_focused() {
+        fields.push("focus");
+    }
+    if before.get_pinned() != after.get_pinned() {
+        fields.push("pin");
+    }
+    if before.get_starred() != after.get_starred() {
+        fields.push("star");
+    }
+    fields
+}
+
+/* one line per change: "+" added, "-" removed, "~" modified */
+impl fmt::Display for TaskDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for task in &self.removed {
+            writeln!(
+                f,
+                "- #{} [{}] {}",
+                task.get_id(),
+                task.priority().name(),
+                task.get_description()
+            )?;
+        }
+        for task in &self.added {
+            writeln!(
+                f,
+                "+ #{} [{}] {}",
+                task.get_id(),
+                task.priority().name(),
+                task.get_description()
+            )?;
+        }
+        for (before, after) in &self.modified {
+            writeln!(
+                f,
+                "~ #{} {} changed",
+                after.get_id(),
+                changed_fields(before, after).join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Priority;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn snoozing_is_a_change() {
+        let before = Task::new_task("Pay rent", 1, Priority::Medium);
+        let mut after = before.clone();
+        assert!(changed_fields(&before, &after).is_empty());
+        after.set_snoozed_until(NaiveDate::from_ymd_opt(2030, 1, 1));
+        assert_eq!(changed_fields(&before, &after), ["snooze"]);
+        let (old, new) = (vec![before], vec![after]);
+        assert_eq!(TaskDiff::new(&old, &new).modified.len(), 1);
+    }
+}
@@ -2,7 +2,37 @@ use rustyline::error::ReadlineError;
 use std::io;
 use thiserror::Error;
 
+/* one violation per line, as a bulleted list */
+fn format_violations(violations: &[String]) -> String {
+    let mut report = String::from("Validation failed:");
+    for violation in violations {
+        report.push_str("\n  • ");
+        report.push_str(violation);
+    }
+    report
+}
+
+/* "#3, #7" */
+fn format_ids(ids: &[usize]) -> String {
+    ids.iter()
+        .map(|id| format!("#{}", id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/* "2 pending subtasks (#4, #5)" */
+fn format_pending_subtasks(ids: &[usize]) -> String {
+    let noun = if ids.len() == 1 {
+        "subtask"
+    } else {
+        "subtasks"
+    };
+    format!("{} pending {} ({})", ids.len(), noun, format_ids(ids))
+}
+
+/* more variants are to come (file locking), so callers outside the crate need a catch-all arm */
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum TaskError {
     #[error("Task with id {0} not found")]
     TaskNotFound(usize),
@@ -12,6 +42,14 @@ pub enum TaskError {
     Empty(String),
     #[error("Error parsing/serializing JSON data: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("Error parsing TOML data: {0}")]
+    TomlDe(#[from] toml::de::Error),
+    #[error("Error serializing TOML data: {0}")]
+    TomlSer(#[from] toml::ser::Error),
+    #[error("Error parsing/serializing YAML data: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("Error parsing/serializing CSV data: {0}")]
+    Csv(#[from] csv::Error),
     #[error("An unknown error occured: {0}")]
     Unknown(String),
     #[error("Interactive input error: {0}")]
@@ -20,4 +58,39 @@ pub enum TaskError {
     InputCancelled,
     #[error("Argument mismatch: {0}")]
     ArgumentMismatch(String),
+    #[error("Invalid regular expression: {0}")]
+    Regex(#[from] regex::Error),
+    #[error("Invalid task data: {0}")]
+    Corrupt(String),
+    #[error("Ambiguous: matched tasks {}", format_ids(.0))]
+    Ambiguous(Vec<usize>),
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("{}", format_violations(.0))]
+    ValidationErrors(Vec<String>),
+    #[error("Task #{parent_id} has {}", format_pending_subtasks(.pending_child_ids))]
+    HasPendingChildren {
+        parent_id: usize,
+        pending_child_ids: Vec<usize>,
+    },
+    #[error("No task file to save to, set one with TaskManager::set_file_path first")]
+    NoFilePath,
+    #[error("Operation '{operation}' timed out after {after_ms}ms")]
+    Timeout { operation: String, after_ms: u64 },
+    #[error("Not supported on this platform: {0}")]
+    NotSupported(String),
+}
+
+/* a lock attempt that would have to wait gives WouldBlock: for callers it's a timeout without
+ * any waiting */
+impl From<io::ErrorKind> for TaskError {
+    fn from(kind: io::ErrorKind) -> Self {
+        match kind {
+            io::ErrorKind::WouldBlock => TaskError::Timeout {
+                operation: "I/O".to_string(),
+                after_ms: 0,
+            },
+            kind => TaskError::Io(kind.into()),
+        }
+    }
 }
@@ -0,0 +1,140 @@
+use super::task::{Priority, Task};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Criteria for selecting tasks, e.g. for the task list. Fields that are not set don't filter.
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    /// only tasks assigned to this person
+    pub assignee: Option<String>,
+    /// only tasks with a link
+    pub has_link: bool,
+    /// only starred tasks
+    pub starred: bool,
+    /// only tasks with this priority
+    pub priority: Option<Priority>,
+    /// only completed (true) or only pending (false) tasks
+    pub completed: Option<bool>,
+    /// only tasks whose description contains this text, ignoring case
+    pub search: Option<String>,
+    /// only tasks with this "+tag" word in the description (given without the '+')
+    pub tag: Option<String>,
+    /// only tasks with this "@context" word in the description (given without the '@')
+    pub context: Option<String>,
+    /// only tasks due on or after this day
+    pub due_since: Option<NaiveDate>,
+    /// only tasks due before this day
+    pub due_before: Option<NaiveDate>,
+    /// also snoozed tasks, which are left out otherwise
+    pub include_snoozed: bool,
+    /// only tasks whose description has at least this many words
+    pub min_words: Option<usize>,
+    /// only tasks whose description has at most this many words
+    pub max_words: Option<usize>,
+    /// only tasks added at or after this time
+    pub created_since: Option<DateTime<Utc>>,
+    /// only tasks added before this time
+    pub created_before: Option<DateTime<Utc>>,
+    /// only tasks completed at or after this time, see Task::get_completed_at
+    pub completed_since: Option<DateTime<Utc>>,
+    /// only tasks completed before this time, see Task::get_completed_at
+    pub completed_before: Option<DateTime<Utc>>,
+}
+
+impl TaskFilter {
+    /// returns true if the task fulfills all criteria of the filter, same as
+    /// `task.matches_filter(filter)`
+    pub fn matches(&self, task: &Task) -> bool {
+        if let Some(assignee) = &self.assignee
+            && task.get_assignee() != Some(assignee.as_str())
+        {
+            return false;
+        }
+        if self.has_link && task.get_url().is_none() {
+            return false;
+        }
+        if self.starred && !task.get_starred() {
+            return false;
+        }
+        if !self.include_snoozed && task.is_snoozed() {
+            return false;
+        }
+        if self
+            .priority
+            .is_some_and(|priority| task.priority() != priority)
+        {
+            return false;
+        }
+        if self
+            .completed
+            .is_some_and(|completed| task.get_completed() != completed)
+        {
+            return false;
+        }
+        if let Some(search) = &self.search
+            && !task
+                .get_description()
+                .to_lowercase()
+                .contains(&search.to_lowercase())
+        {
+            return false;
+        }
+        if let Some(tag) = &self.tag
+            && !task.tags().contains(&tag.trim_start_matches('+'))
+        {
+            return false;
+        }
+        if let Some(context) = &self.context
+            && !task.contexts().contains(&context.trim_start_matches('@'))
+        {
+            return false;
+        }
+        if (self.due_since.is_some() || self.due_before.is_some())
+            && !task.get_due().is_some_and(|due| {
+                self.due_since.is_none_or(|since| due >= since)
+                    && self.due_before.is_none_or(|before| due < before)
+            })
+        {
+            return false;
+        }
+        let words = task.word_count();
+        if self.min_words.is_some_and(|min| words < min)
+            || self.max_words.is_some_and(|max| words > max)
+        {
+            return false;
+        }
+        /* tasks of older files don't know when they were added or changed, so they can't be in
+         * any time range */
+        if !in_range(
+            task.get_created_at(),
+            self.created_since,
+            self.created_before,
+        ) {
+            return false;
+        }
+        if (self.completed_since.is_some() || self.completed_before.is_some())
+            && (!task.get_completed()
+                || !in_range(
+                    task.get_completed_at(),
+                    self.completed_since,
+                    self.completed_before,
+                ))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/* true if there is no range or the time is in it (since inclusive, before exclusive) */
+fn in_range(
+    time: Option<DateTime<Utc>>,
+    since: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+) -> bool {
+    if since.is_none() && before.is_none() {
+        return true;
+    }
+    time.is_some_and(|time| {
+        since.is_none_or(|since| time >= since) && before.is_none_or(|before| time < before)
+    })
+}
@@ -1,16 +1,432 @@
-use super::task::Task;
+use super::audit::{AuditOperation, TaskAuditLog};
+use super::config::{Config, StorageFormat};
+use super::health::{self, HealthIssue};
+use super::interop::plaintext;
+use super::lint::{LintWarning, MAX_DESCRIPTION_CHARS};
+use super::serializer::{
+    JsonSerializer, Serializer, TomlSerializer, YamlSerializer, serializer_for,
+};
+use super::task::{Priority, Task, TaskStatus, TimeEntry, format_minutes, sanitize_description};
+use super::task_diff::TaskDiff;
 use super::task_error::TaskError;
+use super::task_filter::TaskFilter;
+use super::telemetry::{ErrorReporter, NoopReporter};
+use super::util::check_url;
+use chrono::{Days, Local, NaiveDate, NaiveDateTime, Utc};
 use colored::Colorize;
+use rand::seq::IndexedRandom;
+use regex::Regex;
 use serde_json;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/* styles and scripts of the HTML report; everything is inline, so the page works offline */
+const HTML_STYLE: &str = r#"body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.5em; }
+#search { padding: 0.4em; width: 20em; margin-bottom: 1em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.4em 0.8em; border-bottom: 1px solid #ddd; }
+th { cursor: pointer; background: #f4f4f4; user-select: none; }
+th:hover { background: #e8e8e8; }
+.badge { display: inline-block; padding: 0.1em 0.6em; border-radius: 0.8em; color: #fff; font-size: 0.85em; }
+.prio-high { background: #d33; }
+.prio-medium { background: #c90; }
+.prio-low { background: #393; }
+tr.completed td.desc { text-decoration: line-through; color: #888; }
+footer { margin-top: 1em; color: #888; font-size: 0.85em; }"#;
+
+const HTML_SCRIPT: &str = r#"function filterRows() {
+  const query = document.getElementById('search').value.toLowerCase();
+  for (const row of document.querySelectorAll('#tasks tbody tr')) {
+    row.style.display = row.textContent.toLowerCase().includes(query) ? '' : 'none';
+  }
+}
+function sortBy(column) {
+  const body = document.querySelector('#tasks tbody');
+  const header = document.querySelectorAll('#tasks th')[column];
+  const ascending = header.dataset.order !== 'asc';
+  header.dataset.order = ascending ? 'asc' : 'desc';
+  const key = row => row.cells[column].dataset.sort ?? row.cells[column].textContent;
+  const rows = Array.from(body.rows).sort((a, b) => {
+    const result = key(a).localeCompare(key(b), undefined, { numeric: true });
+    return ascending ? result : -result;
+  });
+  rows.forEach(row => body.appendChild(row));
+}"#;
+
+/// if this environment variable is set (to any value), TaskManagers neither load nor save files
+pub const NO_SAVE_ENV: &str = "TASKMASTER_NO_SAVE";
+
+/// timestamp used in the names of backup files
+pub const BACKUP_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
 
 #[derive(Debug)]
 pub struct TaskManager {
     tasks: Vec<Task>,
     file_path: PathBuf,
     next_available_id: usize,
+    in_batch: bool,
+    /* files loaded with load_additional; their tasks are saved back to them */
+    additional_files: Vec<PathBuf>,
+    /* tasks of additional files whose ID had to be changed: current ID -> ID in their file */
+    original_ids: HashMap<usize, usize>,
+    audit_log: Option<TaskAuditLog>,
+    config: Config,
+    dry_run: bool,
+    no_save: bool,
+    verbose: bool,
+    /* the task list shows the reading time of each task */
+    long_list: bool,
+    hooks: IoHooks,
+    error_reporter: Arc<dyn ErrorReporter>,
+}
+
+/* callbacks of library users around loading and saving, see set_pre_save_hook and
+ * set_post_load_hook; closures aren't Debug, so only their presence is shown */
+type PreSaveHook = Box<dyn Fn(&[Task])>;
+type PostLoadHook = Box<dyn Fn(&mut Vec<Task>)>;
+
+#[derive(Default)]
+struct IoHooks {
+    pre_save: Option<PreSaveHook>,
+    post_load: Option<PostLoadHook>,
+}
+
+impl std::fmt::Debug for IoHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IoHooks")
+            .field("pre_save", &self.pre_save.is_some())
+            .field("post_load", &self.post_load.is_some())
+            .finish()
+    }
+}
+
+/// How the task list is divided into sections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// one section per priority, High first
+    Priority,
+    /// pending tasks, then completed ones
+    Status,
+    /// one section per "@context" (by the first one of a task, sorted by name), then the tasks
+    /// without context
+    Context,
+    /// no sections
+    #[default]
+    None,
+}
+
+impl FromStr for GroupBy {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "priority" => Ok(GroupBy::Priority),
+            "status" => Ok(GroupBy::Status),
+            "context" => Ok(GroupBy::Context),
+            "none" => Ok(GroupBy::None),
+            _ => Err(TaskError::ArgumentMismatch(format!(
+                "unknown grouping '{}', expected priority, status, context or none",
+                s
+            ))),
+        }
+    }
+}
+
+/// Orders for the task list
+///
+/// Other orders can be given as a comparison function, e.g. the shortest description first:
+///
+/// ```
+/// # use tasks::{SortKey, TaskManager};
+/// # use std::sync::Arc;
+/// let mut manager = TaskManager::in_memory();
+/// manager.add_task("write the quarterly report").unwrap();
+/// manager.add_task("call Bob").unwrap();
+/// let by_length = SortKey::Custom(Arc::new(|a, b| {
+///     a.get_description().len().cmp(&b.get_description().len())
+/// }));
+/// manager.sort_tasks(&[by_length, SortKey::Id]);
+/// assert_eq!(manager.iter().next().unwrap().get_description(), "call Bob");
+/// ```
+#[derive(Clone)]
+pub enum SortKey {
+    /// most urgent first, see Task::urgency_score
+    Urgency,
+    /// High first, starred tasks first within the same priority
+    Priority,
+    /// earliest due date first, tasks without due date last
+    Due,
+    /// lowest ID first
+    Id,
+    /// the order of the comparison function
+    Custom(TaskComparator),
+}
+
+/// a comparison function for SortKey::Custom
+pub type TaskComparator = Arc<dyn Fn(&Task, &Task) -> Ordering + Send + Sync>;
+
+/* closures are neither Debug nor comparable: custom keys show as Custom(..) and are only equal
+ * to themselves (the same Arc) */
+impl std::fmt::Debug for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortKey::Urgency => write!(f, "Urgency"),
+            SortKey::Priority => write!(f, "Priority"),
+            SortKey::Due => write!(f, "Due"),
+            SortKey::Id => write!(f, "Id"),
+            SortKey::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+impl PartialEq for SortKey {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SortKey::Custom(a), SortKey::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for SortKey {}
+
+impl SortKey {
+    /* compares two tasks by this key; the urgency depends on the day */
+    fn compare(&self, a: &Task, b: &Task, today: NaiveDate) -> Ordering {
+        match self {
+            SortKey::Urgency => b
+                .urgency_score_on(today)
+                .total_cmp(&a.urgency_score_on(today)),
+            SortKey::Priority => b
+                .priority()
+                .cmp(&a.priority())
+                .then(b.get_starred().cmp(&a.get_starred())),
+            /* None would come first, so compare "has no due date" first */
+            SortKey::Due => {
+                (a.get_due().is_none(), a.get_due()).cmp(&(b.get_due().is_none(), b.get_due()))
+            }
+            SortKey::Id => a.get_id().cmp(&b.get_id()),
+            SortKey::Custom(compare) => compare(a, b),
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "urgency" => Ok(SortKey::Urgency),
+            "priority" => Ok(SortKey::Priority),
+            "due" => Ok(SortKey::Due),
+            "id" => Ok(SortKey::Id),
+            _ => Err(TaskError::ArgumentMismatch(format!(
+                "unknown sort order '{}', expected urgency, priority, due or id",
+                s
+            ))),
+        }
+    }
+}
+
+/// Which tasks TaskManager::deduplicate treats as duplicates of each other
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeduplicateStrategy {
+    /// the same description, ignoring case; the task with the lowest ID is kept
+    Exact,
+    /// descriptions with a similarity (see fuzzy_find) above the threshold; the task with the
+    /// highest priority is kept, the lowest ID if the priorities are equal
+    Fuzzy(f32),
+}
+
+impl DeduplicateStrategy {
+    /* true if b counts as a duplicate of a */
+    fn is_duplicate(&self, a: &Task, b: &Task) -> bool {
+        match self {
+            DeduplicateStrategy::Exact => {
+                a.get_description().trim().to_lowercase()
+                    == b.get_description().trim().to_lowercase()
+            }
+            DeduplicateStrategy::Fuzzy(threshold) => {
+                bigram_similarity(a.get_description(), b.get_description()) > *threshold
+            }
+        }
+    }
+
+    /* the task that is kept comes first */
+    fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        match self {
+            DeduplicateStrategy::Exact => a.get_id().cmp(&b.get_id()),
+            DeduplicateStrategy::Fuzzy(_) => b
+                .priority()
+                .cmp(&a.priority())
+                .then(a.get_id().cmp(&b.get_id())),
+        }
+    }
+}
+
+/// the commands TaskManager::apply_command_str understands, with their short forms
+pub const COMMAND_NAMES: &[&str] = &[
+    "a", "add", "c", "complete", "d", "delete", "+", "up", "-", "down", "prio", "priority", "ch",
+    "change", "pin", "unpin", "swap", "clr", "clear",
+];
+
+/// the result of one line of a batch: its line number (starting at 1) and what the command gave
+pub type LineResult = (usize, Result<String, TaskError>);
+
+/// A single mutation that can be applied with `TaskManager::apply_operations`
+#[derive(Debug, Clone)]
+pub enum TaskOperation {
+    Add(String),
+    Complete(usize),
+    Delete(usize),
+    SetPriority(usize, Priority),
+    ChangeDescription(usize, String),
+}
+
+/// Returned by `TaskManager::begin_batch`. While the batch is open, `save_tasks` does not write
+/// to disk; `commit` ends the batch and writes the file once. The handle borrows the manager and
+/// gives access to it, so the batch can't outlive its changes. Dropping the handle without
+/// `commit` (e.g. on an early `?` return) ends the batch without saving; the changes stay in
+/// memory and are written by the next save.
+///
+/// ```
+/// # use tasks::TaskManager;
+/// let mut manager = TaskManager::new("tasks.json".into());
+/// manager.set_no_save(true);
+/// {
+///     let mut batch = manager.begin_batch();
+///     batch.add_task("Write the report").unwrap();
+///     batch.add_task("Send the report").unwrap();
+///     // dropped without commit
+/// }
+/// assert!(!manager.in_batch());
+/// let mut batch = manager.begin_batch();
+/// batch.complete_task(1).unwrap();
+/// batch.commit().unwrap();
+/// assert!(!manager.in_batch());
+/// ```
+#[must_use = "the batch has to be committed, otherwise nothing gets saved"]
+#[derive(Debug)]
+pub struct BatchHandle<'m> {
+    manager: &'m mut TaskManager,
+}
+
+impl BatchHandle<'_> {
+    /// ends the batch and saves the tasks of the manager
+    pub fn commit(self) -> Result<(), TaskError> {
+        self.manager.in_batch = false;
+        self.manager.save_tasks()
+    }
+}
+
+impl std::ops::Deref for BatchHandle<'_> {
+    type Target = TaskManager;
+
+    fn deref(&self) -> &TaskManager {
+        self.manager
+    }
+}
+
+impl std::ops::DerefMut for BatchHandle<'_> {
+    fn deref_mut(&mut self) -> &mut TaskManager {
+        self.manager
+    }
+}
+
+/* a batch that isn't committed must not keep suppressing every later save */
+impl Drop for BatchHandle<'_> {
+    fn drop(&mut self) {
+        self.manager.in_batch = false;
+    }
+}
+
+/// Number of tasks per priority, see `TaskManager::count_by_priority`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct PriorityCounts {
+    pub low: usize,
+    pub medium: usize,
+    pub high: usize,
+}
+
+impl PriorityCounts {
+    fn count<'t>(tasks: impl Iterator<Item = &'t Task>) -> Self {
+        let mut counts = PriorityCounts::default();
+        for task in tasks {
+            match task.priority() {
+                Priority::Low => counts.low += 1,
+                Priority::Medium => counts.medium += 1,
+                Priority::High => counts.high += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// Number of tasks with a tag or context, see `TaskManager::statistics_by_tag`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct TagStats {
+    pub total: usize,
+    pub completed: usize,
+    pub high_priority: usize,
+}
+
+impl TagStats {
+    /* the stats per word, for the words (tags or contexts) each task gives */
+    fn count<'t>(
+        tasks: impl Iterator<Item = &'t Task>,
+        words: impl Fn(&'t Task) -> Vec<&'t str>,
+    ) -> HashMap<String, TagStats> {
+        let mut stats: HashMap<String, TagStats> = HashMap::new();
+        for task in tasks {
+            for word in words(task) {
+                let entry = stats.entry(word.to_string()).or_default();
+                entry.total += 1;
+                entry.completed += task.get_completed() as usize;
+                entry.high_priority += (task.priority() == Priority::High) as usize;
+            }
+        }
+        stats
+    }
+}
+
+/// A copy of the task list, taken with `TaskManager::snapshot` and put back with
+/// `TaskManager::restore` - e.g. to undo a half-done operation that failed
+#[derive(Debug, Clone)]
+pub struct TaskSnapshot {
+    tasks: Vec<Task>,
+    next_available_id: usize,
+    /* how many changes the audit log had recorded, see TaskAuditLog::position */
+    audit_position: Option<usize>,
+}
+
+impl TaskSnapshot {
+    /// the tasks of the snapshot, in list order
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+}
+
+/// The task file the CLI uses if no workspace is chosen: ~/.tasks.json.
+/// None if the home directory can't be determined.
+pub fn platform_default_task_file() -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(".tasks.json"))
+}
+
+/// a manager for the default task file (see platform_default_task_file), or for .tasks.json in
+/// the current directory if there is no home directory
+impl Default for TaskManager {
+    fn default() -> Self {
+        TaskManager::new(platform_default_task_file().unwrap_or(PathBuf::from(".tasks.json")))
+    }
 }
 
 impl TaskManager {
@@ -19,32 +435,129 @@ impl TaskManager {
             tasks: Vec::new(),
             file_path,
             next_available_id: 1,
+            in_batch: false,
+            additional_files: Vec::new(),
+            original_ids: HashMap::new(),
+            audit_log: None,
+            config: Config::default(),
+            dry_run: false,
+            no_save: false,
+            verbose: false,
+            long_list: false,
+            hooks: IoHooks::default(),
+            error_reporter: Arc::new(NoopReporter),
         }
     }
 
+    /// same as new, for code that reads better with a named constructor
+    pub fn with_path(path: PathBuf) -> Self {
+        Self::new(path)
+    }
+
+    /// A manager that never touches the disk (see set_no_save): it starts empty and saving
+    /// does nothing. Meant for tests and for programs that only use the tasks in memory.
+    pub fn in_memory() -> Self {
+        let mut manager = Self::new(PathBuf::new());
+        manager.set_no_save(true);
+        manager
+    }
+
+    /// A manager for tasks built in memory, e.g. by another program. New tasks get IDs after
+    /// the highest given one. There is no task file, so save_tasks fails with
+    /// TaskError::NoFilePath until one is set with set_file_path.
+    ///
+    /// ```
+    /// # use tasks::{Priority, Task, TaskError, TaskManager};
+    /// let tasks = vec![Task::new_task("write report", 4, Priority::High)];
+    /// let mut manager = TaskManager::from_vec(tasks);
+    /// assert_eq!(manager.add_task("call Bob").unwrap(), 5);
+    /// assert!(matches!(manager.save_tasks(), Err(TaskError::NoFilePath)));
+    /// ```
+    pub fn from_vec(tasks: Vec<Task>) -> Self {
+        let mut manager = Self::new(PathBuf::new());
+        manager.next_available_id = tasks.iter().map(Task::get_id).max().unwrap_or(0) + 1;
+        manager.tasks = tasks;
+        manager
+    }
+
+    /// Loads from and saves to this file from now on; the tasks in memory are kept
+    pub fn set_file_path(&mut self, path: PathBuf) {
+        self.file_path = path;
+    }
+
+    /// Errors while loading or saving the task file are passed to this reporter as well as
+    /// returned; the default reporter ignores them
+    pub fn set_error_reporter(&mut self, reporter: Arc<dyn ErrorReporter>) {
+        self.error_reporter = reporter;
+    }
+
+    /// Sets a function that is called with all tasks every time before they are written
+    /// (not in dry-run mode or while a batch is open, when nothing is written)
+    pub fn set_pre_save_hook<F: Fn(&[Task]) + 'static>(&mut self, f: F) {
+        self.hooks.pre_save = Some(Box::new(f));
+    }
+
+    /// Sets a function that gets the tasks read by load_tasks to change them, before IDs are
+    /// checked; e.g. to decrypt descriptions. It isn't called if there is no task file.
+    pub fn set_post_load_hook<F: Fn(&mut Vec<Task>) + 'static>(&mut self, f: F) {
+        self.hooks.post_load = Some(Box::new(f));
+    }
+
     // Load tasks from given file if possible
     pub fn load_tasks(&mut self) -> Result<(), TaskError> {
-        if !self.file_path.exists() {
-            self.tasks = Vec::new();
-            self.next_available_id = 1;
-            return Ok(()); // No file, no problem - new vector;
-        }
+        let format = self.storage_format();
+        self.load_main_file(|path| Self::read_tasks(path, format))
+    }
 
-        let mut file = fs::File::open(&self.file_path)?;
-        /* read entire file content into a string */
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
+    /// Like load_tasks, but reads the main task file in the format of the serializer instead of
+    /// Config::format or the file extension
+    pub fn load_tasks_with<S: Serializer>(&mut self, s: S) -> Result<(), TaskError> {
+        self.load_main_file(|path| s.deserialize(&fs::read(path)?))
+    }
 
-        if contents.trim().is_empty() {
+    /* reads the main file with the given function, reporting errors */
+    fn load_main_file(
+        &mut self,
+        read: impl FnOnce(&Path) -> Result<Vec<Task>, TaskError>,
+    ) -> Result<(), TaskError> {
+        let result = self.read_main_file(read);
+        if let Err(e) = &result {
+            self.error_reporter.report_error(e);
+        }
+        result
+    }
+
+    fn read_main_file(
+        &mut self,
+        read: impl FnOnce(&Path) -> Result<Vec<Task>, TaskError>,
+    ) -> Result<(), TaskError> {
+        self.additional_files.clear();
+        self.original_ids.clear();
+        if self.no_save_active() || !self.file_path.exists() {
+            self.debug_log(|| {
+                format!(
+                    "load_tasks: {} not read, starting empty",
+                    self.file_path.display()
+                )
+            });
             self.tasks = Vec::new();
             self.next_available_id = 1;
-            return Ok(());
+            return Ok(()); // No file, no problem - new vector;
         }
 
-        /* Deserialze the JSON string into Vec<Task>
-         * the ? operator will propagate any serde_json::Error into io::Error
-         */
-        self.tasks = serde_json::from_str(&contents)?;
+        self.tasks = read(&self.file_path)?;
+        self.debug_log(|| {
+            let bytes = fs::metadata(&self.file_path).map(|m| m.len()).unwrap_or(0);
+            format!(
+                "load_tasks: {} ({} bytes, {} tasks)",
+                self.file_path.display(),
+                bytes,
+                self.tasks.len()
+            )
+        });
+        if let Some(hook) = &self.hooks.post_load {
+            hook(&mut self.tasks);
+        }
 
         /* ID Renumberation logic: id was introduced in 0.3.0 - to be compatible with < 0.3.0,
          * the default value for ID is 0. Real ID is 1-based, so every id that euqals zero, needs
@@ -68,148 +581,3071 @@ impl TaskManager {
         Ok(())
     }
 
-    // Save tasks to given file
-    pub fn save_tasks(&self) -> Result<(), TaskError> {
-        /* serialize the Vec<Task> into a pretty-printed JSON string */
-        let json_string = serde_json::to_string_pretty(&self.tasks)?;
-        /* write the JSOn string to the file, overwriting it */
-        let mut file = fs::File::create(&self.file_path)?; // create ovverrides file if they exist
-        file.write_all(json_string.as_bytes())?;
+    /// Checks the task list for problems (IDs that are 0 or used twice, empty descriptions) and
+    /// reports all of them at once as TaskError::ValidationErrors
+    pub fn validate(&self) -> Result<(), TaskError> {
+        let problems = task_list_violations(&self.tasks);
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(TaskError::ValidationErrors(problems))
+        }
+    }
+
+    /// remembers the current tasks, see restore
+    pub fn snapshot(&self) -> TaskSnapshot {
+        TaskSnapshot {
+            tasks: self.tasks.clone(),
+            next_available_id: self.next_available_id,
+            audit_position: self.audit_log.as_ref().map(TaskAuditLog::position),
+        }
+    }
+
+    /// Replaces the task list with the one of the snapshot. Changes recorded for the audit log
+    /// since the snapshot and not saved yet are dropped, they were rolled back.
+    pub fn restore(&mut self, snapshot: TaskSnapshot) {
+        self.tasks = snapshot.tasks;
+        self.next_available_id = snapshot.next_available_id;
+        if let (Some(log), Some(position)) = (&mut self.audit_log, snapshot.audit_position) {
+            log.discard_from(position);
+        }
+    }
+
+    /// Runs several changes as one: if the closure returns an error, or saving afterwards
+    /// fails, the task list is put back as it was before and the error is returned. Otherwise
+    /// the tasks are saved once at the end (the closure's own saves are suppressed) and its
+    /// result is returned.
+    ///
+    /// ```
+    /// use tasks::{TaskError, TaskManager};
+    ///
+    /// let mut manager = TaskManager::from_vec(Vec::new());
+    /// manager.set_no_save(true);
+    /// let result = manager.transaction(|m| {
+    ///     m.add_task("Write the report")?;
+    ///     m.complete_task(42)
+    /// });
+    /// assert!(matches!(result, Err(TaskError::TaskNotFound(42))));
+    /// assert_eq!(manager.iter().count(), 0);
+    /// ```
+    pub fn transaction<F, R>(&mut self, f: F) -> Result<R, TaskError>
+    where
+        F: FnOnce(&mut TaskManager) -> Result<R, TaskError>,
+    {
+        let snapshot = self.snapshot();
+        let was_in_batch = self.in_batch;
+        self.in_batch = true;
+        let result = f(self);
+        self.in_batch = was_in_batch;
+        match result.and_then(|r| self.save_tasks().map(|_| r)) {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                /* this drops the changes for the audit log as well */
+                self.restore(snapshot);
+                Err(e)
+            }
+        }
+    }
+
+    /// Moves a task into another task file, e.g. the one of another workspace: the task is added
+    /// there with the next free ID of that file, both files are saved, and the new ID is
+    /// returned. If saving this manager's file fails, the target file is written back as it was,
+    /// so the task is never lost or in both files.
+    pub fn move_task_to_file(&mut self, id: usize, target_path: &Path) -> Result<usize, TaskError> {
+        let task = self.at(id).ok_or(TaskError::TaskNotFound(id))?.clone();
+        if target_path == self.file_path {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "task #{} is already in {}",
+                id,
+                target_path.display()
+            )));
+        }
+        let mut target = TaskManager::new(target_path.to_path_buf());
+        target.set_config(self.config.clone());
+        target.set_dry_run(self.dry_run);
+        target.set_no_save(self.no_save);
+        target.load_tasks()?;
+        let target_before = target.snapshot();
+        let new_id = target.import_tasks(vec![task])[0];
+        target.save_tasks()?;
+
+        let before = self.snapshot();
+        self.delete_task(id)?;
+        if let Err(e) = self.save_tasks() {
+            self.restore(before);
+            target.restore(target_before);
+            /* the move failed anyway, report that and not a second error */
+            let _ = target.save_tasks();
+            return Err(e);
+        }
+        Ok(new_id)
+    }
+
+    /// Replaces all tasks at once, e.g. with the list of an external system, keeping their IDs.
+    /// The list is checked first: IDs have to be unique and not 0, descriptions must not be
+    /// empty; otherwise TaskError::Corrupt is returned and nothing changes. With auto_backup
+    /// configured, the task file is backed up before. Tasks of additional files are dropped as
+    /// well - all new tasks belong to the main file. Saving is still up to the caller.
+    pub fn replace_tasks(&mut self, new_tasks: Vec<Task>) -> Result<(), TaskError> {
+        let problems = task_list_violations(&new_tasks);
+        if !problems.is_empty() {
+            return Err(TaskError::Corrupt(problems.join(", ")));
+        }
+
+        if self.config.auto_backup && self.file_path.exists() {
+            self.backup(&self.backup_dir())?;
+        }
+        self.tasks = new_tasks;
+        for task in &mut self.tasks {
+            task.set_source_file(None);
+        }
+        self.additional_files.clear();
+        self.original_ids.clear();
+        self.next_available_id = self.tasks.iter().map(Task::get_id).max().unwrap_or(0) + 1;
         Ok(())
     }
 
-    /* creates a new task and adds it to the vector */
-    pub fn add_task<S: Into<String>>(&mut self, description: S) -> usize {
-        let new_id = self.next_available_id;
-        let new_task = Task::new_task(description, self.next_available_id, crate::Priority::Medium);
-        self.next_available_id += 1;
-        self.tasks.push(new_task);
-        new_id // return ID of newly created task
+    /// Loads the tasks of another file and adds them to the list. Tasks whose ID is already taken
+    /// get a new one for this session; saving writes them back to their file with their
+    /// original IDs. Returns the number of loaded tasks. The main task file and files that were
+    /// already loaded can't be loaded (again), their tasks would be duplicated.
+    pub fn load_additional(&mut self, path: &Path) -> Result<usize, TaskError> {
+        let canonical = fs::canonicalize(path)?;
+        /* the main file might not exist yet, then it can't be the same file */
+        if fs::canonicalize(&self.file_path).is_ok_and(|main| main == canonical) {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "{} is the main task file, its tasks are already loaded",
+                path.display()
+            )));
+        }
+        if self
+            .additional_files
+            .iter()
+            .any(|loaded| fs::canonicalize(loaded).is_ok_and(|loaded| loaded == canonical))
+        {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "{} is already loaded",
+                path.display()
+            )));
+        }
+        let loaded = Self::read_tasks(path, StorageFormat::from_path(path))?;
+        let source = path.to_string_lossy().to_string();
+        let count = loaded.len();
+        let first = self.tasks.len();
+        /* the IDs the tasks of the file have for this session, by their ID in the file */
+        let mut new_ids: HashMap<usize, usize> = HashMap::new();
+        for mut task in loaded {
+            let original_id = task.get_id();
+            if original_id == 0 || self.find_id(original_id).is_some() {
+                let new_id = self.next_available_id;
+                self.original_ids.insert(new_id, original_id);
+                task.set_id(new_id);
+            }
+            new_ids.entry(original_id).or_insert(task.get_id());
+            self.next_available_id = self.next_available_id.max(task.get_id() + 1);
+            task.set_source_file(Some(source.clone()));
+            self.tasks.push(task);
+        }
+        /* subtasks follow their parent to its new ID, so they don't end up under a task of
+         * another file */
+        for task in &mut self.tasks[first..] {
+            if let Some(parent_id) = task.get_parent_id() {
+                task.renumber_parent(new_ids.get(&parent_id).copied());
+            }
+        }
+        self.additional_files.push(path.to_path_buf());
+        Ok(count)
     }
 
-    /* show tasks */
-    pub fn list_tasks(&self) {
-        if self.tasks.is_empty() {
-            println!("{}", "No tasks, all done!".green());
-        } else {
-            /* calculate how many spaces should be used for the numbers. */
-            let num_width = self.next_available_id / 10 + 2;
-            println!("{}", "Your tasks:".bold().underline());
-            for task in &self.tasks {
-                let index_str = format!("{1:>0$}", num_width, task.get_id()).cyan().bold();
-                let status_str = task.get_status();
-                let colored_status = if task.get_completed() {
-                    status_str.green().bold()
-                } else {
-                    status_str.magenta()
-                };
-                let desc = task.get_description();
-                let colored_desc = if task.get_completed() {
-                    desc.dimmed()
-                } else {
-                    desc.normal()
-                };
-                println!(
-                    "{}: {} {} {}",
-                    index_str,
-                    task.get_priority(),
-                    colored_status,
-                    colored_desc
-                );
+    // Save tasks to given file; does nothing while a batch is open.
+    // Tasks loaded with load_additional are written back to their own file.
+    // Changes recorded for the audit log are appended to it after the tasks were written.
+    // A JSON file is written compact if Config::json_pretty is off.
+    pub fn save_tasks(&mut self) -> Result<(), TaskError> {
+        match self.storage_format() {
+            StorageFormat::Json if !self.config.json_pretty => {
+                self.save_main_file_as(&JsonSerializer::compact())
             }
+            format => self.save_main_file_as(serializer_for(format)),
         }
     }
 
-    pub fn complete_task(&mut self, id: usize) -> Result<String, TaskError> {
-        if let Some(task) = self.at_mut(id) {
-            task.mark_completed();
-            Ok(format!("Completed Task: {}", task.get_description()))
-        } else {
-            Err(TaskError::TaskNotFound(id))
+    /// Like save_tasks, but writes the main task file in the format of the serializer.
+    /// Additional files keep the format of their extension.
+    pub fn save_tasks_with<S: Serializer>(&mut self, s: S) -> Result<(), TaskError> {
+        self.save_main_file_as(&s)
+    }
+
+    /* writes all files, the main file with the given serializer, reporting errors */
+    fn save_main_file_as(&mut self, serializer: &dyn Serializer) -> Result<(), TaskError> {
+        if self.in_batch || self.dry_run || self.no_save_active() {
+            return Ok(());
+        }
+        let result = self.write_files(serializer);
+        if let Err(e) = &result {
+            self.error_reporter.report_error(e);
         }
+        result
     }
 
-    pub fn prioritize_task(&mut self, id: usize) -> Result<String, TaskError> {
-        if let Some(task) = self.at_mut(id) {
-            task.prio_up();
-            Ok(format!("Prioritized Task: {}", task.get_description()))
-        } else {
-            Err(TaskError::TaskNotFound(id))
+    /* the backup, the task files and the audit log */
+    fn write_files(&mut self, serializer: &dyn Serializer) -> Result<(), TaskError> {
+        /* from_vec starts without a file */
+        if self.file_path.as_os_str().is_empty() {
+            return Err(TaskError::NoFilePath);
         }
+        /* there is nothing to back up before the file was written the first time */
+        if self.config.auto_backup && self.file_path.exists() {
+            self.backup(&self.backup_dir())?;
+        }
+        if let Some(hook) = &self.hooks.pre_save {
+            hook(&self.tasks);
+        }
+        let data = serializer.serialize(&self.main_file_tasks())?;
+        fs::write(&self.file_path, &data)?;
+        self.debug_log(|| {
+            format!(
+                "save_tasks: {} ({} bytes)",
+                self.file_path.display(),
+                data.len()
+            )
+        });
+
+        for path in &self.additional_files {
+            let source = path.to_string_lossy();
+            let file_tasks: Vec<Task> = self
+                .tasks
+                .iter()
+                .filter(|t| t.get_source_file() == Some(source.as_ref()))
+                .map(|t| {
+                    let mut task = t.clone();
+                    if let Some(original_id) = self.original_ids.get(&t.get_id()) {
+                        task.set_id(*original_id);
+                    }
+                    if let Some(original_parent_id) =
+                        t.get_parent_id().and_then(|p| self.original_ids.get(&p))
+                    {
+                        task.renumber_parent(Some(*original_parent_id));
+                    }
+                    task
+                })
+                .collect();
+            Self::write_tasks(path, &file_tasks, StorageFormat::from_path(path))?;
+        }
+        if let Some(log) = &mut self.audit_log {
+            log.flush()?;
+        }
+        Ok(())
     }
 
-    pub fn deprioritize_task(&mut self, id: usize) -> Result<String, TaskError> {
-        if let Some(task) = self.at_mut(id) {
-            task.prio_down();
-            Ok(format!("Deprioritized Task: {}", task.get_description()))
-        } else {
-            Err(TaskError::TaskNotFound(id))
+    /// In dry-run mode, save_tasks (and with it backups and the audit log) doesn't write anything
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Detaches the manager from the file system, meant for tests of code using a TaskManager:
+    /// load_tasks starts with an empty list instead of reading the file and save_tasks doesn't
+    /// write anything. Setting the environment variable TASKMASTER_NO_SAVE has the same effect
+    /// for all managers (and can't be switched off with set_no_save(false)).
+    pub fn set_no_save(&mut self, no_save: bool) {
+        self.no_save = no_save;
+    }
+
+    /// In the long list format, every task of the list also shows how long it takes to read its
+    /// description and notes
+    pub fn set_long_list(&mut self, long_list: bool) {
+        self.long_list = long_list;
+    }
+
+    /// In verbose mode, changes of tasks are logged to stderr with the task before and after,
+    /// and loading and saving with the file and its size
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /* the state of a task for the verbose log; None if not verbose */
+    fn debug_state(&self, id: usize) -> Option<String> {
+        if !self.verbose {
+            return None;
         }
+        Some(match self.at(id) {
+            Some(task) => format!(
+                "Task{{id:{}, desc:{:?}, completed:{}, priority:{}}}",
+                task.get_id(),
+                task.get_description(),
+                task.get_completed(),
+                task.priority().name()
+            ),
+            None => "(none)".to_string(),
+        })
     }
 
-    pub fn change_priority(&mut self, id: usize, prioritize: bool) -> Result<String, TaskError> {
-        if prioritize {
-            self.prioritize_task(id)
-        } else {
-            self.deprioritize_task(id)
+    /* logs an operation in verbose mode, with the state before (from debug_state) and now */
+    fn debug_change(&self, operation: &str, id: usize, before: Option<String>) {
+        if let (Some(before), Some(after)) = (before, self.debug_state(id)) {
+            eprintln!("[DEBUG] {}({}): {} → {}", operation, id, before, after);
         }
     }
 
-    /// Deletes all tasks that are marked as completed.
-    /// Returns the number of tasks cleared.
-    pub fn clear_completed_tasks(&mut self) -> usize {
-        let initial_len = self.tasks.len();
-        self.tasks.retain(|task| !task.get_completed());
+    /* logs a message in verbose mode */
+    fn debug_log(&self, message: impl FnOnce() -> String) {
+        if self.verbose {
+            eprintln!("[DEBUG] {}", message());
+        }
+    }
 
-        initial_len - self.tasks.len()
+    /* set_no_save or TASKMASTER_NO_SAVE */
+    fn no_save_active(&self) -> bool {
+        self.no_save || std::env::var_os(NO_SAVE_ENV).is_some()
     }
 
-    /// Changes the description of a task with a given ID
-    pub fn change_description<S: Into<String>>(
-        &mut self,
-        id: usize,
-        new_description: S,
-    ) -> Result<String, TaskError> {
-        if let Some(task) = self.at_mut(id) {
-            let old_desc: String = task.get_description().to_string();
-            task.set_description(new_description);
-            Ok(format!(
-                "Description of task {} changed.\n\tOld: \"{}\"\n\tNew: \"{}\"",
-                id,
-                old_desc,
-                task.get_description()
-            ))
-        } else {
-            Err(TaskError::TaskNotFound(id))
+    /// Uses the given configuration from now on
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    pub fn get_config(&self) -> &Config {
+        &self.config
+    }
+
+    /// the directory for backups: Config::backup_dir, or the directory of the task file
+    pub fn backup_dir(&self) -> PathBuf {
+        match &self.config.backup_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => self
+                .file_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
         }
     }
 
-    /// Deletes the task with the given ID
-    pub fn delete_task(&mut self, id: usize) -> Result<String, TaskError> {
-        if let Some(idx) = self.find_id(id) {
-            let old_task = self.tasks.remove(idx);
-            Ok(format!(
-                "Deleted task ID {}\n\t'{}'",
-                id,
-                old_task.get_description()
-            ))
-        } else {
-            Err(TaskError::TaskNotFound(id))
+    /// Copies the task file into the backup directory, named after the task file plus a
+    /// timestamp, e.g. `.tasks_2024-11-03T15:04:05.json`. Afterwards only the newest
+    /// Config::backup_keep backups are kept. Returns the path of the new backup.
+    pub fn backup(&self, backup_dir: &Path) -> Result<PathBuf, TaskError> {
+        let (stem, extension) = self.backup_name_parts();
+        let timestamp = Local::now().format(BACKUP_TIMESTAMP_FORMAT);
+        fs::create_dir_all(backup_dir)?;
+        let backup_path = backup_dir.join(format!("{}_{}.{}", stem, timestamp, extension));
+        /* a second save within the same second keeps the backup from before the first one */
+        if !backup_path.exists() {
+            fs::copy(&self.file_path, &backup_path)?;
         }
+
+        let backups = self.list_backups(backup_dir)?;
+        let surplus = backups.len().saturating_sub(self.config.backup_keep.max(1));
+        for old_backup in &backups[..surplus] {
+            fs::remove_file(old_backup)?;
+        }
+        Ok(backup_path)
     }
 
-    /// find Task with given id, if it exits, and returns index
-    pub fn find_id(&self, id: usize) -> Option<usize> {
-        self.tasks.iter().position(|t| t.get_id() == id)
+    /// returns all backups of the task file in the backup directory, oldest first
+    pub fn list_backups(&self, backup_dir: &Path) -> Result<Vec<PathBuf>, TaskError> {
+        if !backup_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let (stem, extension) = self.backup_name_parts();
+        let prefix = format!("{}_", stem);
+        let suffix = format!(".{}", extension);
+        /* only names with a timestamp between prefix and suffix: the task file of workspace
+         * "home_1" also starts with "home_" */
+        let mut backups: Vec<(NaiveDateTime, PathBuf)> = fs::read_dir(backup_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter_map(|path| {
+                let name = path.file_name()?.to_string_lossy().to_string();
+                let timestamp = name.strip_prefix(&prefix)?.strip_suffix(&suffix)?;
+                let time =
+                    NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT).ok()?;
+                Some((time, path))
+            })
+            .collect();
+        backups.sort();
+        Ok(backups.into_iter().map(|(_, path)| path).collect())
     }
 
-    /// return Task with given id, if it exists
-    pub fn at(&self, id: usize) -> Option<&Task> {
-        self.tasks.iter().find(|t| t.get_id() == id)
+    /// Replaces the task file with the backup with the given timestamp and reloads the tasks
+    pub fn restore_backup(
+        &mut self,
+        backup_dir: &Path,
+        timestamp: &str,
+    ) -> Result<PathBuf, TaskError> {
+        if NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT).is_err() {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "'{}' is not a backup timestamp like 2024-11-03T15:04:05",
+                timestamp
+            )));
+        }
+        let (stem, extension) = self.backup_name_parts();
+        let backup_path = backup_dir.join(format!("{}_{}.{}", stem, timestamp, extension));
+        if !backup_path.exists() {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "there is no backup from {}",
+                timestamp
+            )));
+        }
+        fs::copy(&backup_path, &self.file_path)?;
+        self.load_tasks()?;
+        Ok(backup_path)
     }
 
-    /// return mutable Task with given id, if it exists
-    pub fn at_mut(&mut self, id: usize) -> Option<&mut Task> {
-        self.tasks.iter_mut().find(|t| t.get_id() == id)
+    /* file stem and extension of the task file, used to name its backups */
+    fn backup_name_parts(&self) -> (String, String) {
+        let stem = self
+            .file_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "tasks".to_string());
+        let extension = self
+            .file_path
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_else(|| "json".to_string());
+        (stem, extension)
+    }
+
+    /// Enables (or with None disables) recording all changes in an audit log
+    pub fn set_audit_log(&mut self, audit_log: Option<TaskAuditLog>) {
+        self.audit_log = audit_log;
+    }
+
+    /// returns the audit log, if one is set
+    pub fn get_audit_log(&self) -> Option<&TaskAuditLog> {
+        self.audit_log.as_ref()
+    }
+
+    /* records a change in the audit log, if there is one */
+    fn audit<S: Into<String>>(&mut self, operation: AuditOperation, task_id: usize, detail: S) {
+        if let Some(log) = &mut self.audit_log {
+            log.record(operation, task_id, detail);
+        }
+    }
+
+    /* records the change of a task's priority, given its old priority */
+    fn audit_priority(&mut self, id: usize, old_priority: Priority) {
+        if let Some(new_priority) = self.at(id).map(|t| t.priority()) {
+            let detail = format!("{} → {}", old_priority.name(), new_priority.name());
+            self.audit(AuditOperation::ChangePriority, id, detail);
+        }
+    }
+
+    /// Creates a self-contained HTML page (inline CSS and JavaScript, no external resources) with
+    /// all tasks in a table: colored priority badges, completed tasks struck through, columns
+    /// sortable by clicking the header and a search box
+    pub fn to_html(&self) -> String {
+        let mut rows = String::new();
+        for task in &self.tasks {
+            let prio = task.priority();
+            let due = task.get_due().map(|d| d.to_string()).unwrap_or_default();
+            /* data-sort values: High > Medium > Low, tasks without due date last */
+            rows.push_str(&format!(
+                concat!(
+                    "<tr class=\"{}\">",
+                    "<td>{}</td>",
+                    "<td data-sort=\"{}\"><span class=\"badge prio-{}\">{}</span></td>",
+                    "<td>{}</td>",
+                    "<td class=\"desc\">{}</td>",
+                    "<td data-sort=\"{}\">{}</td>",
+                    "<td>{}</td>",
+                    "</tr>\n"
+                ),
+                if task.get_completed() {
+                    "completed"
+                } else {
+                    "pending"
+                },
+                task.get_id(),
+                prio as u8,
+                prio.name().to_lowercase(),
+                prio.name(),
+                if task.get_completed() {
+                    "done"
+                } else {
+                    "pending"
+                },
+                escape_html(task.get_description()),
+                if due.is_empty() { "9999-12-31" } else { &due },
+                due,
+                escape_html(task.get_assignee().unwrap_or("")),
+            ));
+        }
+        format!(
+            concat!(
+                "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n",
+                "<title>Tasks</title>\n<style>\n{}\n</style>\n</head>\n<body>\n",
+                "<h1>Tasks</h1>\n",
+                "<input id=\"search\" type=\"search\" placeholder=\"Search...\" ",
+                "oninput=\"filterRows()\">\n",
+                "<table id=\"tasks\">\n<thead><tr>",
+                "<th onclick=\"sortBy(0)\">ID</th><th onclick=\"sortBy(1)\">Priority</th>",
+                "<th onclick=\"sortBy(2)\">Status</th><th onclick=\"sortBy(3)\">Description</th>",
+                "<th onclick=\"sortBy(4)\">Due</th><th onclick=\"sortBy(5)\">Assignee</th>",
+                "</tr></thead>\n<tbody>\n{}</tbody>\n</table>\n",
+                "<footer>{} tasks, generated {}</footer>\n",
+                "<script>\n{}\n</script>\n</body>\n</html>\n"
+            ),
+            HTML_STYLE,
+            rows,
+            self.tasks.len(),
+            Local::now().format("%Y-%m-%d %H:%M"),
+            HTML_SCRIPT
+        )
+    }
+
+    /// Writes the tasks of the main task file (not the ones from load_additional) as JSON
+    pub fn to_json_writer<W: Write>(&self, w: W) -> Result<(), TaskError> {
+        let main_tasks: Vec<&Task> = self
+            .tasks
+            .iter()
+            .filter(|t| t.get_source_file().is_none())
+            .collect();
+        Self::write_json(w, &main_tasks)
+    }
+
+    /// Reads tasks in the JSON format of the task file. Empty input gives no tasks.
+    /// IDs are taken as they are; tasks from files before 0.3.0 have the ID 0.
+    pub fn from_json_reader<R: Read>(mut r: R) -> Result<Vec<Task>, TaskError> {
+        /* read everything first, so empty input can be told apart from invalid JSON */
+        let mut contents = String::new();
+        r.read_to_string(&mut contents)?;
+        if contents.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// The tasks of the main task file as a YAML document (starting with `---`)
+    pub fn to_yaml(&self) -> Result<String, TaskError> {
+        let yaml = YamlSerializer.serialize(&self.main_file_tasks())?;
+        Ok(String::from_utf8_lossy(&yaml).into_owned())
+    }
+
+    /// Reads tasks from a YAML document as written by to_yaml. Empty input gives no tasks.
+    pub fn from_yaml(s: &str) -> Result<Vec<Task>, TaskError> {
+        YamlSerializer.deserialize(s.as_bytes())
+    }
+
+    /// The tasks of the main task file as plain text, one line per task, e.g. for mobile apps or
+    /// emails: `[x] #5 (High) Buy groceries @home due:2024-11-10 #shopping`. See
+    /// interop::plaintext::to_plain_text.
+    pub fn to_plain_text(&self) -> String {
+        plaintext::to_plain_text(&self.main_file_tasks())
+    }
+
+    /// Reads tasks from plain text as written by to_plain_text. Empty input gives no tasks.
+    pub fn from_plain_text(s: &str) -> Result<Vec<Task>, TaskError> {
+        plaintext::import_plain_text(s)
+    }
+
+    /// Writes the tasks of the main task file (not the ones from load_additional) in the format
+    /// of the serializer, e.g. `manager.export_with(file, CsvSerializer)`
+    pub fn export_with<S: Serializer>(
+        &self,
+        mut writer: impl Write,
+        s: S,
+    ) -> Result<(), TaskError> {
+        writer.write_all(&s.serialize(&self.main_file_tasks())?)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads tasks in the format of the serializer and adds them with new IDs, see import_tasks.
+    /// Returns the new IDs.
+    pub fn import_with<S: Serializer>(
+        &mut self,
+        mut reader: impl Read,
+        s: S,
+    ) -> Result<Vec<usize>, TaskError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Ok(self.import_tasks(s.deserialize(&data)?))
+    }
+
+    /* copies of the tasks that belong to the main task file */
+    fn main_file_tasks(&self) -> Vec<Task> {
+        self.tasks
+            .iter()
+            .filter(|t| t.get_source_file().is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// format of the main task file: Config::format, or guessed from the file extension
+    pub fn storage_format(&self) -> StorageFormat {
+        self.config
+            .format
+            .unwrap_or_else(|| StorageFormat::from_path(&self.file_path))
+    }
+
+    /// Writes the tasks of the main task file in the given format to a file next to it, with the
+    /// extension of the format (e.g. .tasks.json -> .tasks.toml). The old file is renamed to
+    /// `<name>.bak` and the manager uses the new file from now on. Returns the new path.
+    pub fn convert_to(&mut self, format: StorageFormat) -> Result<PathBuf, TaskError> {
+        let new_path = self.file_path.with_extension(format.extension());
+        if new_path == self.file_path && format == self.storage_format() {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "{} is already stored as {}",
+                self.file_path.display(),
+                format.extension()
+            )));
+        }
+        Self::write_tasks(&new_path, &self.main_file_tasks(), format)?;
+        if new_path != self.file_path && self.file_path.exists() {
+            let mut old_name = self.file_path.clone().into_os_string();
+            old_name.push(".bak");
+            fs::rename(&self.file_path, old_name)?;
+        }
+        self.file_path = new_path.clone();
+        self.config.format = Some(format);
+        Ok(new_path)
+    }
+
+    /// Looks for problems with the task file that would make loading or saving fail: missing,
+    /// unreadable or damaged file, low disk space, file of another user. An empty list means all
+    /// is fine.
+    pub fn health_check(&self) -> Vec<HealthIssue> {
+        let mut issues = Vec::new();
+        let directory = match self.file_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        if let Some(free_bytes) = health::free_disk_space(directory)
+            && free_bytes < health::LOW_DISK_SPACE_BYTES
+        {
+            issues.push(HealthIssue::DiskSpaceLow { free_bytes });
+        }
+        let metadata = match fs::metadata(&self.file_path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                issues.push(HealthIssue::FileMissing);
+                return issues;
+            }
+            Err(e) => {
+                issues.push(HealthIssue::FileUnreadable(e));
+                return issues;
+            }
+        };
+        if health::owned_by_other_user(&metadata) {
+            issues.push(HealthIssue::FileOwnedByOtherUser);
+        }
+        /* reading as a string also catches directories and content that isn't UTF-8 */
+        if let Err(e) = fs::read_to_string(&self.file_path) {
+            issues.push(HealthIssue::FileUnreadable(e));
+            return issues;
+        }
+        if let Err(TaskError::Json(_) | TaskError::TomlDe(_) | TaskError::Yaml(_)) =
+            Self::read_tasks(&self.file_path, self.storage_format())
+        {
+            issues.push(HealthIssue::FileNotJson);
+        }
+        issues
+    }
+
+    /* reads a task file in the given format; an empty file has no tasks */
+    fn read_tasks(path: &Path, format: StorageFormat) -> Result<Vec<Task>, TaskError> {
+        match format {
+            /* JSON and YAML files are told apart by their content, so a file that was written
+             * in the other format (or renamed) still loads */
+            StorageFormat::Json | StorageFormat::Yaml => {
+                let contents = fs::read_to_string(path)?;
+                let start = contents.trim_start();
+                if start.starts_with("---") {
+                    Self::from_yaml(&contents)
+                } else if start.starts_with('[') || start.starts_with('{') {
+                    Self::from_json_reader(contents.as_bytes())
+                } else if format == StorageFormat::Yaml {
+                    Self::from_yaml(&contents)
+                } else {
+                    Self::from_json_reader(contents.as_bytes())
+                }
+            }
+            StorageFormat::Toml => TomlSerializer.deserialize(&fs::read(path)?),
+        }
+    }
+
+    /// writes the given tasks in the given format to the given file, overwriting it
+    fn write_tasks(path: &Path, tasks: &[Task], format: StorageFormat) -> Result<(), TaskError> {
+        fs::write(path, serializer_for(format).serialize(tasks)?)?;
+        Ok(())
+    }
+
+    /* serializes the tasks into pretty-printed JSON */
+    fn write_json<W: Write, T: serde::Serialize>(mut w: W, tasks: &[T]) -> Result<(), TaskError> {
+        serde_json::to_writer_pretty(&mut w, tasks)?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Starts a batch: saving is suppressed until the returned handle is committed or dropped
+    pub fn begin_batch(&mut self) -> BatchHandle<'_> {
+        self.in_batch = true;
+        BatchHandle { manager: self }
+    }
+
+    /// true while a batch or transaction is open, i.e. `save_tasks` doesn't write
+    pub fn in_batch(&self) -> bool {
+        self.in_batch
+    }
+
+    /// Applies all operations in order and writes the file once at the end.
+    /// Returns the result of every single operation; a failing operation doesn't stop the
+    /// following ones. If the final save fails, all operations are undone and the error is
+    /// appended as an additional result.
+    pub fn apply_operations(&mut self, ops: Vec<TaskOperation>) -> Vec<Result<String, TaskError>> {
+        let mut results: Vec<Result<String, TaskError>> = Vec::new();
+        let saved = self.transaction(|manager| {
+            results.extend(ops.into_iter().map(|op| manager.apply_operation(op)));
+            Ok(())
+        });
+        if let Err(e) = saved {
+            results.push(Err(e));
+        }
+        results
+    }
+
+    fn apply_operation(&mut self, op: TaskOperation) -> Result<String, TaskError> {
+        match op {
+            TaskOperation::Add(description) => {
+                if description.trim().is_empty() {
+                    return Err(TaskError::Empty("Description".to_string()));
+                }
+                let id = self.add_task(description)?;
+                Ok(format!("Added Task #{}", id))
+            }
+            TaskOperation::Complete(id) => self.complete_task(id),
+            TaskOperation::Delete(id) => self.delete_task(id),
+            TaskOperation::SetPriority(id, priority) => self.set_priority(id, priority),
+            TaskOperation::ChangeDescription(id, description) => {
+                self.change_description(id, description)
+            }
+        }
+    }
+
+    /// Runs one command line in the grammar of interactive mode, e.g. "add Buy milk",
+    /// "complete 3" or "prio 3 high" (see COMMAND_NAMES), and returns its message. A missing
+    /// argument gives TaskError::Empty. Nothing is saved.
+    pub fn apply_command_str(&mut self, input: &str) -> Result<String, TaskError> {
+        let parts: Vec<&str> = input.split_whitespace().collect();
+        let Some((command, args)) = parts.split_first() else {
+            return Err(TaskError::Empty("Command".to_string()));
+        };
+        let id_arg = |index: usize| -> Result<usize, TaskError> {
+            let arg = args
+                .get(index)
+                .ok_or_else(|| TaskError::Empty("ID".to_string()))?;
+            arg.parse().map_err(|_| {
+                TaskError::ArgumentMismatch(format!(
+                    "wrong argument: '{}' is not a valid task ID.",
+                    arg
+                ))
+            })
+        };
+        match command.to_lowercase().as_str() {
+            "a" | "add" => {
+                if args.is_empty() {
+                    return Err(TaskError::Empty("Description".to_string()));
+                }
+                let id = self.add_task(args.join(" "))?;
+                Ok(format!("Added Task #{}", id))
+            }
+            "c" | "complete" if args.contains(&"--force") => {
+                self.complete_task_with_subtasks(id_arg(0)?)
+            }
+            "c" | "complete" => self.complete_task(id_arg(0)?),
+            "d" | "delete" => self.delete_task(id_arg(0)?),
+            "+" | "up" => self.change_priority(id_arg(0)?, true),
+            "-" | "down" => self.change_priority(id_arg(0)?, false),
+            "prio" | "priority" => {
+                let id = id_arg(0)?;
+                let name = args
+                    .get(1)
+                    .ok_or_else(|| TaskError::Empty("Priority".to_string()))?;
+                self.set_priority_by_name(id, name)
+            }
+            "ch" | "change" => {
+                let id = id_arg(0)?;
+                if args.len() < 2 {
+                    return Err(TaskError::Empty("Description".to_string()));
+                }
+                self.change_description(id, args[1..].join(" "))
+            }
+            "pin" => self.pin_task(id_arg(0)?),
+            "unpin" => self.unpin_task(id_arg(0)?),
+            "swap" => self.swap_positions(id_arg(0)?, id_arg(1)?),
+            "clr" | "clear" => Ok(format!(
+                "Cleared {} completed tasks",
+                self.clear_completed_tasks()
+            )),
+            other => Err(TaskError::ArgumentMismatch(format!(
+                "unknown command '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Runs every line of the reader with apply_command_str and returns the result of each. Empty
+    /// lines and lines starting with '#' are skipped; a failing command doesn't stop the following
+    /// ones. Nothing is saved.
+    pub fn apply_from_reader<R: BufRead>(
+        &mut self,
+        reader: R,
+    ) -> Result<Vec<LineResult>, TaskError> {
+        let mut results = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            results.push((index + 1, self.apply_command_str(line)));
+        }
+        Ok(results)
+    }
+
+    /// apply_from_reader for the lines piped into stdin
+    pub fn apply_from_stdin(&mut self) -> Result<Vec<LineResult>, TaskError> {
+        self.apply_from_reader(std::io::stdin().lock())
+    }
+
+    /* creates a new task and adds it to the vector */
+    pub fn add_task<S: Into<String>>(&mut self, description: S) -> Result<usize, TaskError> {
+        let description = self
+            .check_description(description.into())
+            .map_err(TaskError::ValidationErrors)?;
+        let new_id = self.next_available_id;
+        let new_task = Task::new_task(description, self.next_available_id, Priority::Medium);
+        self.next_available_id += 1;
+        self.audit(AuditOperation::Add, new_id, new_task.get_description());
+        let before = self.verbose.then(|| "(none)".to_string());
+        self.tasks.push(new_task);
+        self.debug_change("add_task", new_id, before);
+        Ok(new_id) // return ID of newly created task
+    }
+
+    /// Adds tasks read from another file (e.g. with from_yaml) to the main task file. They get
+    /// new IDs, everything else is kept; subtasks keep their parent if it is imported as well.
+    /// Returns the new IDs in the order of the tasks.
+    pub fn import_tasks(&mut self, tasks: Vec<Task>) -> Vec<usize> {
+        let new_ids: HashMap<usize, usize> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.get_id(), self.next_available_id + i))
+            .collect();
+        let mut ids = Vec::with_capacity(tasks.len());
+        for mut task in tasks {
+            let new_id = self.next_available_id;
+            self.next_available_id += 1;
+            task.set_id(new_id);
+            task.set_source_file(None);
+            if let Some(parent_id) = task.get_parent_id() {
+                task.set_parent_id(new_ids.get(&parent_id).copied());
+            }
+            self.audit(AuditOperation::Add, new_id, task.get_description());
+            self.tasks.push(task);
+            ids.push(new_id);
+        }
+        ids
+    }
+
+    /// Checks the description against the length limits of the config. Returns it (cleaned up
+    /// if auto_sanitize is set, cut down to the maximum if truncate_description is set) or all
+    /// violated limits.
+    pub fn check_description(&self, description: String) -> Result<String, Vec<String>> {
+        let mut description = description;
+        if self.config.auto_sanitize {
+            description = sanitize_description(&description);
+            if description.is_empty() {
+                return Err(vec![
+                    "Description has nothing but whitespace and punctuation".to_string(),
+                ]);
+            }
+        }
+        let len = description.chars().count();
+        let mut violations = Vec::new();
+        if let Some(max) = self.config.max_description_len
+            && len > max
+        {
+            if self.config.truncate_description {
+                description = description.chars().take(max).collect();
+            } else {
+                violations.push(format!("Description too long: {} chars (max {})", len, max));
+            }
+        }
+        if let Some(min) = self.config.min_description_len
+            && len < min
+        {
+            violations.push(format!(
+                "Description too short: {} chars (min {})",
+                len, min
+            ));
+        }
+        if violations.is_empty() {
+            Ok(description)
+        } else {
+            Err(violations)
+        }
+    }
+
+    /* show tasks */
+    pub fn list_tasks(&self) {
+        print!("{}", self.format_task_list(&TaskFilter::default(), 0));
+    }
+
+    /// prints all tasks in sections, see format_task_list_grouped
+    pub fn list_tasks_grouped(&self, by: GroupBy) {
+        print!(
+            "{}",
+            self.format_task_list_grouped(&TaskFilter::default(), 0, by)
+        );
+    }
+
+    /// Formats the tasks matching the filter as a list, including the header. Pinned tasks come
+    /// first. With a limit > 0, only that many tasks are shown, followed by a note how many are
+    /// hidden.
+    pub fn format_task_list(&self, filter: &TaskFilter, limit: usize) -> String {
+        self.format_task_list_grouped(filter, limit, GroupBy::None)
+    }
+
+    /// Like format_task_list, but divided into sections with a subheader each (e.g. "▲ High:").
+    /// Empty sections are left out; the limit counts the tasks of all sections together.
+    pub fn format_task_list_grouped(
+        &self,
+        filter: &TaskFilter,
+        limit: usize,
+        by: GroupBy,
+    ) -> String {
+        if let Some(message) = self.empty_list_message(filter) {
+            return message;
+        }
+        let sections = self.list_sections(filter, by);
+        let matching: usize = sections.iter().map(|(_, tasks)| tasks.len()).sum();
+        let shown = if limit == 0 { matching } else { limit };
+        let mut output = self.format_sections(&sections, 0, shown);
+        if matching > shown {
+            let hidden = format!("... and {} more tasks.", matching - shown);
+            output.push_str(&format!("{}\n", hidden.dimmed()));
+        }
+        output
+    }
+
+    /// prints one page of all tasks, see format_task_list_page
+    pub fn list_tasks_paged(&self, page: usize, page_size: usize) {
+        print!(
+            "{}",
+            self.format_task_list_page(&TaskFilter::default(), GroupBy::None, page, page_size)
+        );
+    }
+
+    /// Formats page `page` (1-based) of the list, with page_size tasks per page, followed by a
+    /// navigation hint. A page size of 0 shows everything on one page.
+    pub fn format_task_list_page(
+        &self,
+        filter: &TaskFilter,
+        by: GroupBy,
+        page: usize,
+        page_size: usize,
+    ) -> String {
+        if page_size == 0 {
+            return self.format_task_list_grouped(filter, 0, by);
+        }
+        if let Some(message) = self.empty_list_message(filter) {
+            return message;
+        }
+        let sections = self.list_sections(filter, by);
+        let matching: usize = sections.iter().map(|(_, tasks)| tasks.len()).sum();
+        let pages = matching.div_ceil(page_size);
+        if page == 0 || page > pages {
+            let msg = format!("No tasks on page {} (total pages: {})", page, pages);
+            return format!("{}\n", msg.yellow());
+        }
+        let mut output = self.format_sections(&sections, (page - 1) * page_size, page_size);
+        let footer = if page < pages {
+            format!("Page {}/{} — use --page {} for next", page, pages, page + 1)
+        } else {
+            format!("Page {}/{}", page, pages)
+        };
+        output.push_str(&format!("{}\n", footer.dimmed()));
+        output
+    }
+
+    /* the message shown instead of the list if there is nothing to show */
+    fn empty_list_message(&self, filter: &TaskFilter) -> Option<String> {
+        if self.tasks.is_empty() {
+            Some(format!("{}\n", "No tasks, all done!".green()))
+        } else if self.tasks_matching_filter(filter).next().is_none() {
+            Some(format!("{}\n", "No matching tasks.".yellow()))
+        } else {
+            None
+        }
+    }
+
+    /* the matching tasks in list order, divided into sections with an optional subheader */
+    fn list_sections(&self, filter: &TaskFilter, by: GroupBy) -> Vec<(Option<String>, Vec<&Task>)> {
+        /* pinned tasks are always shown first (within their section), keeping their relative order */
+        let ordered: Vec<&Task> = self
+            .tasks_matching(|t| t.get_pinned() && t.matches_filter(filter))
+            .chain(self.tasks_matching(|t| !t.get_pinned() && t.matches_filter(filter)))
+            .collect();
+        match by {
+            GroupBy::None => vec![(None, ordered)],
+            GroupBy::Priority => [Priority::High, Priority::Medium, Priority::Low]
+                .into_iter()
+                .map(|prio| {
+                    let header = format!("{} {}:", prio, prio.name());
+                    let tasks = ordered.iter().filter(|t| t.priority() == prio).copied();
+                    (Some(header), tasks.collect())
+                })
+                .collect(),
+            GroupBy::Status => vec![
+                (
+                    Some("Pending:".to_string()),
+                    ordered
+                        .iter()
+                        .filter(|t| !t.get_completed())
+                        .copied()
+                        .collect(),
+                ),
+                (
+                    Some("Completed:".to_string()),
+                    ordered
+                        .iter()
+                        .filter(|t| t.get_completed())
+                        .copied()
+                        .collect(),
+                ),
+            ],
+            GroupBy::Context => {
+                let mut by_context: BTreeMap<Option<&str>, Vec<&Task>> = BTreeMap::new();
+                for task in ordered {
+                    by_context
+                        .entry(task.contexts().first().copied())
+                        .or_default()
+                        .push(task);
+                }
+                /* None sorts first, but the tasks without context are listed last */
+                let without = by_context.remove(&None);
+                by_context
+                    .into_iter()
+                    .map(|(context, tasks)| (context.map(|c| format!("@{}:", c)), tasks))
+                    .chain(without.map(|tasks| (Some("No context:".to_string()), tasks)))
+                    .collect()
+            }
+        }
+    }
+
+    /* the header and the tasks skip..skip+take of all sections; subheaders of sections without
+     * tasks in that range are left out */
+    fn format_sections(
+        &self,
+        sections: &[(Option<String>, Vec<&Task>)],
+        skip: usize,
+        take: usize,
+    ) -> String {
+        let mut output = format!("{}\n", "Your tasks:".bold().underline());
+        let mut position = 0;
+        for (header, tasks) in sections {
+            let start = skip.saturating_sub(position).min(tasks.len());
+            let end = (skip + take).saturating_sub(position).min(tasks.len());
+            position += tasks.len();
+            if start >= end {
+                continue;
+            }
+            if let Some(header) = header {
+                output.push_str(&format!("{}\n", header.bold()));
+            }
+            for task in &tasks[start..end] {
+                output.push_str(&self.format_task_line(task));
+                output.push('\n');
+            }
+        }
+        output
+    }
+
+    /// prints the given tasks, one per line, formatted like in list_tasks
+    pub fn print_tasks(&self, tasks: &[&Task]) {
+        for task in tasks {
+            println!("{}", self.format_task_line(task));
+        }
+    }
+
+    /// formats a single task as a colored line for the task list
+    fn format_task_line(&self, task: &Task) -> String {
+        /* calculate how many spaces should be used for the numbers. */
+        let num_width = self.next_available_id / 10 + 2;
+        let theme = &self.config.theme;
+        let index_str = theme
+            .index_color
+            .paint(&format!("{1:>0$}", num_width, task.get_id()))
+            .bold();
+        let status = task.status();
+        let status_str = status.to_string();
+        let colored_status = match status {
+            TaskStatus::Completed => theme.completed_color.paint(&status_str).bold(),
+            TaskStatus::Overdue => status_str.red().bold(),
+            TaskStatus::Snoozed => status_str.magenta().dimmed(),
+            TaskStatus::Pending => status_str.magenta(),
+        };
+        let desc = task.get_description();
+        let mut colored_desc = if task.get_completed() {
+            desc.dimmed()
+        } else {
+            desc.normal()
+        };
+        /* the focused task gets a marker in front and bold text */
+        let focus_marker = if task.get_focused() {
+            colored_desc = colored_desc.bold();
+            "►".yellow().bold()
+        } else {
+            " ".normal()
+        };
+        let pin_marker = if task.get_pinned() { "📌 " } else { "" };
+        let star_marker = if task.get_starred() {
+            "★ ".yellow().bold()
+        } else {
+            "".normal()
+        };
+        let timer_marker = if task.running_time_entry().is_some() {
+            "⏱ "
+        } else {
+            ""
+        };
+        let link_marker = if task.get_url().is_some() {
+            "🔗 "
+        } else {
+            ""
+        };
+        let assignee = match task.get_assignee() {
+            Some(name) => format!("[{}] ", name).cyan(),
+            None => "".normal(),
+        };
+        /* tasks from additional files show the name of their file */
+        let source = match task.get_source_file() {
+            Some(path) => {
+                let name = Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.to_string());
+                format!(" ({})", name).dimmed()
+            }
+            None => "".normal(),
+        };
+        let due = match task.format_due_date_relative() {
+            Some(relative) => format!(" ({})", relative).color(task.due_date_color()),
+            None => "".normal(),
+        };
+        let snoozed = match task.get_snoozed_until() {
+            Some(until) if status == TaskStatus::Snoozed => {
+                format!(" (snoozed until {})", until).bright_black()
+            }
+            _ => "".normal(),
+        };
+        let estimate = match task.get_estimate_minutes() {
+            Some(minutes) => format!(" ({})", format_minutes(minutes as u64)).bright_black(),
+            None => "".normal(),
+        };
+        let reading_time = if self.long_list {
+            format!(" [{} read]", task.estimated_reading_time_display()).bright_black()
+        } else {
+            "".normal()
+        };
+        format!(
+            "{}{}: {} {} {}{}{}{}{}{}{}{}{}{}{}",
+            focus_marker,
+            index_str,
+            task.get_priority(theme),
+            colored_status,
+            star_marker,
+            pin_marker,
+            timer_marker,
+            link_marker,
+            assignee,
+            colored_desc,
+            due,
+            snoozed,
+            estimate,
+            reading_time,
+            source
+        )
+    }
+
+    /// Marks the task completed. A task with pending subtasks isn't completed, that gives
+    /// TaskError::HasPendingChildren; complete_task_with_subtasks completes them all.
+    pub fn complete_task(&mut self, id: usize) -> Result<String, TaskError> {
+        self.at(id).ok_or(TaskError::TaskNotFound(id))?;
+        let pending_child_ids = self.pending_subtasks(id);
+        if !pending_child_ids.is_empty() {
+            return Err(TaskError::HasPendingChildren {
+                parent_id: id,
+                pending_child_ids,
+            });
+        }
+        self.mark_task_completed(id)
+    }
+
+    /// Completes the task and all of its pending subtasks (and their subtasks)
+    pub fn complete_task_with_subtasks(&mut self, id: usize) -> Result<String, TaskError> {
+        self.at(id).ok_or(TaskError::TaskNotFound(id))?;
+        let subtasks = self.pending_subtasks(id);
+        for subtask in &subtasks {
+            self.mark_task_completed(*subtask)?;
+        }
+        let msg = self.mark_task_completed(id)?;
+        Ok(match subtasks.len() {
+            0 => msg,
+            1 => format!("{} and 1 subtask", msg),
+            n => format!("{} and {} subtasks", msg, n),
+        })
+    }
+
+    /* completes the task without looking at its subtasks */
+    fn mark_task_completed(&mut self, id: usize) -> Result<String, TaskError> {
+        let before = self.debug_state(id);
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.mark_completed();
+        let description = task.get_description().to_string();
+        self.audit(AuditOperation::Complete, id, description.as_str());
+        self.debug_change("complete_task", id, before);
+        Ok(format!("Completed Task: {}", description))
+    }
+
+    pub fn prioritize_task(&mut self, id: usize) -> Result<String, TaskError> {
+        let before = self.debug_state(id);
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let old_priority = task.priority();
+        task.prio_up();
+        let msg = format!("Prioritized Task: {}", task.get_description());
+        self.audit_priority(id, old_priority);
+        self.debug_change("prioritize_task", id, before);
+        Ok(msg)
+    }
+
+    pub fn deprioritize_task(&mut self, id: usize) -> Result<String, TaskError> {
+        let before = self.debug_state(id);
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let old_priority = task.priority();
+        task.prio_down();
+        let msg = format!("Deprioritized Task: {}", task.get_description());
+        self.audit_priority(id, old_priority);
+        self.debug_change("deprioritize_task", id, before);
+        Ok(msg)
+    }
+
+    /// Sets the priority of the task with the given ID
+    pub fn set_priority(&mut self, id: usize, priority: Priority) -> Result<String, TaskError> {
+        let before = self.debug_state(id);
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let old_priority = task.priority();
+        task.set_priority(priority);
+        let msg = format!("Set priority of Task: {}", task.get_description());
+        self.audit_priority(id, old_priority);
+        self.debug_change("set_priority", id, before);
+        Ok(msg)
+    }
+
+    /// Sets the priority of the task with the given ID to the priority with the given name
+    pub fn set_priority_by_name(&mut self, id: usize, name: &str) -> Result<String, TaskError> {
+        let new_priority: Priority = name.parse()?;
+        let before = self.debug_state(id);
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let old_priority = task.priority();
+        task.set_priority(new_priority);
+        self.audit_priority(id, old_priority);
+        self.debug_change("set_priority_by_name", id, before);
+        Ok(format!(
+            "Task #{} priority changed: {} {} → {} {}",
+            id,
+            old_priority,
+            old_priority.name(),
+            new_priority,
+            new_priority.name()
+        ))
+    }
+
+    pub fn change_priority(&mut self, id: usize, prioritize: bool) -> Result<String, TaskError> {
+        if prioritize {
+            self.prioritize_task(id)
+        } else {
+            self.deprioritize_task(id)
+        }
+    }
+
+    /// Focuses the task with the given ID. At most one task can be focused, so any previously
+    /// focused task gets unfocused.
+    pub fn focus_task(&mut self, id: usize) -> Result<String, TaskError> {
+        if self.find_id(id).is_none() {
+            return Err(TaskError::TaskNotFound(id));
+        }
+        for task in &mut self.tasks {
+            task.set_focused(task.get_id() == id);
+        }
+        let task = self.at(id).ok_or(TaskError::TaskNotFound(id))?;
+        Ok(format!("Focused Task: {}", task.get_description()))
+    }
+
+    /// Removes the focus from the currently focused task, if there is one
+    pub fn unfocus_task(&mut self) -> String {
+        match self.tasks.iter_mut().find(|t| t.get_focused()) {
+            Some(task) => {
+                task.set_focused(false);
+                format!("Unfocused Task: {}", task.get_description())
+            }
+            None => "No task is focused".to_string(),
+        }
+    }
+
+    /// Sorts the task list by the first key, tasks that are equal by it by the second key and so
+    /// on. Tasks that are equal by all keys keep their order.
+    pub fn sort_tasks(&mut self, keys: &[SortKey]) {
+        let today = Local::now().date_naive();
+        self.tasks.sort_by(|a, b| {
+            keys.iter().fold(Ordering::Equal, |ordering, key| {
+                ordering.then_with(|| key.compare(a, b, today))
+            })
+        });
+    }
+
+    /// Sorts the task list by any key computed from a task. The sort is stable: tasks with the
+    /// same key keep their order, so sorting by a second key first and then by the first one sorts
+    /// by both.
+    ///
+    /// ```
+    /// # use tasks::TaskManager;
+    /// # use std::cmp::Reverse;
+    /// let mut manager = TaskManager::new("tasks.json".into());
+    /// manager.add_task("write report").unwrap();
+    /// manager.add_task("call Bob").unwrap();
+    /// manager.add_task("pay rent").unwrap();
+    /// manager.assign_task(3, "alice").unwrap();
+    /// // longest description first, "call Bob" and "pay rent" keep their order
+    /// manager.sort_stable_by_key(|t| Reverse(t.get_description().len()));
+    /// let ids: Vec<usize> = manager.iter().map(|t| t.get_id()).collect();
+    /// assert_eq!(ids, [1, 2, 3]);
+    /// // assigned tasks first, unassigned ones still ordered by description length
+    /// manager.sort_stable_by_key(|t| t.get_assignee().is_none());
+    /// let ids: Vec<usize> = manager.iter().map(|t| t.get_id()).collect();
+    /// assert_eq!(ids, [3, 1, 2]);
+    /// ```
+    pub fn sort_stable_by_key<K: Ord, F: Fn(&Task) -> K>(&mut self, key_fn: F) {
+        self.tasks.sort_by_key(key_fn);
+    }
+
+    /// sorts the task list by urgency, most urgent first
+    pub fn sort_by_urgency(&mut self) {
+        self.sort_tasks(&[SortKey::Urgency]);
+    }
+
+    /// returns the task to work on next: the focused one, otherwise the most urgent pending task
+    /// that isn't snoozed (a starred one, then the first one in the list if several are equally
+    /// urgent)
+    ///
+    /// ```
+    /// # use tasks::TaskManager;
+    /// let mut manager = TaskManager::new("tasks.json".into());
+    /// manager.add_task("water plants").unwrap();
+    /// manager.add_task("call Bob").unwrap();
+    /// assert_eq!(manager.next_task().unwrap().get_description(), "water plants");
+    /// manager.star_task(2).unwrap();
+    /// assert_eq!(manager.next_task().unwrap().get_description(), "call Bob");
+    /// ```
+    pub fn next_task(&self) -> Option<&Task> {
+        if let Some(task) = self.focused_task().filter(|t| !t.get_completed()) {
+            return Some(task);
+        }
+        let today = Local::now().date_naive();
+        self.tasks
+            .iter()
+            .filter(|t| !t.get_completed() && !t.is_snoozed_on(today))
+            .fold(None, |best: Option<&Task>, task| match best {
+                Some(b)
+                    if b.urgency_score_on(today)
+                        .total_cmp(&task.urgency_score_on(today))
+                        .then(b.get_starred().cmp(&task.get_starred()))
+                        .is_ge() =>
+                {
+                    Some(b)
+                }
+                _ => Some(task),
+            })
+    }
+
+    /// returns the focused task, if there is one
+    pub fn focused_task(&self) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.get_focused())
+    }
+
+    /// Pins the task with the given ID, so it is always listed first
+    pub fn pin_task(&mut self, id: usize) -> Result<String, TaskError> {
+        if let Some(task) = self.at_mut(id) {
+            task.set_pinned(true);
+            Ok(format!("Pinned Task: {}", task.get_description()))
+        } else {
+            Err(TaskError::TaskNotFound(id))
+        }
+    }
+
+    /// Unpins the task with the given ID
+    pub fn unpin_task(&mut self, id: usize) -> Result<String, TaskError> {
+        if let Some(task) = self.at_mut(id) {
+            task.set_pinned(false);
+            Ok(format!("Unpinned Task: {}", task.get_description()))
+        } else {
+            Err(TaskError::TaskNotFound(id))
+        }
+    }
+
+    /// Stars the task with the given ID, marking it as important regardless of its priority
+    pub fn star_task(&mut self, id: usize) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.set_starred(true);
+        Ok(format!("Starred Task: {}", task.get_description()))
+    }
+
+    /// Removes the star from the task with the given ID
+    pub fn unstar_task(&mut self, id: usize) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.set_starred(false);
+        Ok(format!("Unstarred Task: {}", task.get_description()))
+    }
+
+    /// Exchanges the positions of two tasks in the list; their IDs stay the same
+    pub fn swap_positions(&mut self, id1: usize, id2: usize) -> Result<String, TaskError> {
+        let idx1 = self.find_id(id1).ok_or(TaskError::TaskNotFound(id1))?;
+        let idx2 = self.find_id(id2).ok_or(TaskError::TaskNotFound(id2))?;
+        self.tasks.swap(idx1, idx2); // swapping a task with itself does nothing
+        Ok(format!("Swapped #{} and #{}", id1, id2))
+    }
+
+    /// The new IDs reindex would give: (old ID, new ID) for every task whose ID changes
+    pub fn reindex_mapping(&self) -> Vec<(usize, usize)> {
+        (1..)
+            .zip(&self.tasks)
+            .filter(|(new_id, t)| t.get_id() != *new_id)
+            .map(|(new_id, t)| (t.get_id(), new_id))
+            .collect()
+    }
+
+    /// Numbers all tasks 1, 2, 3, ... in their list order, so the gaps left by deleted tasks
+    /// disappear. Returns the (old ID, new ID) pairs of all tasks whose ID changed.
+    pub fn reindex(&mut self) -> Vec<(usize, usize)> {
+        let mapping = self.reindex_mapping();
+        let new_ids: HashMap<usize, usize> = mapping.iter().copied().collect();
+        let existing: HashSet<usize> = self.tasks.iter().map(Task::get_id).collect();
+        for (new_id, task) in (1..).zip(self.tasks.iter_mut()) {
+            task.set_id(new_id);
+            /* the parent of a subtask gets its new ID, a deleted parent is forgotten, so the
+             * subtask doesn't end up under the task that gets the old ID */
+            if let Some(parent_id) = task.get_parent_id() {
+                let parent_id = match new_ids.get(&parent_id) {
+                    Some(new_parent_id) => Some(*new_parent_id),
+                    None => Some(parent_id).filter(|p| existing.contains(p)),
+                };
+                if parent_id != task.get_parent_id() {
+                    task.set_parent_id(parent_id);
+                }
+            }
+        }
+        /* tasks of additional files are saved with their new IDs, which are unique now */
+        self.original_ids.clear();
+        self.next_available_id = self.tasks.len() + 1;
+        mapping
+    }
+
+    /// Moves the task with the given ID in front of the target task
+    pub fn move_before(&mut self, id: usize, target_id: usize) -> Result<(), TaskError> {
+        self.move_next_to(id, target_id, false)
+    }
+
+    /// Moves the task with the given ID behind the target task
+    pub fn move_after(&mut self, id: usize, target_id: usize) -> Result<(), TaskError> {
+        self.move_next_to(id, target_id, true)
+    }
+
+    fn move_next_to(&mut self, id: usize, target_id: usize, after: bool) -> Result<(), TaskError> {
+        let idx = self.find_id(id).ok_or(TaskError::TaskNotFound(id))?;
+        if self.find_id(target_id).is_none() {
+            return Err(TaskError::TaskNotFound(target_id));
+        }
+        if id == target_id {
+            return Ok(()); // nothing to move
+        }
+        let task = self.tasks.remove(idx);
+        /* the target index has to be looked up again, removing the task might have shifted it */
+        let target_idx = self
+            .find_id(target_id)
+            .ok_or(TaskError::TaskNotFound(target_id))?;
+        let insert_idx = if after { target_idx + 1 } else { target_idx };
+        self.tasks.insert(insert_idx, task);
+        Ok(())
+    }
+
+    /// Sets the time estimate of the task with the given ID, in minutes
+    pub fn estimate_task(&mut self, id: usize, minutes: u32) -> Result<String, TaskError> {
+        if let Some(task) = self.at_mut(id) {
+            task.set_estimate_minutes(Some(minutes));
+            Ok(format!(
+                "Estimated Task {} at {}",
+                task.get_description(),
+                format_minutes(minutes as u64)
+            ))
+        } else {
+            Err(TaskError::TaskNotFound(id))
+        }
+    }
+
+    /// Sets (or with None removes) the due date of the task with the given ID
+    pub fn set_due(&mut self, id: usize, due: Option<NaiveDate>) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.set_due(due);
+        Ok(match due {
+            Some(date) => format!("Task {} is due on {}", task.get_description(), date),
+            None => format!("Removed due date of Task: {}", task.get_description()),
+        })
+    }
+
+    /// Hides the task from the list until the given day, which has to be after today
+    pub fn snooze_task(&mut self, id: usize, until: NaiveDate) -> Result<String, TaskError> {
+        if until <= Local::now().date_naive() {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "can only snooze until a day after today, not {}",
+                until
+            )));
+        }
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.set_snoozed_until(Some(until));
+        Ok(format!(
+            "Snoozed Task until {}: {}",
+            until,
+            task.get_description()
+        ))
+    }
+
+    /// shows a snoozed task in the list again
+    pub fn unsnooze_task(&mut self, id: usize) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        if task.get_snoozed_until().is_none() {
+            return Ok(format!("Task isn't snoozed: {}", task.get_description()));
+        }
+        task.set_snoozed_until(None);
+        Ok(format!("Unsnoozed Task: {}", task.get_description()))
+    }
+
+    /// Clears the snooze date of the tasks whose day has come. Returns how many tasks woke up.
+    pub fn wake_snoozed_tasks(&mut self) -> usize {
+        let today = Local::now().date_naive();
+        let mut woken = 0;
+        for task in &mut self.tasks {
+            if task.get_snoozed_until().is_some() && !task.is_snoozed_on(today) {
+                task.set_snoozed_until(None);
+                woken += 1;
+            }
+        }
+        if woken > 0 {
+            self.debug_log(|| format!("woke {} snoozed tasks", woken));
+        }
+        woken
+    }
+
+    /// Sums up the estimates of all pending tasks; None if no pending task has an estimate
+    pub fn total_estimated_pending(&self) -> Option<Duration> {
+        self.tasks
+            .iter()
+            .filter(|t| !t.get_completed())
+            .filter_map(|t| t.get_estimate_minutes())
+            .map(|m| Duration::from_secs(m as u64 * 60))
+            .reduce(|a, b| a + b)
+    }
+
+    /// returns all tasks sorted by their estimate, longest first; tasks without estimate last
+    pub fn tasks_by_estimate(&self) -> Vec<&Task> {
+        let mut tasks: Vec<&Task> = self.tasks.iter().collect();
+        tasks.sort_by_key(|t| std::cmp::Reverse(t.get_estimate_minutes()));
+        tasks
+    }
+
+    /// Assigns the task with the given ID to a person; the name must not be blank
+    pub fn assign_task<S: Into<String>>(
+        &mut self,
+        id: usize,
+        assignee: S,
+    ) -> Result<String, TaskError> {
+        let assignee = assignee.into().trim().to_string();
+        if assignee.is_empty() {
+            return Err(TaskError::Empty("Assignee".to_string()));
+        }
+        if let Some(task) = self.at_mut(id) {
+            let msg = format!("Assigned Task {} to {}", task.get_description(), assignee);
+            task.set_assignee(Some(assignee));
+            Ok(msg)
+        } else {
+            Err(TaskError::TaskNotFound(id))
+        }
+    }
+
+    /// Removes the assignee of the task with the given ID
+    pub fn unassign_task(&mut self, id: usize) -> Result<String, TaskError> {
+        if let Some(task) = self.at_mut(id) {
+            task.set_assignee(None);
+            Ok(format!("Unassigned Task: {}", task.get_description()))
+        } else {
+            Err(TaskError::TaskNotFound(id))
+        }
+    }
+
+    /// Makes the task a subtask of the parent, or with None a task of its own again. A task can't
+    /// be a subtask of itself or of one of its own subtasks.
+    pub fn set_parent(&mut self, id: usize, parent_id: Option<usize>) -> Result<String, TaskError> {
+        self.at(id).ok_or(TaskError::TaskNotFound(id))?;
+        if let Some(parent_id) = parent_id {
+            self.at(parent_id)
+                .ok_or(TaskError::TaskNotFound(parent_id))?;
+            if parent_id == id || self.subtasks(id).contains(&parent_id) {
+                return Err(TaskError::ArgumentMismatch(format!(
+                    "#{} can't be a subtask of #{}, that would be a loop",
+                    id, parent_id
+                )));
+            }
+        }
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.set_parent_id(parent_id);
+        Ok(match parent_id {
+            Some(parent_id) => format!(
+                "Task {} is a subtask of #{}",
+                task.get_description(),
+                parent_id
+            ),
+            None => format!("Task {} has no parent", task.get_description()),
+        })
+    }
+
+    /// IDs of the pending subtasks of the task, their subtasks included, in list order
+    pub fn pending_subtasks(&self, id: usize) -> Vec<usize> {
+        let subtasks = self.subtasks(id);
+        self.tasks
+            .iter()
+            .filter(|t| !t.get_completed() && subtasks.contains(&t.get_id()))
+            .map(Task::get_id)
+            .collect()
+    }
+
+    /* the IDs of the subtasks of the task, their subtasks and so on */
+    fn subtasks(&self, id: usize) -> HashSet<usize> {
+        let mut found: HashSet<usize> = HashSet::new();
+        let mut parents = vec![id];
+        while let Some(parent) = parents.pop() {
+            for task in &self.tasks {
+                if task.get_parent_id() == Some(parent) && found.insert(task.get_id()) {
+                    parents.push(task.get_id());
+                }
+            }
+        }
+        /* in a file edited by hand the task may be its own ancestor */
+        found.remove(&id);
+        found
+    }
+
+    /// Attaches a URL (http or https) to the task with the given ID
+    pub fn link_task<S: Into<String>>(&mut self, id: usize, url: S) -> Result<String, TaskError> {
+        let url = url.into();
+        check_url(&url)?;
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let msg = format!("Linked Task {} to {}", task.get_description(), url);
+        task.set_url(Some(url));
+        Ok(msg)
+    }
+
+    /// Replaces the notes of the task with the given ID; None or empty text removes them
+    pub fn set_notes(&mut self, id: usize, notes: Option<String>) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.set_notes(notes);
+        let msg = match task.get_notes() {
+            Some(_) => format!("Set notes of Task: {}", task.get_description()),
+            None => format!("Cleared notes of Task: {}", task.get_description()),
+        };
+        Ok(msg)
+    }
+
+    /// Adds a line to the notes of the task with the given ID
+    pub fn append_note(&mut self, id: usize, line: &str) -> Result<String, TaskError> {
+        if line.trim().is_empty() {
+            return Err(TaskError::Empty("Note".to_string()));
+        }
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.append_note(line);
+        Ok(format!("Added a note to Task: {}", task.get_description()))
+    }
+
+    /// Starts the time tracking of the task with the given ID; an error if it's already running
+    pub fn start_timer(&mut self, id: usize) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        if let Some(entry) = task.running_time_entry() {
+            return Err(TaskError::ArgumentMismatch(format!(
+                "the timer of task #{} is already running since {}",
+                id,
+                entry.start.with_timezone(&Local).format("%H:%M")
+            )));
+        }
+        task.start_time_entry();
+        Ok(format!("Started Task: {}", task.get_description()))
+    }
+
+    /// Stops the running time tracking of the task with the given ID
+    pub fn stop_timer(&mut self, id: usize) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let description = task.get_description().to_string();
+        let entry = task.stop_time_entry().ok_or_else(|| {
+            TaskError::ArgumentMismatch(format!("the timer of task #{} isn't running", id))
+        })?;
+        let minutes = entry.duration().unwrap_or_default().as_secs() / 60;
+        Ok(format!(
+            "Stopped Task: {} after {}",
+            description,
+            format_minutes(minutes)
+        ))
+    }
+
+    /// Adds time worked on the task with the given ID, ending now
+    pub fn log_time(
+        &mut self,
+        id: usize,
+        minutes: u32,
+        note: Option<String>,
+    ) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let end = Utc::now();
+        task.add_time_entry(TimeEntry {
+            start: end - chrono::Duration::minutes(minutes as i64),
+            end: Some(end),
+            note: note.filter(|n| !n.trim().is_empty()),
+        });
+        Ok(format!(
+            "Logged {} for Task: {}",
+            format_minutes(minutes as u64),
+            task.get_description()
+        ))
+    }
+
+    /// the time logged for all tasks together
+    pub fn total_time_spent(&self) -> Duration {
+        self.tasks.iter().map(Task::total_time_spent).sum()
+    }
+
+    /// Removes the link of the task with the given ID
+    pub fn unlink_task(&mut self, id: usize) -> Result<String, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.set_url(None);
+        Ok(format!("Unlinked Task: {}", task.get_description()))
+    }
+
+    /// returns the link of the task with the given ID; an error if the task has none
+    pub fn task_url(&self, id: usize) -> Result<&str, TaskError> {
+        let task = self.at(id).ok_or(TaskError::TaskNotFound(id))?;
+        task.get_url()
+            .ok_or_else(|| TaskError::ArgumentMismatch(format!("task #{} doesn't have a link", id)))
+    }
+
+    /// Deletes all tasks that are marked as completed.
+    /// Returns the number of tasks cleared.
+    pub fn clear_completed_tasks(&mut self) -> usize {
+        self.drain_completed().len()
+    }
+
+    /// Removes all completed tasks and returns them, e.g. to archive them
+    pub fn drain_completed(&mut self) -> Vec<Task> {
+        self.remove_where(|task| task.get_completed())
+    }
+
+    /// Removes the completed tasks that weren't changed for longer than `older_than` (completing
+    /// a task counts as a change). Tasks without a change time are kept. Returns how many were
+    /// removed.
+    pub fn purge_old_completed(&mut self, older_than: Duration) -> usize {
+        self.retain(|task| !(task.get_completed() && task.is_stale(older_than)))
+    }
+
+    /// Fixes a warning of TaskLinter, if it can be fixed (see LintWarning::is_fixable): a long
+    /// description is cut down and the full text moved to the start of the notes, an old
+    /// completed task is deleted. Returns what was done, None if the warning isn't fixable.
+    pub fn fix_lint_warning(&mut self, warning: &LintWarning) -> Result<Option<String>, TaskError> {
+        match warning {
+            LintWarning::LongDescription { id, .. } => {
+                let task = self.at(*id).ok_or(TaskError::TaskNotFound(*id))?;
+                let full = task.get_description().to_string();
+                let notes = match task.get_notes() {
+                    Some(notes) => format!("{}\n{}", full, notes),
+                    None => full.clone(),
+                };
+                let short = shorten_description(&full, MAX_DESCRIPTION_CHARS);
+                self.change_description(*id, short.as_str())?;
+                self.set_notes(*id, Some(notes))?;
+                Ok(Some(format!(
+                    "Shortened #{} to '{}', the full text is in its notes",
+                    id, short
+                )))
+            }
+            LintWarning::OldCompleted { id, .. } => {
+                self.delete_task(*id)?;
+                Ok(Some(format!("Cleared completed task #{}", id)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The duplicates deduplicate would remove, as (ID of the duplicate, ID of the task it
+    /// duplicates), in list order
+    pub fn find_duplicates(&self, strategy: DeduplicateStrategy) -> Vec<(usize, usize)> {
+        let mut candidates: Vec<&Task> = self.tasks.iter().collect();
+        candidates.sort_by(|a, b| strategy.compare(a, b));
+        let mut kept: Vec<&Task> = Vec::new();
+        let mut duplicates = HashMap::new();
+        for task in candidates {
+            match kept.iter().find(|k| strategy.is_duplicate(k, task)) {
+                Some(original) => {
+                    duplicates.insert(task.get_id(), original.get_id());
+                }
+                None => kept.push(task),
+            }
+        }
+        self.tasks
+            .iter()
+            .filter_map(|t| duplicates.get(&t.get_id()).map(|kept| (t.get_id(), *kept)))
+            .collect()
+    }
+
+    /// Removes duplicate tasks, see DeduplicateStrategy for which task of the duplicates is
+    /// kept. Returns the number of removed tasks.
+    pub fn deduplicate(&mut self, strategy: DeduplicateStrategy) -> usize {
+        let duplicates: HashSet<usize> = self
+            .find_duplicates(strategy)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        self.retain(|task| !duplicates.contains(&task.get_id()))
+    }
+
+    /// Keeps only the tasks for which the predicate returns true.
+    /// Returns the number of tasks removed.
+    pub fn retain<F: Fn(&Task) -> bool>(&mut self, predicate: F) -> usize {
+        let before = self.tasks.len();
+        /* removes in place; only the audit log needs to know about the removed tasks */
+        let audit_log = &mut self.audit_log;
+        self.tasks.retain(|task| {
+            let keep = predicate(task);
+            if !keep && let Some(log) = audit_log.as_mut() {
+                log.record(
+                    AuditOperation::Delete,
+                    task.get_id(),
+                    task.get_description(),
+                );
+            }
+            keep
+        });
+        before - self.tasks.len()
+    }
+
+    /// Removes all tasks for which the predicate returns true and returns them
+    pub fn remove_where<F: Fn(&Task) -> bool>(&mut self, predicate: F) -> Vec<Task> {
+        let (removed, kept): (Vec<Task>, Vec<Task>) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition(|task| predicate(task));
+        self.tasks = kept;
+        for task in &removed {
+            self.audit(
+                AuditOperation::Delete,
+                task.get_id(),
+                task.get_description(),
+            );
+        }
+        removed
+    }
+
+    /// Changes the description of a task with a given ID
+    pub fn change_description<S: Into<String>>(
+        &mut self,
+        id: usize,
+        new_description: S,
+    ) -> Result<String, TaskError> {
+        let new_description = self
+            .check_description(new_description.into())
+            .map_err(TaskError::ValidationErrors)?;
+        let before = self.debug_state(id);
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let old_desc: String = task.get_description().to_string();
+        task.set_description(new_description);
+        let new_desc = task.get_description().to_string();
+        self.audit(
+            AuditOperation::ChangeDescription,
+            id,
+            format!("'{}' → '{}'", old_desc, new_desc),
+        );
+        self.debug_change("change_description", id, before);
+        Ok(format!(
+            "Description of task {} changed.\n\tOld: \"{}\"\n\tNew: \"{}\"",
+            id, old_desc, new_desc
+        ))
+    }
+
+    /// Cleans up the description of a task, see Task::sanitize_description. Returns true if it
+    /// changed.
+    pub fn sanitize_task(&mut self, id: usize) -> Result<bool, TaskError> {
+        let task = self.at_mut(id).ok_or(TaskError::TaskNotFound(id))?;
+        let old_desc = task.get_description().to_string();
+        if !task.sanitize_description() {
+            return Ok(false);
+        }
+        let new_desc = task.get_description().to_string();
+        self.audit(
+            AuditOperation::ChangeDescription,
+            id,
+            format!("'{}' → '{}'", old_desc, new_desc),
+        );
+        Ok(true)
+    }
+
+    /// Cleans up the descriptions of all tasks. Returns the IDs of the changed tasks.
+    pub fn sanitize_all(&mut self) -> Vec<usize> {
+        let ids: Vec<usize> = self.tasks.iter().map(|t| t.get_id()).collect();
+        ids.into_iter()
+            .filter(|id| self.sanitize_task(*id).unwrap_or(false))
+            .collect()
+    }
+
+    /// Computes which descriptions replace_in_descriptions would change, without changing them.
+    /// Returns ID, old and new description of every affected task, in list order.
+    pub fn description_replacements(
+        &self,
+        pattern: &str,
+        replacement: &str,
+        regex: bool,
+    ) -> Result<Vec<(usize, String, String)>, TaskError> {
+        if pattern.is_empty() {
+            return Err(TaskError::Empty("Pattern".to_string()));
+        }
+        /* a plain pattern is matched literally and case-insensitively; its replacement is taken
+         * literally as well, while a regex replacement may refer to groups like $1 */
+        let (re, replacement) = if regex {
+            (Regex::new(pattern)?, Cow::Borrowed(replacement))
+        } else {
+            let re = Regex::new(&format!("(?i){}", regex::escape(pattern)))?;
+            (re, Cow::Owned(replacement.replace('$', "$$")))
+        };
+        Ok(self
+            .tasks
+            .iter()
+            .filter_map(|task| {
+                let old = task.get_description();
+                let new = re.replace_all(old, replacement.as_ref());
+                (new != old).then(|| (task.get_id(), old.to_string(), new.into_owned()))
+            })
+            .collect())
+    }
+
+    /// Replaces the pattern (a case-insensitive substring, or a regex) with the replacement in
+    /// all descriptions. Returns the IDs of the changed tasks.
+    pub fn replace_in_descriptions(
+        &mut self,
+        pattern: &str,
+        replacement: &str,
+        regex: bool,
+    ) -> Result<Vec<usize>, TaskError> {
+        let changes = self.description_replacements(pattern, replacement, regex)?;
+        /* all or nothing: if one change fails, the ones before are undone */
+        let snapshot = self.snapshot();
+        for (id, _, new_desc) in &changes {
+            if let Err(e) = self.change_description(*id, new_desc.as_str()) {
+                self.restore(snapshot);
+                return Err(e);
+            }
+        }
+        Ok(changes.into_iter().map(|(id, _, _)| id).collect())
+    }
+
+    /// Deletes the task with the given ID; its subtasks become tasks of their own
+    pub fn delete_task(&mut self, id: usize) -> Result<String, TaskError> {
+        if let Some(idx) = self.find_id(id) {
+            let before = self.debug_state(id);
+            let old_task = self.tasks.remove(idx);
+            for child in self.tasks_matching_mut(|t| t.get_parent_id() == Some(id)) {
+                child.set_parent_id(None);
+            }
+            self.audit(AuditOperation::Delete, id, old_task.get_description());
+            self.debug_change("delete_task", id, before);
+            Ok(format!(
+                "Deleted task ID {}\n\t'{}'",
+                id,
+                old_task.get_description()
+            ))
+        } else {
+            Err(TaskError::TaskNotFound(id))
+        }
+    }
+
+    /// returns all tasks whose description contains the query, ignoring case
+    pub fn find_by_description(&self, query: &str) -> Vec<&Task> {
+        let query = query.to_lowercase();
+        self.tasks_matching(|t| t.get_description().to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// the task whose description is exactly the given one, ignoring case (the first of them if
+    /// several tasks have the same description)
+    pub fn get_by_description_exact(&self, desc: &str) -> Option<&Task> {
+        let desc = desc.to_lowercase();
+        self.tasks_matching(|t| t.get_description().to_lowercase() == desc)
+            .next()
+    }
+
+    /// returns all tasks whose description starts with the prefix, ignoring case
+    pub fn get_by_description_prefix(&self, prefix: &str) -> Vec<&Task> {
+        let prefix = prefix.to_lowercase();
+        self.tasks_matching(|t| t.get_description().to_lowercase().starts_with(&prefix))
+            .collect()
+    }
+
+    /// like get_by_description_prefix, but the tasks can be changed
+    pub fn get_by_description_prefix_mut(&mut self, prefix: &str) -> Vec<&mut Task> {
+        let prefix = prefix.to_lowercase();
+        self.tasks_matching_mut(|t| t.get_description().to_lowercase().starts_with(&prefix))
+            .collect()
+    }
+
+    /// returns all tasks whose description is similar to the query, together with the similarity
+    /// score (0.0 - 1.0), best matches first. Similarity is the Jaccard index of character bigrams.
+    pub fn fuzzy_find(&self, query: &str) -> Vec<(&Task, f32)> {
+        let mut matches: Vec<(&Task, f32)> = self
+            .tasks
+            .iter()
+            .map(|t| (t, bigram_similarity(query, t.get_description())))
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        matches
+    }
+
+    /// A random pending task matching the filter (all pending tasks without one); every task
+    /// has the same chance. None if no task matches.
+    pub fn random_task(&self, filter: Option<TaskFilter>) -> Option<&Task> {
+        self.random_candidates(filter)
+            .choose(&mut rand::rng())
+            .copied()
+    }
+
+    /// Like random_task, but the chance of a task depends on its priority, see
+    /// Config::priority_weights. If all matching tasks have the weight 0, every task has the
+    /// same chance.
+    pub fn random_task_weighted(&self, filter: Option<TaskFilter>) -> Option<&Task> {
+        let candidates = self.random_candidates(filter);
+        let weights = self.config.priority_weights;
+        let mut rng = rand::rng();
+        match candidates.choose_weighted(&mut rng, |task| weights.weight(task.priority())) {
+            Ok(task) => Some(*task),
+            Err(_) => candidates.choose(&mut rng).copied(),
+        }
+    }
+
+    /* the pending tasks random_task picks from */
+    fn random_candidates(&self, filter: Option<TaskFilter>) -> Vec<&Task> {
+        let filter = filter.unwrap_or_default();
+        self.tasks
+            .iter()
+            .filter(|task| !task.get_completed() && task.matches_filter(&filter))
+            .collect()
+    }
+
+    /// Returns the tasks that should be worked on today: all pending tasks that are overdue or
+    /// due today, and High priority tasks without a due date; snoozed tasks are left out. They
+    /// are grouped by priority, High first, and in list order within a priority.
+    pub fn tasks_for_today(&self) -> Vec<&Task> {
+        let today = Local::now().date_naive();
+        let mut tasks: Vec<&Task> = self
+            .tasks
+            .iter()
+            .filter(|t| !t.get_completed() && !t.is_snoozed_on(today))
+            .filter(|t| {
+                t.get_due()
+                    .map_or(t.priority() == Priority::High, |d| d <= today)
+            })
+            .collect();
+        tasks.sort_by_key(|t| std::cmp::Reverse(t.priority()));
+        tasks
+    }
+
+    /// Groups the pending tasks due in the given number of days from start on by their due date,
+    /// leaving out snoozed tasks. Every day of the range is in the map, days without tasks have
+    /// an empty list.
+    pub fn tasks_by_day(&self, start: NaiveDate, days: u32) -> BTreeMap<NaiveDate, Vec<&Task>> {
+        let mut by_day: BTreeMap<NaiveDate, Vec<&Task>> = (0..days as u64)
+            .filter_map(|offset| start.checked_add_days(Days::new(offset)))
+            .map(|day| (day, Vec::new()))
+            .collect();
+        for task in self
+            .tasks
+            .iter()
+            .filter(|t| !t.get_completed() && !t.is_snoozed())
+        {
+            if let Some(due) = task.get_due()
+                && let Some(day) = by_day.get_mut(&due)
+            {
+                day.push(task);
+            }
+        }
+        by_day
+    }
+
+    /// Counts the completed tasks per day over the last days (today included), by the local date
+    /// they were completed (see Task::get_completed_at). Every day of the range is in the map.
+    /// Tasks that were cleared or deleted aren't counted anymore.
+    pub fn completion_history(&self, days: u32) -> BTreeMap<NaiveDate, usize> {
+        let today = Local::now().date_naive();
+        let mut history: BTreeMap<NaiveDate, usize> = (0..days as u64)
+            .filter_map(|offset| today.checked_sub_days(Days::new(offset)))
+            .map(|day| (day, 0))
+            .collect();
+        for task in self.tasks.iter().filter(|t| t.get_completed()) {
+            if let Some(completed_at) = task.get_completed_at()
+                && let Some(count) =
+                    history.get_mut(&completed_at.with_timezone(&Local).date_naive())
+            {
+                *count += 1;
+            }
+        }
+        history
+    }
+
+    /// counts all tasks per priority
+    pub fn count_by_priority(&self) -> PriorityCounts {
+        PriorityCounts::count(self.tasks.iter())
+    }
+
+    /// counts the pending tasks per priority
+    pub fn count_by_priority_pending(&self) -> PriorityCounts {
+        PriorityCounts::count(self.tasks.iter().filter(|t| !t.get_completed()))
+    }
+
+    /// Counts the tasks per "+tag" of their description; tasks without tags aren't counted,
+    /// tasks with several tags are counted for each of them
+    ///
+    /// ```
+    /// use tasks::{Priority, Task, TaskManager};
+    ///
+    /// assert!(TaskManager::from_vec(Vec::new()).statistics_by_tag().is_empty());
+    ///
+    /// let mut done = Task::new_task("Send invoice +work +billing", 1, Priority::High);
+    /// done.mark_completed();
+    /// let manager = TaskManager::from_vec(vec![
+    ///     done,
+    ///     Task::new_task("Plan sprint +work", 2, Priority::Medium),
+    ///     Task::new_task("Buy milk", 3, Priority::Low),
+    /// ]);
+    /// let stats = manager.statistics_by_tag();
+    /// assert_eq!(stats.len(), 2);
+    /// assert_eq!((stats["work"].total, stats["work"].completed), (2, 1));
+    /// assert_eq!(stats["billing"].high_priority, 1);
+    /// ```
+    pub fn statistics_by_tag(&self) -> HashMap<String, TagStats> {
+        TagStats::count(self.tasks.iter(), Task::tags)
+    }
+
+    /// counts the tasks per "@context" of their description, like statistics_by_tag
+    pub fn statistics_by_context(&self) -> HashMap<String, TagStats> {
+        TagStats::count(self.tasks.iter(), Task::contexts)
+    }
+
+    /// counts the tasks per status (as of today); states without tasks are left out
+    pub fn count_by_status(&self) -> HashMap<TaskStatus, usize> {
+        let mut counts = HashMap::new();
+        for task in &self.tasks {
+            *counts.entry(task.status()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// number of tasks that have a due date, completed ones included
+    pub fn count_with_due_date(&self) -> usize {
+        self.tasks.iter().filter(|t| t.get_due().is_some()).count()
+    }
+
+    /// number of pending tasks whose due date has passed (by the local date)
+    pub fn count_overdue(&self) -> usize {
+        let today = Local::now().date_naive();
+        self.tasks.iter().filter(|t| t.is_overdue(today)).count()
+    }
+
+    /// returns the pending tasks that weren't changed for longer than the threshold, least
+    /// recently changed first
+    pub fn stale_tasks(&self, threshold: Duration) -> Vec<&Task> {
+        let mut stale: Vec<&Task> = self
+            .tasks_matching(|t| !t.get_completed() && t.is_stale(threshold))
+            .collect();
+        stale.sort_by_key(|t| t.get_updated_at());
+        stale
+    }
+
+    /// returns the pending tasks whose due date is before the given day, oldest first
+    pub fn overdue_tasks(&self, today: NaiveDate) -> Vec<&Task> {
+        let mut overdue: Vec<&Task> = self.tasks.iter().filter(|t| t.is_overdue(today)).collect();
+        overdue.sort_by_key(|t| t.get_due());
+        overdue
+    }
+
+    /// returns the path of the file the tasks are loaded from and saved to
+    pub fn get_file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Compares two task lists, matching tasks by their ID: which tasks were added, removed or
+    /// changed from `before` to `after`
+    pub fn diff<'a>(before: &'a [Task], after: &'a [Task]) -> TaskDiff<'a> {
+        TaskDiff::new(before, after)
+    }
+
+    /// iterates over all tasks in list order
+    pub fn iter(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter()
+    }
+
+    /// iterates over the tasks that match the filter, in list order
+    pub fn tasks_matching_filter<'f>(
+        &'f self,
+        filter: &'f TaskFilter,
+    ) -> impl Iterator<Item = &'f Task> {
+        self.tasks.iter().filter(|t| t.matches_filter(filter))
+    }
+
+    /// iterates over the tasks for which the predicate returns true, in list order; nothing is
+    /// collected, the predicate is called while iterating
+    pub fn tasks_matching<F: Fn(&Task) -> bool>(
+        &self,
+        predicate: F,
+    ) -> impl Iterator<Item = &Task> {
+        self.tasks.iter().filter(move |t| predicate(t))
+    }
+
+    /// like tasks_matching, but the tasks can be changed
+    pub fn tasks_matching_mut<F: Fn(&Task) -> bool>(
+        &mut self,
+        predicate: F,
+    ) -> impl Iterator<Item = &mut Task> {
+        self.tasks.iter_mut().filter(move |t| predicate(t))
+    }
+
+    /// find Task with given id, if it exits, and returns index
+    pub fn find_id(&self, id: usize) -> Option<usize> {
+        self.tasks.iter().position(|t| t.get_id() == id)
+    }
+
+    /// return Task with given id, if it exists
+    pub fn at(&self, id: usize) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.get_id() == id)
+    }
+
+    /// return mutable Task with given id, if it exists
+    pub fn at_mut(&mut self, id: usize) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|t| t.get_id() == id)
+    }
+
+    /// the first pending task in list order (without sorting), e.g. the oldest one
+    pub fn first_pending(&self) -> Option<&Task> {
+        self.tasks.iter().find(|t| !t.get_completed())
+    }
+
+    /// the last pending task in list order
+    pub fn last_pending(&self) -> Option<&Task> {
+        self.tasks.iter().rfind(|t| !t.get_completed())
+    }
+
+    /// the n-th (0-based) pending task in list order, e.g. for paging through them
+    pub fn nth_pending(&self, n: usize) -> Option<&Task> {
+        self.tasks.iter().filter(|t| !t.get_completed()).nth(n)
+    }
+
+    /// the first completed task in list order
+    pub fn first_completed(&self) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.get_completed())
+    }
+
+    /// the last completed task in list order
+    pub fn last_completed(&self) -> Option<&Task> {
+        self.tasks.iter().rfind(|t| t.get_completed())
+    }
+}
+
+/* everything that's wrong with a task list: IDs that are 0 or used more than once and empty
+ * descriptions */
+fn task_list_violations(tasks: &[Task]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+    for task in tasks {
+        if task.get_id() == 0 {
+            problems.push(format!("'{}' has no ID", task.get_description()));
+        } else if !seen.insert(task.get_id()) {
+            problems.push(format!("ID {} is used more than once", task.get_id()));
+        }
+        if task.get_description().trim().is_empty() {
+            problems.push(format!("task #{} has an empty description", task.get_id()));
+        }
+    }
+    problems
+}
+
+/* the text cut down to at most max_chars characters at a word boundary, ending in "…" */
+fn shorten_description(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut short = String::new();
+    for word in text.split_whitespace() {
+        /* one character is left for the "…" */
+        if short.chars().count() + word.chars().count() + 1 >= max_chars {
+            break;
+        }
+        if !short.is_empty() {
+            short.push(' ');
+        }
+        short.push_str(word);
+    }
+    if short.is_empty() {
+        /* a single endless word */
+        short = text.chars().take(max_chars - 1).collect();
+    }
+    short.push('…');
+    short
+}
+
+/* escapes the characters that have a meaning in HTML */
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// splits a string into its lowercase character bigrams; strings shorter than two characters
+/// are represented by their single character
+fn bigrams(s: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = s.to_lowercase().chars().collect();
+    if chars.len() == 1 {
+        return HashSet::from([(chars[0], chars[0])]);
+    }
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// Jaccard similarity of the bigram sets of two strings (0.0 = nothing in common, 1.0 = equal)
+fn bigram_similarity(a: &str, b: &str) -> f32 {
+    let a = bigrams(a);
+    let b = bigrams(b);
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /* a manager for a task file in the given directory, with the given tasks saved in it */
+    fn manager_in(dir: &Path, descriptions: &[&str]) -> TaskManager {
+        let mut manager = TaskManager::new(dir.join("tasks.json"));
+        for description in descriptions {
+            manager.add_task(*description).unwrap();
+        }
+        manager.save_tasks().unwrap();
+        manager
+    }
+
+    #[test]
+    fn load_additional_rejects_main_and_loaded_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = dir.path().join("other.json");
+        manager_in(dir.path(), &["other task"]);
+        fs::rename(dir.path().join("tasks.json"), &other).unwrap();
+        let mut manager = manager_in(dir.path(), &["main task"]);
+
+        assert!(
+            manager
+                .load_additional(&dir.path().join("tasks.json"))
+                .is_err()
+        );
+        assert_eq!(manager.load_additional(&other).unwrap(), 1);
+        /* the same file by another path */
+        let again = dir.path().join(".").join("other.json");
+        assert!(manager.load_additional(&again).is_err());
+        assert_eq!(manager.iter().count(), 2);
+    }
+
+    #[test]
+    fn assign_task_rejects_blank_names() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.add_task("Write the report").unwrap();
+        assert!(matches!(
+            manager.assign_task(1, "  "),
+            Err(TaskError::Empty(_))
+        ));
+        manager.assign_task(1, " alice ").unwrap();
+        assert_eq!(manager.at(1).unwrap().get_assignee(), Some("alice"));
+    }
+
+    #[test]
+    fn group_by_context_uses_the_first_context() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.add_task("Buy milk @shop @town").unwrap();
+        manager.add_task("Write the report").unwrap();
+        manager.add_task("Call Bob @phone").unwrap();
+        let sections: Vec<(Option<String>, Vec<usize>)> = manager
+            .list_sections(&TaskFilter::default(), GroupBy::Context)
+            .into_iter()
+            .map(|(header, tasks)| (header, tasks.iter().map(|t| t.get_id()).collect()))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (Some("@phone:".to_string()), vec![3]),
+                (Some("@shop:".to_string()), vec![1]),
+                (Some("No context:".to_string()), vec![2]),
+            ]
+        );
+        assert_eq!("Context".parse::<GroupBy>().unwrap(), GroupBy::Context);
+    }
+
+    #[test]
+    fn restore_drops_unsaved_audit_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager_in(dir.path(), &[]);
+        manager.set_audit_log(Some(TaskAuditLog::new(dir.path().join("audit.log"))));
+        let empty = manager.snapshot();
+        manager.add_task("Write the report").unwrap();
+        manager.save_tasks().unwrap();
+
+        let saved = manager.snapshot();
+        manager.delete_task(1).unwrap();
+        manager.restore(saved);
+        /* the written entry stays, restoring the older snapshot can't take it back */
+        manager.restore(empty);
+        manager.add_task("Call Bob").unwrap();
+        manager.save_tasks().unwrap();
+
+        let operations: Vec<AuditOperation> = manager
+            .get_audit_log()
+            .unwrap()
+            .read_entries()
+            .unwrap()
+            .iter()
+            .map(|e| e.operation)
+            .collect();
+        assert_eq!(operations, vec![AuditOperation::Add, AuditOperation::Add]);
+    }
+
+    #[test]
+    fn counts_follow_the_changes() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        assert_eq!(manager.count_by_priority(), PriorityCounts::default());
+        assert_eq!(manager.count_with_due_date(), 0);
+        assert_eq!(manager.count_overdue(), 0);
+
+        manager.add_task("Write the report").unwrap();
+        manager.add_task("Call Bob").unwrap();
+        manager.add_task("Pay rent").unwrap();
+        manager.set_priority(2, Priority::High).unwrap();
+        manager.set_priority(3, Priority::Low).unwrap();
+        let today = Local::now().date_naive();
+        manager
+            .set_due(1, today.checked_sub_days(Days::new(2)))
+            .unwrap();
+        manager
+            .set_due(3, today.checked_add_days(Days::new(2)))
+            .unwrap();
+        let all = PriorityCounts {
+            low: 1,
+            medium: 1,
+            high: 1,
+        };
+        assert_eq!(manager.count_by_priority(), all);
+        assert_eq!(manager.count_by_priority_pending(), all);
+        assert_eq!(manager.count_with_due_date(), 2);
+        assert_eq!(manager.count_overdue(), 1);
+
+        /* completed tasks aren't pending and not overdue */
+        manager.complete_task(1).unwrap();
+        manager.complete_task(2).unwrap();
+        assert_eq!(manager.count_by_priority(), all);
+        assert_eq!(
+            manager.count_by_priority_pending(),
+            PriorityCounts {
+                low: 1,
+                medium: 0,
+                high: 0,
+            }
+        );
+        assert_eq!(manager.count_with_due_date(), 2);
+        assert_eq!(manager.count_overdue(), 0);
+
+        manager.delete_task(3).unwrap();
+        assert_eq!(manager.count_with_due_date(), 1);
+        assert_eq!(
+            manager.count_by_priority_pending(),
+            PriorityCounts::default()
+        );
+    }
+
+    #[test]
+    fn sorting_keeps_the_order_of_equal_tasks() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        for description in ["a", "b", "c", "d"] {
+            manager.add_task(description).unwrap();
+        }
+        manager.set_priority(2, Priority::High).unwrap();
+        manager.set_priority(4, Priority::High).unwrap();
+        let ids = |m: &TaskManager| m.iter().map(|t| t.get_id()).collect::<Vec<_>>();
+
+        manager.sort_by_urgency();
+        assert_eq!(ids(&manager), [2, 4, 1, 3]);
+        manager.sort_tasks(&[SortKey::Priority, SortKey::Id]);
+        assert_eq!(ids(&manager), [2, 4, 1, 3]);
+        manager.sort_stable_by_key(|_| 0);
+        assert_eq!(ids(&manager), [2, 4, 1, 3]);
+        manager.sort_tasks(&[SortKey::Id]);
+        assert_eq!(ids(&manager), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn huge_thresholds_are_never_stale() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.add_task("Write the report").unwrap();
+        manager.complete_task(1).unwrap();
+        let forever = Duration::from_secs(u64::MAX);
+        assert!(!manager.at(1).unwrap().is_stale(forever));
+        assert!(manager.stale_tasks(forever).is_empty());
+        assert_eq!(manager.purge_old_completed(forever), 0);
+        assert_eq!(manager.purge_old_completed(Duration::ZERO), 1);
+    }
+
+    #[test]
+    fn yaml_round_trip() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.add_task("Write the report: part 1").unwrap();
+        manager.add_task("Call Bob").unwrap();
+        manager
+            .set_due(2, NaiveDate::from_ymd_opt(2024, 11, 10))
+            .unwrap();
+        manager.complete_task(2).unwrap();
+        let yaml = manager.to_yaml().unwrap();
+        assert!(yaml.starts_with("---\n"));
+
+        let tasks = TaskManager::from_yaml(&yaml).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].get_description(), "Write the report: part 1");
+        assert_eq!(tasks[1].get_due(), NaiveDate::from_ymd_opt(2024, 11, 10));
+        assert!(tasks[1].get_completed());
+        for empty in ["", "---\n", "  \n"] {
+            assert!(TaskManager::from_yaml(empty).unwrap().is_empty());
+        }
+        assert!(TaskManager::from_yaml("- just: [text").is_err());
+    }
+
+    #[test]
+    fn yaml_and_json_are_detected_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager_in(dir.path(), &["Write the report"]);
+        manager.convert_to(StorageFormat::Yaml).unwrap();
+        let yaml_file = dir.path().join("tasks.yaml");
+        assert!(fs::read_to_string(&yaml_file).unwrap().starts_with("---"));
+
+        /* a YAML file with a .json name still loads, and so does JSON in a .yaml file */
+        let json_file = dir.path().join("tasks.json");
+        fs::copy(&yaml_file, &json_file).unwrap();
+        let mut manager = TaskManager::new(json_file.clone());
+        manager.load_tasks().unwrap();
+        assert_eq!(manager.iter().count(), 1);
+        let mut json = Vec::new();
+        manager.to_json_writer(&mut json).unwrap();
+        fs::write(&yaml_file, json).unwrap();
+        let mut manager = TaskManager::new(yaml_file);
+        manager.load_tasks().unwrap();
+        assert_eq!(
+            manager.iter().next().unwrap().get_description(),
+            "Write the report"
+        );
+    }
+
+    #[test]
+    fn lint_fixes() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        let long = format!("Write the report {}", "and more ".repeat(20));
+        manager.add_task(long.as_str()).unwrap();
+        manager.add_task("Call Bob").unwrap();
+        manager.complete_task(2).unwrap();
+        let full = manager.at(1).unwrap().get_description().to_string();
+
+        let chars = full.chars().count();
+        let fixed = manager
+            .fix_lint_warning(&LintWarning::LongDescription { id: 1, chars })
+            .unwrap();
+        assert!(fixed.is_some());
+        let task = manager.at(1).unwrap();
+        assert!(task.get_description().chars().count() <= MAX_DESCRIPTION_CHARS);
+        assert_eq!(task.get_notes(), Some(full.as_str()));
+
+        let old = LintWarning::OldCompleted { id: 2, days: 100 };
+        assert!(manager.fix_lint_warning(&old).unwrap().is_some());
+        assert!(manager.at(2).is_none());
+        let stale = LintWarning::Stale { id: 1, days: 100 };
+        assert_eq!(manager.fix_lint_warning(&stale).unwrap(), None);
+    }
+
+    #[test]
+    fn exact_duplicates_keep_the_lowest_id() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        for description in [
+            "Buy milk",
+            "Call Bob",
+            "buy MILK",
+            "Buy milk now",
+            "BUY MILK",
+        ] {
+            manager.add_task(description).unwrap();
+        }
+        manager.set_priority(3, Priority::High).unwrap();
+        assert_eq!(
+            manager.find_duplicates(DeduplicateStrategy::Exact),
+            [(3, 1), (5, 1)]
+        );
+        assert_eq!(manager.deduplicate(DeduplicateStrategy::Exact), 2);
+        let ids: Vec<usize> = manager.iter().map(|t| t.get_id()).collect();
+        assert_eq!(ids, [1, 2, 4]);
+        assert_eq!(manager.deduplicate(DeduplicateStrategy::Exact), 0);
+    }
+
+    #[test]
+    fn fuzzy_duplicates_keep_the_highest_priority() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        for description in [
+            "Write the quarterly report",
+            "Write the quarterly reports",
+            "Call Bob",
+            "Write the quarterly report!",
+        ] {
+            manager.add_task(description).unwrap();
+        }
+        manager.set_priority(2, Priority::High).unwrap();
+        /* nothing is that similar */
+        assert_eq!(manager.deduplicate(DeduplicateStrategy::Fuzzy(1.0)), 0);
+        assert_eq!(
+            manager.find_duplicates(DeduplicateStrategy::Fuzzy(0.8)),
+            [(1, 2), (4, 2)]
+        );
+        assert_eq!(manager.deduplicate(DeduplicateStrategy::Fuzzy(0.8)), 2);
+        let ids: Vec<usize> = manager.iter().map(|t| t.get_id()).collect();
+        assert_eq!(ids, [2, 3]);
+    }
+
+    #[test]
+    fn descriptions_are_sanitized_unless_turned_off() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        assert!(matches!(
+            manager.add_task("\t \t"),
+            Err(TaskError::ValidationErrors(_))
+        ));
+        let id = manager.add_task("  Ship\tit 🚀 ;").unwrap();
+        assert_eq!(manager.at(id).unwrap().get_description(), "Ship it 🚀");
+        manager.change_description(id, "Ship  it\n now.").unwrap();
+        assert_eq!(manager.at(id).unwrap().get_description(), "Ship it now");
+
+        let mut config = manager.get_config().clone();
+        config.auto_sanitize = false;
+        manager.set_config(config);
+        let raw = manager.add_task(" Call  Bob. ").unwrap();
+        assert_eq!(manager.at(raw).unwrap().get_description(), " Call  Bob. ");
+        assert_eq!(manager.sanitize_all(), [raw]);
+        assert_eq!(manager.at(raw).unwrap().get_description(), "Call Bob");
+        assert!(!manager.sanitize_task(raw).unwrap());
+    }
+
+    #[test]
+    fn tag_statistics() {
+        let stats = |total, completed, high_priority| TagStats {
+            total,
+            completed,
+            high_priority,
+        };
+        let mut manager = TaskManager::new("tasks.json".into());
+        assert!(manager.statistics_by_tag().is_empty());
+        /* no tags, an empty "+" and a plus inside a word aren't tags */
+        manager.add_task("Buy milk").unwrap();
+        manager.add_task("Call Bob + Alice, 1+1").unwrap();
+        assert!(manager.statistics_by_tag().is_empty());
+
+        manager
+            .add_task("Send invoice +work +billing +work")
+            .unwrap();
+        manager.add_task("Plan sprint +work @office").unwrap();
+        manager
+            .add_task("Book flight +travel @phone @office")
+            .unwrap();
+        manager.set_priority(3, Priority::High).unwrap();
+        manager.set_priority(4, Priority::High).unwrap();
+        manager.complete_task(3).unwrap();
+        let by_tag = manager.statistics_by_tag();
+        assert_eq!(by_tag.len(), 3);
+        assert_eq!(by_tag["work"], stats(2, 1, 2));
+        assert_eq!(by_tag["billing"], stats(1, 1, 1));
+        assert_eq!(by_tag["travel"], stats(1, 0, 0));
+
+        let by_context = manager.statistics_by_context();
+        assert_eq!(by_context.len(), 2);
+        assert_eq!(by_context["office"], stats(2, 0, 1));
+        assert_eq!(by_context["phone"], stats(1, 0, 0));
+    }
+
+    #[test]
+    fn compact_task_files_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        let mut tasks = vec![
+            Task::new_task("Write the report", 1, Priority::High),
+            Task::new_task("Call Bob", 2, Priority::Low),
+        ];
+        tasks[1].mark_completed();
+        let compact = JsonSerializer::compact().serialize(&tasks).unwrap();
+        assert!(!compact.contains(&b'\n'));
+        fs::write(&path, &compact).unwrap();
+
+        let mut manager = TaskManager::new(path.clone());
+        manager.load_tasks().unwrap();
+        assert_eq!(manager.iter().count(), 2);
+        assert_eq!(manager.at(1).unwrap().get_description(), "Write the report");
+        assert!(manager.at(2).unwrap().get_completed());
+
+        /* with json_pretty off the file stays compact when saved */
+        let mut config = manager.get_config().clone();
+        config.json_pretty = false;
+        manager.set_config(config);
+        manager.add_task("Pay rent").unwrap();
+        manager.save_tasks().unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(saved.lines().count(), 1);
+        let mut reloaded = TaskManager::new(path);
+        reloaded.load_tasks_with(JsonSerializer::pretty()).unwrap();
+        assert_eq!(reloaded.iter().count(), 3);
+    }
+
+    #[test]
+    fn html_has_a_row_per_task() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.add_task("Write the <b>report</b>").unwrap();
+        manager.add_task("Call Bob").unwrap();
+        manager.set_priority(2, Priority::High).unwrap();
+        manager.complete_task(2).unwrap();
+        let html = manager.to_html();
+
+        assert_eq!(html.matches("<tr class=").count(), 2);
+        assert!(html.contains(concat!(
+            "<tr class=\"pending\"><td>1</td>",
+            "<td data-sort=\"1\"><span class=\"badge prio-medium\">Medium</span></td>",
+            "<td>pending</td><td class=\"desc\">Write the &lt;b&gt;report&lt;/b&gt;</td>"
+        )));
+        assert!(html.contains(concat!(
+            "<tr class=\"completed\"><td>2</td>",
+            "<td data-sort=\"2\"><span class=\"badge prio-high\">High</span></td>",
+            "<td>done</td><td class=\"desc\">Call Bob</td>"
+        )));
+        assert!(html.contains("<footer>2 tasks, generated "));
+        assert!(!html.contains("http"));
+    }
+
+    #[test]
+    fn json_writer_and_reader_round_trip_in_memory() {
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.add_task("Write the report").unwrap();
+        manager.add_task("Call Bob").unwrap();
+        manager.complete_task(2).unwrap();
+        let mut buffer = Cursor::new(Vec::new());
+        manager.to_json_writer(&mut buffer).unwrap();
+
+        buffer.set_position(0);
+        let tasks = TaskManager::from_json_reader(buffer).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].get_description(), "Write the report");
+        assert_eq!(tasks[1].get_id(), 2);
+        assert!(tasks[1].get_completed());
+    }
+
+    #[test]
+    fn json_reader_accepts_empty_input_and_rejects_garbage() {
+        assert!(
+            TaskManager::from_json_reader(Cursor::new("  \n"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            TaskManager::from_json_reader(Cursor::new("[{")),
+            Err(TaskError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn high_priority_tasks_due_later_arent_for_today() {
+        let today = Local::now().date_naive();
+        let mut manager = TaskManager::new("tasks.json".into());
+        for desc in ["Urgent, due next week", "Urgent, no due date", "Due today"] {
+            manager.add_task(desc).unwrap();
+        }
+        manager.set_priority(1, Priority::High).unwrap();
+        manager
+            .set_due(1, today.checked_add_days(Days::new(7)))
+            .unwrap();
+        manager.set_priority(2, Priority::High).unwrap();
+        manager.set_due(3, Some(today)).unwrap();
+        let ids: Vec<usize> = manager
+            .tasks_for_today()
+            .iter()
+            .map(|t| t.get_id())
+            .collect();
+        assert_eq!(ids, [2, 3]);
+    }
+
+    #[test]
+    fn backups_leave_other_workspaces_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = TaskManager::new(dir.path().join("home.json"));
+        manager.add_task("Buy milk").unwrap();
+        manager.save_tasks().unwrap();
+        let mut config = manager.get_config().clone();
+        config.backup_keep = 1;
+        manager.set_config(config);
+        /* the task file of workspace "home_1" and an old backup of "home" */
+        fs::write(dir.path().join("home_1.json"), "[]").unwrap();
+        let old_backup = dir.path().join("home_2020-01-01T00:00:00.json");
+        fs::write(&old_backup, "[]").unwrap();
+
+        let backup = manager.backup(dir.path()).unwrap();
+        assert_eq!(manager.list_backups(dir.path()).unwrap(), [backup]);
+        assert!(!old_backup.exists());
+        assert!(dir.path().join("home_1.json").exists());
+
+        assert!(matches!(
+            manager.restore_backup(dir.path(), "../home_1"),
+            Err(TaskError::ArgumentMismatch(_))
+        ));
+    }
+
+    /* 1 has the subtasks 2 and 3, 3 has the subtask 4 */
+    fn manager_with_subtasks() -> TaskManager {
+        let mut manager = TaskManager::new("tasks.json".into());
+        for desc in [
+            "Plan the party",
+            "Book the venue",
+            "Invite guests",
+            "Send the invites",
+        ] {
+            manager.add_task(desc).unwrap();
+        }
+        manager.set_parent(2, Some(1)).unwrap();
+        manager.set_parent(3, Some(1)).unwrap();
+        manager.set_parent(4, Some(3)).unwrap();
+        manager
+    }
+
+    #[test]
+    fn complete_refuses_a_task_with_pending_subtasks() {
+        let mut manager = manager_with_subtasks();
+        match manager.complete_task(1) {
+            Err(TaskError::HasPendingChildren {
+                parent_id,
+                pending_child_ids,
+            }) => {
+                assert_eq!(parent_id, 1);
+                assert_eq!(pending_child_ids, [2, 3, 4]);
+            }
+            other => panic!("expected HasPendingChildren, got {:?}", other),
+        }
+        assert!(manager.iter().all(|t| !t.get_completed()));
+        /* the subtask without subtasks of its own can be completed */
+        manager.complete_task(4).unwrap();
+        assert!(manager.at(4).unwrap().get_completed());
+    }
+
+    #[test]
+    fn completed_subtasks_dont_block() {
+        let mut manager = manager_with_subtasks();
+        manager.complete_task(2).unwrap();
+        manager.complete_task(4).unwrap();
+        manager.complete_task(3).unwrap();
+        assert!(manager.pending_subtasks(1).is_empty());
+        manager.complete_task(1).unwrap();
+        assert!(manager.iter().all(|t| t.get_completed()));
+    }
+
+    #[test]
+    fn force_complete_cascades_to_all_subtasks() {
+        let mut manager = manager_with_subtasks();
+        manager.complete_task(2).unwrap();
+        let msg = manager.complete_task_with_subtasks(1).unwrap();
+        assert!(msg.ends_with(" and 2 subtasks"), "{}", msg);
+        assert!(manager.iter().all(|t| t.get_completed()));
+
+        /* only the subtree of the task is completed */
+        let mut manager = manager_with_subtasks();
+        manager.complete_task_with_subtasks(3).unwrap();
+        let completed: Vec<bool> = manager.iter().map(|t| t.get_completed()).collect();
+        assert_eq!(completed, [false, false, true, true]);
+    }
+
+    #[test]
+    fn complete_force_command() {
+        let mut manager = manager_with_subtasks();
+        assert!(matches!(
+            manager.apply_command_str("complete 1"),
+            Err(TaskError::HasPendingChildren { .. })
+        ));
+        manager.apply_command_str("complete 1 --force").unwrap();
+        assert!(manager.iter().all(|t| t.get_completed()));
+    }
+
+    #[test]
+    fn pending_children_error_message() {
+        let err = TaskError::HasPendingChildren {
+            parent_id: 1,
+            pending_child_ids: vec![2, 4],
+        };
+        assert_eq!(err.to_string(), "Task #1 has 2 pending subtasks (#2, #4)");
+        let err = TaskError::HasPendingChildren {
+            parent_id: 1,
+            pending_child_ids: vec![3],
+        };
+        assert_eq!(err.to_string(), "Task #1 has 1 pending subtask (#3)");
+    }
+
+    #[test]
+    fn set_parent_rejects_loops_and_unknown_tasks() {
+        let mut manager = manager_with_subtasks();
+        assert!(matches!(
+            manager.set_parent(1, Some(1)),
+            Err(TaskError::ArgumentMismatch(_))
+        ));
+        assert!(matches!(
+            manager.set_parent(1, Some(4)),
+            Err(TaskError::ArgumentMismatch(_))
+        ));
+        assert!(matches!(
+            manager.set_parent(2, Some(9)),
+            Err(TaskError::TaskNotFound(9))
+        ));
+        manager.set_parent(4, None).unwrap();
+        assert_eq!(manager.at(4).unwrap().get_parent_id(), None);
+        assert_eq!(manager.pending_subtasks(3), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn import_and_reindex_keep_parents() {
+        let mut source = manager_with_subtasks();
+        source.delete_task(2).unwrap();
+        let tasks: Vec<Task> = source.iter().cloned().collect();
+
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.add_task("Existing task").unwrap();
+        let ids = manager.import_tasks(tasks.clone());
+        assert_eq!(ids, [2, 3, 4]);
+        assert_eq!(manager.at(3).unwrap().get_parent_id(), Some(2));
+        assert_eq!(manager.at(4).unwrap().get_parent_id(), Some(3));
+        /* a parent that isn't imported is dropped */
+        manager.import_tasks(tasks[1..].to_vec());
+        assert_eq!(manager.at(5).unwrap().get_parent_id(), None);
+
+        source.reindex();
+        assert_eq!(source.at(2).unwrap().get_parent_id(), Some(1));
+        assert_eq!(source.at(3).unwrap().get_parent_id(), Some(2));
+    }
+
+    #[test]
+    fn deleting_a_parent_frees_its_subtasks() {
+        let mut manager = manager_with_subtasks();
+        manager.delete_task(1).unwrap();
+        assert_eq!(manager.at(2).unwrap().get_parent_id(), None);
+        assert_eq!(manager.at(3).unwrap().get_parent_id(), None);
+        /* the subtask of a subtask stays where it is */
+        assert_eq!(manager.at(4).unwrap().get_parent_id(), Some(3));
+        manager.add_task("Unrelated task").unwrap();
+        manager.reindex();
+        assert!(manager.pending_subtasks(1).is_empty());
+    }
+
+    #[test]
+    fn load_additional_keeps_parents_of_renumbered_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = dir.path().join("other.json");
+        let mut source = manager_with_subtasks();
+        source.set_file_path(other.clone());
+        source.save_tasks().unwrap();
+        let mut manager = manager_in(dir.path(), &["Main task", "Another main task"]);
+
+        manager.load_additional(&other).unwrap();
+        /* the tasks 1 to 4 of the other file became 3 to 6 */
+        assert_eq!(manager.at(4).unwrap().get_parent_id(), Some(3));
+        assert_eq!(manager.at(5).unwrap().get_parent_id(), Some(3));
+        assert_eq!(manager.at(6).unwrap().get_parent_id(), Some(5));
+        assert!(manager.pending_subtasks(1).is_empty());
+        manager.complete_task(1).unwrap();
+
+        /* saved with the IDs of the file */
+        manager.save_tasks().unwrap();
+        let saved = TaskManager::read_tasks(&other, StorageFormat::Json).unwrap();
+        let parents: Vec<Option<usize>> = saved.iter().map(Task::get_parent_id).collect();
+        assert_eq!(parents, [None, Some(1), Some(1), Some(3)]);
+    }
+
+    #[test]
+    fn tasks_for_today_selects_urgent_tasks_high_first() {
+        let today = Local::now().date_naive();
+        let yesterday = today.checked_sub_days(Days::new(1));
+        let mut manager = TaskManager::new("tasks.json".into());
+        for desc in [
+            "Overdue, low",
+            "Due today",
+            "No due date, high",
+            "No due date, medium",
+            "Overdue but done",
+        ] {
+            manager.add_task(desc).unwrap();
+        }
+        manager.set_priority(1, Priority::Low).unwrap();
+        manager.set_due(1, yesterday).unwrap();
+        manager.set_due(2, Some(today)).unwrap();
+        manager.set_priority(3, Priority::High).unwrap();
+        manager.set_due(5, yesterday).unwrap();
+        manager.complete_task(5).unwrap();
+        let ids: Vec<usize> = manager
+            .tasks_for_today()
+            .iter()
+            .map(|t| t.get_id())
+            .collect();
+        assert_eq!(ids, [3, 2, 1]);
+    }
+
+    #[test]
+    fn snoozed_tasks_are_neither_next_nor_for_today() {
+        let today = Local::now().date_naive();
+        let mut manager = TaskManager::new("tasks.json".into());
+        manager.add_task("Renew the passport").unwrap();
+        manager.add_task("Water the plants").unwrap();
+        manager.set_priority(1, Priority::High).unwrap();
+        manager
+            .snooze_task(1, today.checked_add_days(Days::new(3)).unwrap())
+            .unwrap();
+        assert_eq!(manager.next_task().unwrap().get_id(), 2);
+        assert!(manager.tasks_for_today().is_empty());
+
+        manager.set_due(2, Some(today)).unwrap();
+        manager
+            .snooze_task(2, today.checked_add_days(Days::new(1)).unwrap())
+            .unwrap();
+        assert!(manager.next_task().is_none());
+        assert!(manager.tasks_by_day(today, 7).values().all(Vec::is_empty));
     }
 }
@@ -0,0 +1,107 @@
+use super::task::{Priority, Task};
+use super::task_error::TaskError;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// A saved pattern for new tasks, e.g. "Weekly review {date}" with high priority.
+/// Tasks have no separate tags or context, so they are added to the description as
+/// "+tag" and "@context" words.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskTemplate {
+    pub name: String,
+    /// may contain {date} (the day the task is created) and {user}
+    pub description_template: String,
+    #[serde(default)]
+    pub default_priority: Option<Priority>,
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+    #[serde(default)]
+    pub default_context: Option<String>,
+}
+
+impl TaskTemplate {
+    /// A template with only a description; the name may contain letters, digits, '-' and '_'
+    pub fn new(name: &str, description_template: &str) -> Result<TaskTemplate, TaskError> {
+        check_template_name(name)?;
+        if description_template.trim().is_empty() {
+            return Err(TaskError::Empty("Template description".to_string()));
+        }
+        Ok(TaskTemplate {
+            name: name.to_string(),
+            description_template: description_template.trim().to_string(),
+            default_priority: None,
+            default_tags: Vec::new(),
+            default_context: None,
+        })
+    }
+
+    /// a template with the description and priority of the task
+    pub fn from_task(name: &str, task: &Task) -> Result<TaskTemplate, TaskError> {
+        let mut template = TaskTemplate::new(name, task.get_description())?;
+        template.default_priority = Some(task.priority());
+        Ok(template)
+    }
+
+    /// The description of a new task: the template (or the given override) with {date} and
+    /// {user} filled in, followed by "@context" and "+tag" for the context and the tags
+    pub fn render_description(
+        &self,
+        description_override: Option<&str>,
+        date: NaiveDate,
+        user: &str,
+    ) -> String {
+        let mut description = description_override
+            .unwrap_or(&self.description_template)
+            .replace("{date}", &date.format("%Y-%m-%d").to_string())
+            .replace("{user}", user);
+        if let Some(context) = &self.default_context {
+            description.push_str(&format!(" @{}", context.trim_start_matches('@')));
+        }
+        for tag in &self.default_tags {
+            description.push_str(&format!(" +{}", tag.trim_start_matches('+')));
+        }
+        description
+    }
+
+    /// Loads all templates from the given JSON file. A missing or empty file gives no templates.
+    pub fn load_all(path: &Path) -> Result<Vec<TaskTemplate>, TaskError> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(path)?;
+        if contents.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Writes the templates to the given JSON file, sorted by name; creates its directory
+    pub fn save_all(path: &Path, templates: &[TaskTemplate]) -> Result<(), TaskError> {
+        let mut templates = templates.to_vec();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&templates)?)?;
+        Ok(())
+    }
+}
+
+/* template names are typed on the command line, so keep them simple */
+fn check_template_name(name: &str) -> Result<(), TaskError> {
+    if name.is_empty() {
+        return Err(TaskError::Empty("Template name".to_string()));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(TaskError::ArgumentMismatch(format!(
+            "'{}' is not a valid template name, use only letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    Ok(())
+}
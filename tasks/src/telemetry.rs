@@ -0,0 +1,27 @@
+use super::task_error::TaskError;
+use std::fmt::Debug;
+
+/// Receives the errors a TaskManager runs into while loading or saving the task file, e.g. to
+/// send them to an error tracking service. Errors of single operations (an unknown ID, an
+/// empty description) are mistakes of the user and aren't reported.
+pub trait ErrorReporter: Debug + Send + Sync {
+    fn report_error(&self, err: &TaskError);
+}
+
+/// Ignores all errors; the default of every TaskManager
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopReporter;
+
+impl ErrorReporter for NoopReporter {
+    fn report_error(&self, _err: &TaskError) {}
+}
+
+/// Writes every error to stderr
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogReporter;
+
+impl ErrorReporter for LogReporter {
+    fn report_error(&self, err: &TaskError) {
+        eprintln!("[taskmaster] error: {}", err);
+    }
+}
@@ -0,0 +1,226 @@
+use super::task_error::TaskError;
+use colored::{ColoredString, Colorize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::fmt;
+use std::str::FromStr;
+
+/// A color of the theme: one of the 16 terminal colors ("red", "bright blue", ...), an RGB
+/// color ("#ff8800") or "none", which leaves the text as it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub Option<colored::Color>);
+
+impl Color {
+    pub const NONE: Color = Color(None);
+
+    /// the text in this color
+    pub fn paint(&self, text: &str) -> ColoredString {
+        match self.0 {
+            Some(color) => text.color(color),
+            None => text.normal(),
+        }
+    }
+}
+
+impl From<colored::Color> for Color {
+    fn from(color: colored::Color) -> Self {
+        Color(Some(color))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use colored::Color::*;
+        let name = match self.0 {
+            None => "none",
+            Some(Black) => "black",
+            Some(Red) => "red",
+            Some(Green) => "green",
+            Some(Yellow) => "yellow",
+            Some(Blue) => "blue",
+            Some(Magenta) => "magenta",
+            Some(Cyan) => "cyan",
+            Some(White) => "white",
+            Some(BrightBlack) => "bright black",
+            Some(BrightRed) => "bright red",
+            Some(BrightGreen) => "bright green",
+            Some(BrightYellow) => "bright yellow",
+            Some(BrightBlue) => "bright blue",
+            Some(BrightMagenta) => "bright magenta",
+            Some(BrightCyan) => "bright cyan",
+            Some(BrightWhite) => "bright white",
+            Some(TrueColor { r, g, b }) => return write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Color {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || {
+            TaskError::ArgumentMismatch(format!(
+                "'{}' is not a color, use e.g. red, bright blue, #ff8800 or none",
+                s
+            ))
+        };
+        if s.eq_ignore_ascii_case("none") {
+            return Ok(Color::NONE);
+        }
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return Err(invalid());
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+            return Ok(colored::Color::TrueColor {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            }
+            .into());
+        }
+        /* colored knows "bright red", the config may also say "bright_red" */
+        colored::Color::from_str(&s.replace('_', " "))
+            .map(Color::from)
+            .map_err(|_| invalid())
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Colors of the task list. In the config file, `theme` is either the name of a preset
+/// ("dark", "light" or "no-color") or an object with the colors to change; colors that aren't
+/// given are taken from the dark theme.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Theme {
+    pub high_priority_color: Color,
+    pub medium_priority_color: Color,
+    pub low_priority_color: Color,
+    /// the ✓ of completed tasks
+    pub completed_color: Color,
+    /// the task IDs in the list
+    pub index_color: Color,
+}
+
+impl Theme {
+    /// the colors taskmaster always had, for dark terminal backgrounds
+    pub fn dark() -> Theme {
+        Theme {
+            high_priority_color: colored::Color::Red.into(),
+            medium_priority_color: colored::Color::Yellow.into(),
+            low_priority_color: colored::Color::Green.into(),
+            completed_color: colored::Color::Green.into(),
+            index_color: colored::Color::Cyan.into(),
+        }
+    }
+
+    /// darker colors that stay readable on light backgrounds (no yellow or cyan)
+    pub fn light() -> Theme {
+        Theme {
+            high_priority_color: colored::Color::Red.into(),
+            medium_priority_color: colored::Color::Magenta.into(),
+            low_priority_color: colored::Color::Green.into(),
+            completed_color: colored::Color::Blue.into(),
+            index_color: colored::Color::Blue.into(),
+        }
+    }
+
+    /// no colors at all for the theme's parts of the list
+    pub fn no_color() -> Theme {
+        Theme {
+            high_priority_color: Color::NONE,
+            medium_priority_color: Color::NONE,
+            low_priority_color: Color::NONE,
+            completed_color: Color::NONE,
+            index_color: Color::NONE,
+        }
+    }
+
+    /// the preset with the given name: dark, light or no-color
+    pub fn preset(name: &str) -> Result<Theme, TaskError> {
+        match name.trim().to_lowercase().as_str() {
+            "dark" => Ok(Theme::dark()),
+            "light" => Ok(Theme::light()),
+            "no-color" | "no_color" | "none" => Ok(Theme::no_color()),
+            _ => Err(TaskError::ArgumentMismatch(format!(
+                "unknown theme '{}', expected dark, light or no-color",
+                name
+            ))),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+/* the colors of a theme object in the config; the missing ones come from the dark theme */
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeColors {
+    high_priority_color: Color,
+    medium_priority_color: Color,
+    low_priority_color: Color,
+    completed_color: Color,
+    index_color: Color,
+}
+
+impl Default for ThemeColors {
+    fn default() -> Self {
+        let dark = Theme::dark();
+        ThemeColors {
+            high_priority_color: dark.high_priority_color,
+            medium_priority_color: dark.medium_priority_color,
+            low_priority_color: dark.low_priority_color,
+            completed_color: dark.completed_color,
+            index_color: dark.index_color,
+        }
+    }
+}
+
+/* a theme in the config is a preset name or an object with colors. A visitor instead of an
+ * untagged enum, so a wrong color is reported as such and not as "no variant matched" */
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ThemeVisitor;
+
+        impl<'de> de::Visitor<'de> for ThemeVisitor {
+            type Value = Theme;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a theme name (dark, light, no-color) or an object with colors")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Theme, E> {
+                Theme::preset(name).map_err(E::custom)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Theme, A::Error> {
+                let colors = ThemeColors::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(Theme {
+                    high_priority_color: colors.high_priority_color,
+                    medium_priority_color: colors.medium_priority_color,
+                    low_priority_color: colors.low_priority_color,
+                    completed_color: colors.completed_color,
+                    index_color: colors.index_color,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(ThemeVisitor)
+    }
+}
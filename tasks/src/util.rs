@@ -0,0 +1,170 @@
+use super::task_error::TaskError;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use std::time::Duration;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Parses an age like "30d", "2w", "1m" (a month counts as 30 days) or "1y" (365 days).
+/// A number without unit counts as days. Ages that reach back before the earliest time chrono
+/// knows (about 262,000 years) are rejected.
+pub fn parse_duration(s: &str) -> Result<Duration, TaskError> {
+    let s = s.trim().to_lowercase();
+    let invalid = || {
+        TaskError::ArgumentMismatch(format!(
+            "'{}' is not a valid duration, use e.g. 30d, 2w, 1m or 1y",
+            s
+        ))
+    };
+    let (number, days_per_unit) = match s.char_indices().last() {
+        Some((i, 'd')) => (&s[..i], 1),
+        Some((i, 'w')) => (&s[..i], 7),
+        Some((i, 'm')) => (&s[..i], 30),
+        Some((i, 'y')) => (&s[..i], 365),
+        _ => (s.as_str(), 1),
+    };
+    let value: u64 = number.trim().parse().map_err(|_| invalid())?;
+    let too_long = || TaskError::ArgumentMismatch(format!("'{}' is too long", s));
+    let seconds = value
+        .checked_mul(days_per_unit)
+        .and_then(|days| days.checked_mul(SECONDS_PER_DAY))
+        .ok_or_else(too_long)?;
+    let duration = Duration::from_secs(seconds);
+    chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or_else(too_long)?;
+    Ok(duration)
+}
+
+/// Parses a point in time: a date like "2024-11-01" (midnight, local time), a full RFC 3339
+/// time like "2024-11-01T09:30:00Z", or an age like "7d" or "1w" (see parse_duration) that is
+/// counted back from now
+pub fn parse_datetime(s: &str) -> Result<DateTime<Utc>, TaskError> {
+    let s = s.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        /* midnight doesn't exist on days where the clock jumps forward at 0:00 */
+        return Ok(Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|| midnight.and_utc()));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let age = parse_duration(s).map_err(|_| {
+        TaskError::ArgumentMismatch(format!(
+            "'{}' is not a valid date, use e.g. 2024-11-01, 7d or 2w",
+            s
+        ))
+    })?;
+    chrono::Duration::from_std(age)
+        .ok()
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or_else(|| TaskError::ArgumentMismatch(format!("'{}' is too long ago", s)))
+}
+
+/// whole days of a duration, e.g. for "untouched for more than 14 days"
+pub fn whole_days(duration: Duration) -> u64 {
+    duration.as_secs() / SECONDS_PER_DAY
+}
+
+/// Checks that a link is an http:// or https:// URL that can be handed to the browser as it
+/// is: no whitespace, quotes, control characters or shell characters like `^&|<>`. A URL that
+/// needs them has to be percent-encoded.
+pub fn check_url(url: &str) -> Result<(), TaskError> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(TaskError::ArgumentMismatch(format!(
+            "'{}' is not a valid link, it has to start with http:// or https://",
+            url
+        )));
+    }
+    if let Some(c) = url
+        .chars()
+        .find(|c| c.is_whitespace() || c.is_control() || "\"'`^&|<>".contains(*c))
+    {
+        return Err(TaskError::ArgumentMismatch(format!(
+            "'{}' is not a valid link, it contains {:?} (percent-encode it)",
+            url, c
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_without_shell_characters() {
+        assert!(check_url("https://example.com/issues/42").is_ok());
+        assert!(check_url("http://example.com/?q=a%26b#top").is_ok());
+        for url in [
+            "ftp://example.com",
+            "example.com",
+            "https://x/?a=1&calc",
+            "https://x/a b",
+            "https://x/\"a\"",
+            "https://x/'a'",
+            "https://x/a^b",
+            "https://x/a|b",
+            "https://x/<a>",
+            "https://x/a\nb",
+            "https://x/a\u{7}",
+        ] {
+            assert!(check_url(url).is_err(), "{:?}", url);
+        }
+    }
+
+    #[test]
+    fn durations_in_range() {
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            Duration::from_secs(14 * SECONDS_PER_DAY)
+        );
+        assert_eq!(
+            parse_duration(" 3 ").unwrap(),
+            Duration::from_secs(3 * SECONDS_PER_DAY)
+        );
+        assert_eq!(
+            parse_duration("1000y").unwrap().as_secs(),
+            365_000 * SECONDS_PER_DAY
+        );
+        assert!(parse_duration("2x").is_err());
+    }
+
+    #[test]
+    fn datetimes_and_ages() {
+        let date = parse_datetime("2024-11-01").unwrap();
+        assert_eq!(
+            date.with_timezone(&Local).date_naive(),
+            NaiveDate::from_ymd_opt(2024, 11, 1).unwrap()
+        );
+        assert_eq!(
+            parse_datetime("2024-11-01T09:30:00Z").unwrap().to_rfc3339(),
+            "2024-11-01T09:30:00+00:00"
+        );
+        let week_ago = parse_datetime("1w").unwrap();
+        assert_eq!((Utc::now() - week_ago).num_days(), 7);
+        assert!(parse_datetime("yesterday").is_err());
+        for age in ["18446744073709551615d", "999999999y"] {
+            assert!(
+                matches!(parse_datetime(age), Err(TaskError::ArgumentMismatch(_))),
+                "{}",
+                age
+            );
+        }
+    }
+
+    #[test]
+    fn durations_out_of_range_are_errors() {
+        for age in ["18446744073709551615d", "999999999y", "300000y"] {
+            assert!(
+                matches!(parse_duration(age), Err(TaskError::ArgumentMismatch(_))),
+                "{}",
+                age
+            );
+        }
+    }
+}